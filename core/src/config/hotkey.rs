@@ -0,0 +1,63 @@
+/*
+ * @license
+ * Copyright 2025 a7mddra
+ * SPDX-License-Identifier: Apache-2.0
+ */
+
+use crate::{Error, Result};
+
+/// A global shortcut: modifiers plus one key.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Hotkey {
+    pub ctrl: bool,
+    pub alt: bool,
+    pub shift: bool,
+    /// Cmd on macOS, the Windows key elsewhere.
+    pub super_key: bool,
+    /// `A`-`Z`, `0`-`9` or `F1`-`F12`.
+    pub key: String,
+}
+
+impl Hotkey {
+    pub fn parse(value: &str) -> Result<Self> {
+        let invalid = || Error::Invalid(format!("Invalid hotkey '{}'", value));
+        let mut hotkey = Hotkey::default();
+        let mut parts: Vec<&str> = value.split('+').map(str::trim).collect();
+        let key = parts.pop().filter(|k| !k.is_empty()).ok_or_else(invalid)?;
+
+        for modifier in parts {
+            let flag = match modifier.to_ascii_lowercase().as_str() {
+                "ctrl" | "control" => &mut hotkey.ctrl,
+                "alt" | "option" => &mut hotkey.alt,
+                "shift" => &mut hotkey.shift,
+                "super" | "cmd" | "meta" | "win" => &mut hotkey.super_key,
+                _ => return Err(invalid()),
+            };
+            if *flag {
+                return Err(invalid());
+            }
+            *flag = true;
+        }
+        if !(hotkey.ctrl || hotkey.alt || hotkey.super_key) {
+            return Err(Error::Invalid(format!(
+                "Hotkey '{}' needs Ctrl, Alt or Super so it does not clash with typing",
+                value
+            )));
+        }
+
+        let key = key.to_ascii_uppercase();
+        let valid_key = match key.as_bytes() {
+            [c] => c.is_ascii_alphanumeric(),
+            [b'F', rest @ ..] => std::str::from_utf8(rest)
+                .ok()
+                .and_then(|n| n.parse::<u8>().ok())
+                .is_some_and(|n| (1..=12).contains(&n)),
+            _ => false,
+        };
+        if !valid_key {
+            return Err(invalid());
+        }
+        hotkey.key = key;
+        Ok(hotkey)
+    }
+}
@@ -0,0 +1,71 @@
+/*
+ * @license
+ * Copyright 2025 a7mddra
+ * SPDX-License-Identifier: Apache-2.0
+ */
+
+use serde::{Deserialize, Serialize};
+
+use crate::{Error, Result};
+
+/// Smallest `max_edge` accepted; below this models cannot read anything.
+pub const MIN_EDGE: u32 = 64;
+/// JPEG and WebP quality when none is set.
+pub const DEFAULT_QUALITY: u8 = 85;
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum OutputFormat {
+    /// Keep PNG, JPEG and WebP as they are; anything else becomes PNG.
+    Original,
+    Jpeg,
+    /// Lossy WebP, at `quality`.
+    Webp,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "camelCase", default)]
+pub struct PreprocessOptions {
+    /// Longest allowed edge in pixels; `None` keeps the original size.
+    pub max_edge: Option<u32>,
+    pub format: OutputFormat,
+    /// JPEG or WebP quality, 1 to 100. `None` means `DEFAULT_QUALITY`.
+    pub quality: Option<u8>,
+    pub grayscale: bool,
+    pub trim_borders: bool,
+}
+
+impl Default for PreprocessOptions {
+    fn default() -> Self {
+        Self {
+            max_edge: Some(2048),
+            format: OutputFormat::Original,
+            quality: None,
+            grayscale: false,
+            trim_borders: false,
+        }
+    }
+}
+
+impl PreprocessOptions {
+    pub fn validate(&self) -> Result<()> {
+        if let Some(quality) = self.quality {
+            if !(1..=100).contains(&quality) {
+                return Err(Error::Invalid(
+                    "Image quality must be between 1 and 100".into(),
+                ));
+            }
+        }
+        if self.max_edge.is_some_and(|edge| edge < MIN_EDGE) {
+            return Err(Error::Invalid(format!(
+                "Maximum image edge must be at least {}px",
+                MIN_EDGE
+            )));
+        }
+        Ok(())
+    }
+
+    pub fn output_quality(&self) -> u8 {
+        self.quality.unwrap_or(DEFAULT_QUALITY)
+    }
+}
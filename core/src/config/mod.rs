@@ -0,0 +1,349 @@
+/*
+ * @license
+ * Copyright 2025 a7mddra
+ * SPDX-License-Identifier: Apache-2.0
+ */
+
+//! The settings file shared by the app and the daemon.
+
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::fs;
+use std::path::Path;
+
+use crate::{Error, Result};
+
+mod hotkey;
+pub mod image;
+pub mod pii;
+pub mod search;
+
+pub use hotkey::Hotkey;
+pub use image::{OutputFormat, PreprocessOptions};
+pub use pii::{PiiKind, PiiMode, PiiPolicy};
+pub use search::SearchOptions;
+
+/// Plain JSON in [`crate::paths::config_dir`], so the daemon can read it
+/// without the app running.
+pub const SETTINGS_FILE: &str = "preferences.json";
+
+pub const SETTINGS_VERSION: u32 = 2;
+
+pub const DEFAULT_MODEL: &str = "gemini-2.5-flash";
+pub const DEFAULT_PROMPT: &str =
+    "analyze this image and explain it or discuss fixes about the issue it discribes.";
+pub const DEFAULT_HOTKEY: &str = "Super+Shift+A";
+
+/// Rewrites a settings document, in its JSON form, to the next version. It
+/// gets the config dir to read files the old version kept elsewhere.
+type Migration = fn(&mut Map<String, Value>, &Path);
+
+/// Upgrades a settings document from version `index` to `index + 1`. Append
+/// here and never edit a shipped entry.
+const MIGRATIONS: &[Migration] = &[fold_legacy_files, accept_terms_of_existing_files];
+
+/// Option files that predate this module, and the settings key each one
+/// moved to.
+const LEGACY_FILES: &[(&str, &str)] = &[
+    ("imagePreprocessing", "image_preprocessing.json"),
+    ("pii", "pii_policy.json"),
+    ("reverseSearch", "reverse_search.json"),
+];
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum Theme {
+    Dark,
+    Light,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "camelCase", default)]
+pub struct CaptureSettings {
+    /// Mute system audio while the capture overlay is up.
+    pub mute_audio: bool,
+}
+
+impl Default for CaptureSettings {
+    fn default() -> Self {
+        Self { mute_audio: true }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "camelCase", default)]
+pub struct Settings {
+    pub version: u32,
+    /// Set when the user accepts the terms during onboarding.
+    pub accepted_terms: bool,
+    pub model: String,
+    pub theme: Theme,
+    pub prompt: String,
+    /// Global capture shortcut, e.g. `Super+Shift+A`.
+    pub hotkey: String,
+    pub capture: CaptureSettings,
+    pub image_preprocessing: PreprocessOptions,
+    pub pii: PiiPolicy,
+    pub reverse_search: SearchOptions,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            version: SETTINGS_VERSION,
+            accepted_terms: false,
+            model: DEFAULT_MODEL.to_string(),
+            theme: Theme::Dark,
+            prompt: DEFAULT_PROMPT.to_string(),
+            hotkey: DEFAULT_HOTKEY.to_string(),
+            capture: CaptureSettings::default(),
+            image_preprocessing: PreprocessOptions::default(),
+            pii: PiiPolicy::default(),
+            reverse_search: SearchOptions::default(),
+        }
+    }
+}
+
+impl Settings {
+    pub fn validate(&self) -> Result<()> {
+        if self.model.trim().is_empty() {
+            return Err(Error::Invalid("Model must not be empty".into()));
+        }
+        Hotkey::parse(&self.hotkey)?;
+        self.image_preprocessing.validate()?;
+        self.pii.validate()?;
+        self.reverse_search.validate()
+    }
+
+    /// Applies a partial update: objects in `patch` are merged key by key,
+    /// anything else replaces the current value. The version is not
+    /// writable.
+    pub fn patched(&self, patch: Value) -> Result<Settings> {
+        let mut doc = serde_json::to_value(self).map_err(invalid)?;
+        merge(&mut doc, patch);
+
+        let mut settings: Settings = serde_json::from_value(doc).map_err(invalid)?;
+        settings.version = SETTINGS_VERSION;
+        settings.validate()?;
+        Ok(settings)
+    }
+}
+
+/// Reads the settings in `dir`, upgrading older versions in place. Without a
+/// settings file the defaults are returned and nothing is written.
+pub fn load(dir: &Path) -> Result<Settings> {
+    let path = dir.join(SETTINGS_FILE);
+    let existing = match fs::read_to_string(&path) {
+        Ok(json) => Some(json),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => None,
+        Err(e) => return Err(Error::io("Failed to read settings")(e)),
+    };
+
+    let mut doc = match &existing {
+        Some(json) => match serde_json::from_str(json) {
+            Ok(Value::Object(doc)) => doc,
+            Ok(_) => return Err(invalid("not a JSON object")),
+            Err(e) => return Err(invalid(e)),
+        },
+        None => Map::new(),
+    };
+
+    let version = doc.get("version").and_then(Value::as_u64).unwrap_or(0) as usize;
+    if version > MIGRATIONS.len() {
+        return Err(Error::UnsupportedVersion {
+            found: version as u64,
+            supported: SETTINGS_VERSION,
+        });
+    }
+    for migrate in &MIGRATIONS[version..] {
+        migrate(&mut doc, dir);
+    }
+    doc.insert("version".into(), SETTINGS_VERSION.into());
+
+    let settings: Settings = serde_json::from_value(Value::Object(doc)).map_err(invalid)?;
+    settings.validate()?;
+
+    if existing.is_some() && version < MIGRATIONS.len() {
+        println!(
+            "Migrated settings from version {} to {}",
+            version, SETTINGS_VERSION
+        );
+        save(dir, &settings)?;
+    }
+    Ok(settings)
+}
+
+pub fn save(dir: &Path, settings: &Settings) -> Result<()> {
+    fs::create_dir_all(dir).map_err(Error::io("Failed to write settings"))?;
+    let json = serde_json::to_string_pretty(settings).map_err(invalid)?;
+    // Write then rename so the daemon never reads a half-written file.
+    let tmp = dir.join(format!("{}.tmp", SETTINGS_FILE));
+    fs::write(&tmp, json).map_err(Error::io("Failed to write settings"))?;
+    fs::rename(&tmp, dir.join(SETTINGS_FILE)).map_err(Error::io("Failed to write settings"))
+}
+
+/// v0 -> v1: the unversioned `{ model, theme, prompt }` file gains a version,
+/// and the separate image, PII and reverse search option files move in.
+fn fold_legacy_files(doc: &mut Map<String, Value>, dir: &Path) {
+    for (key, file) in LEGACY_FILES {
+        if doc.contains_key(*key) {
+            continue;
+        }
+        let legacy = fs::read_to_string(dir.join(file))
+            .ok()
+            .and_then(|json| serde_json::from_str::<Value>(&json).ok());
+        if let Some(value @ Value::Object(_)) = legacy {
+            doc.insert(key.to_string(), value);
+        }
+    }
+}
+
+/// v1 -> v2: agreement to the terms was implied by the file existing, and is
+/// now the `acceptedTerms` field.
+fn accept_terms_of_existing_files(doc: &mut Map<String, Value>, dir: &Path) {
+    if dir.join(SETTINGS_FILE).exists() {
+        doc.insert("acceptedTerms".into(), true.into());
+    }
+}
+
+fn invalid(reason: impl std::fmt::Display) -> Error {
+    Error::Invalid(format!("Invalid settings: {}", reason))
+}
+
+fn merge(target: &mut Value, patch: Value) {
+    match (target, patch) {
+        (Value::Object(target), Value::Object(patch)) => {
+            for (key, value) in patch {
+                merge(target.entry(key).or_insert(Value::Null), value);
+            }
+        }
+        (target, patch) => *target = patch,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use std::path::PathBuf;
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!(
+            "spatialshot-settings-{}-{}",
+            name,
+            std::process::id()
+        ));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn defaults_without_writing_a_file() {
+        let dir = temp_dir("defaults");
+        assert_eq!(load(&dir).unwrap(), Settings::default());
+        assert!(!dir.join(SETTINGS_FILE).exists());
+
+        // Saving settings does not accept the terms.
+        save(&dir, &Settings::default()).unwrap();
+        assert!(!load(&dir).unwrap().accepted_terms);
+    }
+
+    #[test]
+    fn migrates_unversioned_preferences_and_legacy_files() {
+        let dir = temp_dir("migrate");
+        fs::write(
+            dir.join(SETTINGS_FILE),
+            r#"{"model":"gemini-2.5-pro","theme":"light","prompt":"hi"}"#,
+        )
+        .unwrap();
+        fs::write(
+            dir.join("image_preprocessing.json"),
+            r#"{"format":"jpeg","quality":70}"#,
+        )
+        .unwrap();
+        fs::write(dir.join("pii_policy.json"), r#"{"mode":"review"}"#).unwrap();
+
+        let settings = load(&dir).unwrap();
+        assert_eq!(settings.version, SETTINGS_VERSION);
+        assert_eq!(settings.model, "gemini-2.5-pro");
+        assert!(settings.accepted_terms);
+        assert_eq!(settings.theme, Theme::Light);
+        assert_eq!(settings.hotkey, DEFAULT_HOTKEY);
+        assert_eq!(settings.image_preprocessing.format, OutputFormat::Jpeg);
+        assert_eq!(settings.image_preprocessing.quality, Some(70));
+        assert_eq!(settings.pii.mode, PiiMode::Review);
+
+        let written: Value =
+            serde_json::from_str(&fs::read_to_string(dir.join(SETTINGS_FILE)).unwrap()).unwrap();
+        assert_eq!(written["version"], json!(SETTINGS_VERSION));
+        assert_eq!(written["pii"]["mode"], json!("review"));
+        assert_eq!(load(&dir).unwrap(), settings);
+    }
+
+    #[test]
+    fn refuses_newer_versions() {
+        let dir = temp_dir("newer");
+        fs::write(dir.join(SETTINGS_FILE), r#"{"version":99}"#).unwrap();
+        assert!(matches!(
+            load(&dir),
+            Err(Error::UnsupportedVersion { found: 99, .. })
+        ));
+    }
+
+    #[test]
+    fn patches_nested_fields_and_validates() {
+        let settings = Settings::default();
+        let patched = settings
+            .patched(json!({
+                "theme": "light",
+                "version": 7,
+                "imagePreprocessing": { "grayscale": true }
+            }))
+            .unwrap();
+        assert_eq!(patched.theme, Theme::Light);
+        assert_eq!(patched.version, SETTINGS_VERSION);
+        assert!(patched.image_preprocessing.grayscale);
+        assert_eq!(patched.image_preprocessing.max_edge, Some(2048));
+
+        assert!(settings.patched(json!({ "model": " " })).is_err());
+        assert!(settings.patched(json!({ "theme": "sepia" })).is_err());
+        assert!(settings
+            .patched(json!({ "imagePreprocessing": { "quality": 0 } }))
+            .is_err());
+        assert!(settings
+            .patched(json!({ "imagePreprocessing": { "format": "webp" } }))
+            .is_ok());
+        assert!(settings
+            .patched(json!({ "imagePreprocessing": { "format": "webp", "quality": 70 } }))
+            .is_ok());
+        assert!(settings
+            .patched(json!({ "reverseSearch": { "region": "USA" } }))
+            .is_err());
+    }
+
+    #[test]
+    fn parses_hotkeys() {
+        assert_eq!(
+            Hotkey::parse("Super+Shift+A").unwrap(),
+            Hotkey {
+                shift: true,
+                super_key: true,
+                key: "A".into(),
+                ..Default::default()
+            }
+        );
+        assert_eq!(Hotkey::parse("ctrl+alt+f12").unwrap().key, "F12");
+        for invalid in [
+            "A",
+            "Shift+A",
+            "Ctrl+Ctrl+A",
+            "Ctrl+",
+            "Hyper+A",
+            "Ctrl+F13",
+            "Ctrl+AB",
+        ] {
+            assert!(Hotkey::parse(invalid).is_err(), "{}", invalid);
+        }
+    }
+}
@@ -0,0 +1,99 @@
+/*
+ * @license
+ * Copyright 2025 a7mddra
+ * SPDX-License-Identifier: Apache-2.0
+ */
+
+use serde::{Deserialize, Serialize};
+
+use crate::{Error, Result};
+
+/// Tesseract language used when none is configured.
+pub const DEFAULT_LANGUAGE: &str = "eng";
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[serde(rename_all = "camelCase")]
+pub enum PiiKind {
+    Email,
+    Phone,
+    ApiKey,
+    IpAddress,
+    CreditCard,
+}
+
+impl PiiKind {
+    pub const ALL: [PiiKind; 5] = [
+        PiiKind::Email,
+        PiiKind::Phone,
+        PiiKind::ApiKey,
+        PiiKind::IpAddress,
+        PiiKind::CreditCard,
+    ];
+
+    /// `count` findings of this kind in words, e.g. "2 phone numbers".
+    pub fn describe(self, count: usize) -> String {
+        let (one, many) = match self {
+            PiiKind::Email => ("email address", "email addresses"),
+            PiiKind::Phone => ("phone number", "phone numbers"),
+            PiiKind::ApiKey => ("API key", "API keys"),
+            PiiKind::IpAddress => ("IP address", "IP addresses"),
+            PiiKind::CreditCard => ("card number", "card numbers"),
+        };
+        format!("{} {}", count, if count == 1 { one } else { many })
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum PiiMode {
+    /// No check.
+    Off,
+    /// Report findings but send anyway.
+    Warn,
+    /// Block sending until the user approves the image.
+    Review,
+    /// Redact findings automatically and send the redacted copy.
+    Redact,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "camelCase", default)]
+pub struct PiiPolicy {
+    pub mode: PiiMode,
+    pub kinds: Vec<PiiKind>,
+    /// OCR language used for the scan.
+    pub language: String,
+}
+
+impl PiiPolicy {
+    pub fn validate(&self) -> Result<()> {
+        validate_language(&self.language)
+    }
+}
+
+impl Default for PiiPolicy {
+    fn default() -> Self {
+        Self {
+            mode: PiiMode::Off,
+            kinds: PiiKind::ALL.to_vec(),
+            language: DEFAULT_LANGUAGE.to_string(),
+        }
+    }
+}
+
+/// Language codes are passed straight to Tesseract, e.g. `eng` or `eng+deu`.
+pub fn validate_language(language: &str) -> Result<()> {
+    let valid = !language.is_empty()
+        && !language.starts_with('+')
+        && language
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '+');
+    if valid {
+        Ok(())
+    } else {
+        Err(Error::Invalid(format!(
+            "Invalid OCR language: {}",
+            language
+        )))
+    }
+}
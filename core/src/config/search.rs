@@ -0,0 +1,43 @@
+/*
+ * @license
+ * Copyright 2025 a7mddra
+ * SPDX-License-Identifier: Apache-2.0
+ */
+
+use serde::{Deserialize, Serialize};
+
+use crate::{Error, Result};
+
+pub const DEFAULT_ENGINE: &str = "lens";
+
+/// Reverse image search defaults. Engine ids are checked by the app, which
+/// owns the engine registry.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+#[serde(rename_all = "camelCase", default)]
+pub struct SearchOptions {
+    /// Engine used when a search names none.
+    pub engine: Option<String>,
+    /// Overrides the system language and region.
+    pub language: Option<String>,
+    pub region: Option<String>,
+}
+
+impl SearchOptions {
+    pub fn engine(&self) -> &str {
+        self.engine.as_deref().unwrap_or(DEFAULT_ENGINE)
+    }
+
+    pub fn validate(&self) -> Result<()> {
+        let two_letters = |code: &Option<String>| {
+            code.as_deref().map_or(true, |c| {
+                c.len() == 2 && c.chars().all(|c| c.is_ascii_alphabetic())
+            })
+        };
+        if !two_letters(&self.language) || !two_letters(&self.region) {
+            return Err(Error::Invalid(
+                "Search language and region must be two-letter codes".into(),
+            ));
+        }
+        Ok(())
+    }
+}
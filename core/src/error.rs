@@ -0,0 +1,63 @@
+/*
+ * @license
+ * Copyright 2025 a7mddra
+ * SPDX-License-Identifier: Apache-2.0
+ */
+
+use std::fmt;
+use std::io;
+
+pub type Result<T, E = Error> = std::result::Result<T, E>;
+
+#[derive(Debug)]
+pub enum Error {
+    /// A file operation failed; `action` says which, e.g. "Failed to read
+    /// settings".
+    Io {
+        action: &'static str,
+        source: io::Error,
+    },
+    /// A value that does not parse or fails validation.
+    Invalid(String),
+    /// Settings written by a newer build than this one.
+    UnsupportedVersion { found: u64, supported: u32 },
+    /// The OS has no directory of this kind, e.g. no home directory.
+    MissingDir(&'static str),
+}
+
+impl Error {
+    pub(crate) fn io(action: &'static str) -> impl FnOnce(io::Error) -> Error {
+        move |source| Error::Io { action, source }
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Io { action, source } => write!(f, "{}: {}", action, source),
+            Error::Invalid(message) => f.write_str(message),
+            Error::UnsupportedVersion { found, supported } => write!(
+                f,
+                "Settings version {} is newer than this app supports ({})",
+                found, supported
+            ),
+            Error::MissingDir(kind) => write!(f, "Could not resolve the {} directory", kind),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Io { source, .. } => Some(source),
+            _ => None,
+        }
+    }
+}
+
+/// The app's commands report errors as strings.
+impl From<Error> for String {
+    fn from(error: Error) -> Self {
+        error.to_string()
+    }
+}
@@ -0,0 +1,262 @@
+/*
+ * @license
+ * Copyright 2025 a7mddra
+ * SPDX-License-Identifier: Apache-2.0
+ */
+
+//! Messages to the app. The daemon starts the app with a request on its
+//! command line. A later launch hands its request to the app that is already
+//! running, over a loopback connection, instead of opening a second copy.
+
+use serde::{Deserialize, Serialize};
+use std::ffi::OsString;
+use std::fs::{self, OpenOptions};
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::{Ipv4Addr, SocketAddr, TcpListener, TcpStream};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use crate::{Error, Result};
+
+/// Chromium flag the daemon passes when starting the app on Linux and
+/// macOS, kept for the Electron builds. The Tauri app accepts and ignores
+/// it.
+pub const NO_SANDBOX: &str = "--no-sandbox";
+
+/// Upper bound on one handoff, which only carries paths and short strings.
+const MAX_MESSAGE_BYTES: u64 = 64 * 1024;
+
+const HANDOFF_TIMEOUT: Duration = Duration::from_secs(2);
+
+const UNREACHABLE: &str = "Failed to reach the running app";
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum AppRequest {
+    /// Show the app without an image.
+    Show,
+    /// Open a screenshot the capture tool just wrote.
+    Open { image: PathBuf },
+}
+
+impl AppRequest {
+    /// The arguments that carry this request, after the program name.
+    pub fn to_args(&self) -> Vec<OsString> {
+        let mut args = Vec::new();
+        if let AppRequest::Open { image } = self {
+            args.push(image.clone().into_os_string());
+        }
+        if cfg!(any(target_os = "linux", target_os = "macos")) {
+            args.push(NO_SANDBOX.into());
+        }
+        args
+    }
+}
+
+/// A launch passed on to the running app, with the command-line overrides
+/// that came with it.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct Handoff {
+    pub request: AppRequest,
+    pub prompt: Option<String>,
+    pub model: Option<String>,
+}
+
+/// The contents of the instance file: where the running app listens and the
+/// token a handoff must present.
+#[derive(Serialize, Deserialize)]
+struct Instance {
+    port: u16,
+    token: String,
+}
+
+#[derive(Serialize, Deserialize)]
+struct Envelope {
+    token: String,
+    handoff: Handoff,
+}
+
+/// Passes `handoff` to the app recorded in `instance_file`. Fails when no
+/// app is running or it does not answer, in which case the caller should
+/// start normally.
+pub fn send(instance_file: &Path, handoff: &Handoff) -> Result<()> {
+    let json = fs::read_to_string(instance_file).map_err(Error::io("No running instance"))?;
+    let instance: Instance = serde_json::from_str(&json)
+        .map_err(|e| Error::Invalid(format!("Invalid instance file: {}", e)))?;
+
+    let address = SocketAddr::from((Ipv4Addr::LOCALHOST, instance.port));
+    let stream =
+        TcpStream::connect_timeout(&address, HANDOFF_TIMEOUT).map_err(Error::io(UNREACHABLE))?;
+    stream
+        .set_read_timeout(Some(HANDOFF_TIMEOUT))
+        .map_err(Error::io(UNREACHABLE))?;
+
+    let envelope = Envelope {
+        token: instance.token,
+        handoff: handoff.clone(),
+    };
+    let mut line = serde_json::to_string(&envelope).map_err(|e| Error::Invalid(e.to_string()))?;
+    line.push('\n');
+    (&stream)
+        .write_all(line.as_bytes())
+        .map_err(Error::io(UNREACHABLE))?;
+
+    let mut reply = String::new();
+    BufReader::new(&stream)
+        .read_line(&mut reply)
+        .map_err(Error::io(UNREACHABLE))?;
+    if reply.trim() == "ok" {
+        Ok(())
+    } else {
+        Err(Error::Invalid("The running app refused the request".into()))
+    }
+}
+
+/// Records this process in `instance_file` and calls `on_handoff`, on a
+/// background thread, for each launch handed to it. `token` must be
+/// unguessable: it is what stops other local processes from driving the
+/// app.
+pub fn listen<F>(instance_file: &Path, token: String, on_handoff: F) -> Result<()>
+where
+    F: Fn(Handoff) + Send + 'static,
+{
+    let listener = TcpListener::bind((Ipv4Addr::LOCALHOST, 0))
+        .map_err(Error::io("Failed to listen for other launches"))?;
+    let port = listener
+        .local_addr()
+        .map_err(Error::io("Failed to listen for other launches"))?
+        .port();
+
+    let json = serde_json::to_string(&Instance {
+        port,
+        token: token.clone(),
+    })
+    .map_err(|e| Error::Invalid(e.to_string()))?;
+    write_private(instance_file, &json).map_err(Error::io("Failed to write instance file"))?;
+
+    std::thread::spawn(move || {
+        for stream in listener.incoming().flatten() {
+            if let Some(handoff) = receive(&stream, &token) {
+                on_handoff(handoff);
+            }
+        }
+    });
+    Ok(())
+}
+
+/// Removes `instance_file` if it still points at the listener that holds
+/// `token`, so a newer instance's file is left alone.
+pub fn release(instance_file: &Path, token: &str) {
+    let ours = fs::read_to_string(instance_file)
+        .ok()
+        .and_then(|json| serde_json::from_str::<Instance>(&json).ok())
+        .is_some_and(|instance| instance.token == token);
+    if ours {
+        let _ = fs::remove_file(instance_file);
+    }
+}
+
+fn receive(stream: &TcpStream, token: &str) -> Option<Handoff> {
+    stream.set_read_timeout(Some(HANDOFF_TIMEOUT)).ok()?;
+    let mut line = String::new();
+    BufReader::new(stream.take(MAX_MESSAGE_BYTES))
+        .read_line(&mut line)
+        .ok()?;
+    let envelope: Envelope = serde_json::from_str(&line).ok()?;
+
+    let accepted = envelope.token == token;
+    let reply: &[u8] = if accepted { b"ok\n" } else { b"denied\n" };
+    let _ = (&*stream).write_all(reply);
+    accepted.then_some(envelope.handoff)
+}
+
+fn write_private(path: &Path, contents: &str) -> std::io::Result<()> {
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    let mut options = OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    options.open(path)?.write_all(contents.as_bytes())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::mpsc;
+
+    fn instance_file(name: &str) -> PathBuf {
+        let path = std::env::temp_dir().join(format!(
+            "spatialshot-ipc-{}-{}.json",
+            name,
+            std::process::id()
+        ));
+        let _ = fs::remove_file(&path);
+        path
+    }
+
+    fn handoff() -> Handoff {
+        Handoff {
+            request: AppRequest::Open {
+                image: PathBuf::from("/tmp/capture 1.png"),
+            },
+            prompt: Some("what is this?".into()),
+            model: None,
+        }
+    }
+
+    #[test]
+    fn carries_requests_as_arguments() {
+        let args = AppRequest::Open {
+            image: PathBuf::from("/tmp/capture 1.png"),
+        }
+        .to_args();
+        assert_eq!(args[0], "/tmp/capture 1.png");
+        assert_eq!(
+            args.len(),
+            AppRequest::Show.to_args().len() + 1,
+            "only the image is positional"
+        );
+    }
+
+    #[test]
+    fn hands_off_to_the_running_instance() {
+        let path = instance_file("handoff");
+        assert!(send(&path, &handoff()).is_err());
+
+        let (tx, rx) = mpsc::channel();
+        listen(&path, "secret".into(), move |handoff| {
+            tx.send(handoff).unwrap();
+        })
+        .unwrap();
+
+        send(&path, &handoff()).unwrap();
+        assert_eq!(rx.recv_timeout(HANDOFF_TIMEOUT).unwrap(), handoff());
+
+        release(&path, "other");
+        assert!(path.exists());
+        release(&path, "secret");
+        assert!(!path.exists());
+    }
+
+    #[test]
+    fn rejects_a_wrong_token() {
+        let path = instance_file("token");
+        let (tx, rx) = mpsc::channel();
+        listen(&path, "secret".into(), move |handoff| {
+            tx.send(handoff).unwrap();
+        })
+        .unwrap();
+
+        let json = fs::read_to_string(&path).unwrap();
+        fs::write(&path, json.replace("secret", "guess")).unwrap();
+        assert!(send(&path, &handoff()).is_err());
+        assert!(rx.recv_timeout(Duration::from_millis(200)).is_err());
+        let _ = fs::remove_file(&path);
+    }
+}
@@ -0,0 +1,15 @@
+/*
+ * @license
+ * Copyright 2025 a7mddra
+ * SPDX-License-Identifier: Apache-2.0
+ */
+
+//! Code shared by the app and the daemon: the settings schema, where files
+//! live on disk, and the messages the two binaries exchange.
+
+pub mod config;
+pub mod error;
+pub mod ipc;
+pub mod paths;
+
+pub use error::{Error, Result};
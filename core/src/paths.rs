@@ -0,0 +1,155 @@
+/*
+ * @license
+ * Copyright 2025 a7mddra
+ * SPDX-License-Identifier: Apache-2.0
+ */
+
+//! Where Spatialshot keeps its files. The per-user directories match what
+//! Tauri resolves for the app's identifier, so the daemon and the app agree
+//! on them without the app running.
+
+use std::path::{Path, PathBuf};
+
+use crate::{Error, Result};
+
+/// The app's bundle identifier, which names its per-user directories.
+pub const APP_ID: &str = "spatialshot";
+
+pub fn config_dir() -> Result<PathBuf> {
+    app_dir(dirs::config_dir(), "config")
+}
+
+pub fn data_dir() -> Result<PathBuf> {
+    app_dir(dirs::data_dir(), "data")
+}
+
+pub fn cache_dir() -> Result<PathBuf> {
+    app_dir(dirs::cache_dir(), "cache")
+}
+
+/// Per-user scratch space for locks and sockets, falling back to the temp
+/// dir where the OS has no runtime dir.
+pub fn runtime_dir() -> PathBuf {
+    dirs::runtime_dir().unwrap_or_else(std::env::temp_dir)
+}
+
+/// Held by the daemon while a capture is running, so a second hotkey press
+/// does not start another one.
+pub fn capture_lock_file() -> PathBuf {
+    runtime_dir().join(format!("spatialshot_daemon_{}.lock", user_name()))
+}
+
+/// Written by a running app so later launches can hand their request to it.
+/// See [`crate::ipc::listen`].
+pub fn instance_file() -> PathBuf {
+    runtime_dir().join(format!("spatialshot_app_{}.json", user_name()))
+}
+
+fn user_name() -> String {
+    std::env::var("USER") // Unix
+        .or_else(|_| std::env::var("USERNAME")) // Windows
+        .unwrap_or_else(|_| "uid".to_string())
+}
+
+fn app_dir(base: Option<PathBuf>, kind: &'static str) -> Result<PathBuf> {
+    base.map(|dir| dir.join(APP_ID))
+        .ok_or(Error::MissingDir(kind))
+}
+
+/// The installed binaries, located from the directory the daemon runs in.
+/// The app and the capture tool ship next to it, or on macOS inside the same
+/// bundle.
+#[derive(Clone, Debug)]
+pub struct InstallLayout {
+    bin_dir: PathBuf,
+}
+
+impl InstallLayout {
+    pub fn new(bin_dir: impl Into<PathBuf>) -> Self {
+        Self {
+            bin_dir: bin_dir.into(),
+        }
+    }
+
+    /// The layout around the running executable.
+    pub fn current() -> Result<Self> {
+        let exe = std::env::current_exe().map_err(Error::io("Failed to locate executable"))?;
+        let bin_dir = exe.parent().ok_or(Error::MissingDir("executable"))?;
+        Ok(Self::new(bin_dir))
+    }
+
+    pub fn bin_dir(&self) -> &Path {
+        &self.bin_dir
+    }
+
+    pub fn capture_binary(&self) -> PathBuf {
+        if cfg!(target_os = "macos") {
+            // Contents/MacOS/daemon -> Contents/Resources/Capture/capture
+            let contents = self.bin_dir.parent().unwrap_or(&self.bin_dir);
+            contents.join("Resources").join("Capture").join("capture")
+        } else if cfg!(windows) {
+            self.bin_dir.join("Capture").join("capture.exe")
+        } else {
+            self.bin_dir.join("capture").join("capture")
+        }
+    }
+
+    pub fn app_binary(&self) -> PathBuf {
+        if cfg!(windows) {
+            self.bin_dir.join("App").join("spatialshot.exe")
+        } else {
+            self.bin_dir.join("app").join("spatialshot")
+        }
+    }
+
+    /// The enclosing `.app` bundle, which macOS launches with `open -a`.
+    pub fn app_bundle(&self) -> Option<&Path> {
+        self.bin_dir
+            .ancestors()
+            .take(3)
+            .find(|dir| dir.extension().is_some_and(|ext| ext == "app"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn app_dirs_are_named_after_the_identifier() {
+        if let Ok(dir) = config_dir() {
+            assert!(dir.ends_with(APP_ID));
+        }
+        let lock = capture_lock_file();
+        assert!(lock.starts_with(runtime_dir()));
+        assert!(lock
+            .file_name()
+            .unwrap()
+            .to_string_lossy()
+            .starts_with("spatialshot_daemon_"));
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn finds_binaries_next_to_the_daemon() {
+        let layout = InstallLayout::new("/opt/spatialshot");
+        assert_eq!(
+            layout.capture_binary(),
+            Path::new("/opt/spatialshot/capture/capture")
+        );
+        assert_eq!(
+            layout.app_binary(),
+            Path::new("/opt/spatialshot/app/spatialshot")
+        );
+        assert_eq!(layout.app_bundle(), None);
+    }
+
+    #[test]
+    fn finds_the_enclosing_bundle() {
+        let layout = InstallLayout::new("/Applications/Spatialshot.app/Contents/MacOS");
+        assert_eq!(
+            layout.app_bundle(),
+            Some(Path::new("/Applications/Spatialshot.app"))
+        );
+    }
+}
@@ -6,6 +6,8 @@
 
 use anyhow::{anyhow, Context, Result};
 use regex::Regex;
+use spatialshot_core::ipc::AppRequest;
+use spatialshot_core::paths::InstallLayout;
 use std::io::{BufRead, BufReader};
 use std::path::{Path, PathBuf};
 use std::process::{Command, Stdio};
@@ -27,14 +29,12 @@ pub fn run_capture(capture_path: &PathBuf) -> Result<PathBuf> {
     let path_regex = Regex::new(r#"([a-zA-Z]:\\[^ \n\r]+|/[^ \n\r]+)"#).unwrap();
     let mut detected_path: Option<PathBuf> = None;
 
-    for line in reader.lines() {
-        if let Ok(content) = line {
-            if let Some(caps) = path_regex.captures(&content) {
-                let p = PathBuf::from(&caps[0]);
-                if p.extension().map_or(false, |ext| ext == "png") {
-                    detected_path = Some(p);
-                    break;
-                }
+    for content in reader.lines().map_while(Result::ok) {
+        if let Some(caps) = path_regex.captures(&content) {
+            let p = PathBuf::from(&caps[0]);
+            if p.extension().is_some_and(|ext| ext == "png") {
+                detected_path = Some(p);
+                break;
             }
         }
     }
@@ -45,24 +45,19 @@ pub fn run_capture(capture_path: &PathBuf) -> Result<PathBuf> {
     detected_path.ok_or_else(|| anyhow!("Capture finished but no valid PNG path was output"))
 }
 
-pub fn spawn_electron(bin_dir: &Path, image_path: &PathBuf) -> Result<()> {
+pub fn spawn_electron(layout: &InstallLayout, image_path: &Path) -> Result<()> {
+    let request = AppRequest::Open {
+        image: image_path.to_path_buf(),
+    };
+
     #[cfg(target_os = "macos")]
     {
-        let mut app_bundle = None;
-        for ancestor in bin_dir.ancestors().take(3) {
-            if ancestor.extension().map_or(false, |e| e == "app") {
-                app_bundle = Some(ancestor);
-                break;
-            }
-        }
-
-        if let Some(bundle) = app_bundle {
+        if let Some(bundle) = layout.app_bundle() {
             Command::new("open")
                 .arg("-a")
                 .arg(bundle)
                 .arg("--args")
-                .arg(image_path)
-                .arg("--no-sandbox")
+                .args(request.to_args())
                 .spawn()
                 .context("Failed to open macOS bundle")?;
             return Ok(());
@@ -71,21 +66,9 @@ pub fn spawn_electron(bin_dir: &Path, image_path: &PathBuf) -> Result<()> {
 
     #[cfg(not(target_os = "macos"))]
     {
-        let electron_executable = if cfg!(target_os = "windows") {
-            bin_dir.join("App").join("spatialshot.exe")
-        } else {
-            bin_dir.join("app").join("spatialshot")
-        };
-
-        let mut cmd = Command::new(electron_executable);
-
-        cmd.arg(image_path);
-
-        if cfg!(target_os = "linux") {
-            cmd.arg("--no-sandbox");
-        }
-
-        cmd.stdin(Stdio::null())
+        Command::new(layout.app_binary())
+            .args(request.to_args())
+            .stdin(Stdio::null())
             .stdout(Stdio::null())
             .stderr(Stdio::inherit())
             .spawn()
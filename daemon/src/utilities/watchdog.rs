@@ -51,7 +51,7 @@ fn get_monitor_count() -> i32 {
     }
 }
 
-#[cfg(all(target_os = "linux"))]
+#[cfg(target_os = "linux")]
 fn get_monitor_count() -> i32 {
     let output = Command::new("xrandr").arg("--listmonitors").output().ok();
     if let Some(out) = output {
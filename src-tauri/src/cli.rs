@@ -0,0 +1,445 @@
+/*
+ * @license
+ * Copyright 2025 a7mddra
+ * SPDX-License-Identifier: Apache-2.0
+ */
+
+//! The command line: `spatialshot [image] [--prompt ..] [--model ..]`, with
+//! `--from-clipboard` or `--stdin` as other image sources and
+//! `--ask .. --print` for a one-shot answer on stdout without a window.
+//! `spatialshot batch <inputs>.. --prompt .. --output ..` answers the same
+//! prompt for many images.
+
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use tauri::{AppHandle, Emitter, Manager};
+
+use crate::commands::chat;
+use crate::services::batch::{self, BatchOptions, BatchSummary};
+use crate::services::clipboard::image as clipboard_image;
+use crate::services::image;
+use crate::services::llm::config::{self, GEMINI_PROVIDER_ID};
+use crate::services::llm::{Message, Part, Role};
+use crate::services::pii;
+use crate::state::AppState;
+use spatialshot_core::ipc::{AppRequest, Handoff};
+
+/// What this invocation asks for.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Invocation {
+    Launch(LaunchOptions),
+    Batch(BatchOptions),
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ImageSource {
+    Path(PathBuf),
+    Clipboard,
+    Stdin,
+}
+
+/// Prompt and model given on the command line. They apply to this session
+/// only and are not saved to the settings.
+#[derive(Serialize, Clone, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct LaunchOverrides {
+    pub prompt: Option<String>,
+    pub model: Option<String>,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct LaunchOptions {
+    pub image: Option<ImageSource>,
+    pub overrides: LaunchOverrides,
+    pub new_window: bool,
+    /// Question for a headless run, set only with `--print`.
+    pub ask: Option<String>,
+    /// Model provider for a headless run.
+    pub provider: String,
+}
+
+impl LaunchOptions {
+    /// Reads the values `tauri-plugin-cli` matched: strings for options that
+    /// take a value, booleans for flags.
+    pub fn from_args(args: &HashMap<String, Value>) -> Result<Self, String> {
+        let text = |name: &str| {
+            args.get(name)
+                .and_then(Value::as_str)
+                .filter(|value| !value.trim().is_empty())
+                .map(str::to_string)
+        };
+        let flag = |name: &str| args.get(name).and_then(Value::as_bool).unwrap_or(false);
+
+        let sources: Vec<ImageSource> = [
+            text("image").map(|path| ImageSource::Path(PathBuf::from(path))),
+            flag("from-clipboard").then_some(ImageSource::Clipboard),
+            flag("stdin").then_some(ImageSource::Stdin),
+        ]
+        .into_iter()
+        .flatten()
+        .collect();
+        if sources.len() > 1 {
+            return Err("Give only one of an image path, --from-clipboard or --stdin".into());
+        }
+
+        let mut options = LaunchOptions {
+            image: sources.into_iter().next(),
+            overrides: LaunchOverrides {
+                prompt: text("prompt"),
+                model: text("model"),
+            },
+            new_window: flag("new-window"),
+            ask: None,
+            provider: text("provider").unwrap_or_else(|| GEMINI_PROVIDER_ID.to_string()),
+        };
+
+        match (text("ask"), flag("print")) {
+            (Some(_), _) if options.overrides.prompt.is_some() => {
+                return Err("Give either --prompt or --ask, not both".into());
+            }
+            (Some(question), true) => {
+                if options.image.is_none() {
+                    return Err("--ask needs an image path, --from-clipboard or --stdin".into());
+                }
+                options.ask = Some(question);
+            }
+            // Without --print the question opens the app as its prompt.
+            (Some(question), false) => options.overrides.prompt = Some(question),
+            (None, true) => return Err("--print needs a question from --ask".into()),
+            (None, false) => {}
+        }
+        if text("provider").is_some() && options.ask.is_none() {
+            return Err("--provider only applies to --ask with --print".into());
+        }
+        Ok(options)
+    }
+
+    pub fn is_headless(&self) -> bool {
+        self.ask.is_some()
+    }
+
+    /// What to send to an app that is already running, or `None` when this
+    /// launch needs its own process: a new window was asked for, or the
+    /// image comes from this process's clipboard read or stdin.
+    pub fn handoff(&self) -> Option<Handoff> {
+        if self.new_window || self.is_headless() {
+            return None;
+        }
+        let request = match &self.image {
+            None => AppRequest::Show,
+            // The running app has its own working directory.
+            Some(ImageSource::Path(path)) => AppRequest::Open {
+                image: std::env::current_dir()
+                    .map(|dir| dir.join(path))
+                    .unwrap_or_else(|_| path.clone()),
+            },
+            Some(ImageSource::Clipboard | ImageSource::Stdin) => return None,
+        };
+        Some(Handoff {
+            request,
+            prompt: self.overrides.prompt.clone(),
+            model: self.overrides.model.clone(),
+        })
+    }
+}
+
+/// Reads the values of the `batch` subcommand.
+pub fn batch_options(args: &HashMap<String, Value>) -> Result<BatchOptions, String> {
+    let text = |name: &str| {
+        args.get(name)
+            .and_then(Value::as_str)
+            .filter(|value| !value.trim().is_empty())
+            .map(str::to_string)
+    };
+    let number = |name: &str| {
+        text(name)
+            .map(|value| {
+                value
+                    .parse::<u32>()
+                    .map_err(|_| format!("--{} must be a whole number", name))
+            })
+            .transpose()
+    };
+
+    let inputs: Vec<String> = match args.get("inputs") {
+        Some(Value::Array(values)) => values
+            .iter()
+            .filter_map(Value::as_str)
+            .map(str::to_string)
+            .collect(),
+        Some(Value::String(value)) => vec![value.clone()],
+        _ => Vec::new(),
+    };
+    if inputs.is_empty() {
+        return Err("batch needs at least one file, directory or pattern".into());
+    }
+    let prompt = text("prompt").ok_or("batch needs a --prompt")?;
+    let output = text("output").ok_or("batch needs an --output file")?;
+    let provider = text("provider").unwrap_or_else(|| GEMINI_PROVIDER_ID.to_string());
+
+    let mut options = BatchOptions::new(inputs, prompt, provider, PathBuf::from(output));
+    options.model = text("model");
+    if let Some(concurrency) = number("concurrency")? {
+        if concurrency == 0 {
+            return Err("--concurrency must be at least 1".into());
+        }
+        options.concurrency = concurrency as usize;
+    }
+    if let Some(retries) = number("retries")? {
+        options.retries = retries;
+    }
+    Ok(options)
+}
+
+/// Makes the image from `source` the current image.
+pub fn load_image(app: &AppHandle, source: &ImageSource) -> Result<(), String> {
+    let bytes = match source {
+        ImageSource::Path(path) => return image::load_image_path(app, &path.to_string_lossy()),
+        ImageSource::Clipboard => clipboard_image::read_png()?,
+        ImageSource::Stdin => {
+            let mut bytes = Vec::new();
+            std::io::stdin()
+                .read_to_end(&mut bytes)
+                .map_err(|e| format!("Failed to read stdin: {}", e))?;
+            bytes
+        }
+    };
+    image::process_bytes_internal(bytes, &app.state::<AppState>()).map(|_| ())
+}
+
+/// Applies a launch handed over by a later `spatialshot` invocation and
+/// brings the window forward.
+pub fn apply_handoff(app: &AppHandle, handoff: Handoff) {
+    let overrides = LaunchOverrides {
+        prompt: handoff.prompt,
+        model: handoff.model,
+    };
+    *app.state::<AppState>().launch_overrides.lock() = overrides.clone();
+    let _ = app.emit("launch-overrides", &overrides);
+
+    if let AppRequest::Open { image } = handoff.request {
+        if let Err(e) = load_image(app, &ImageSource::Path(image)) {
+            eprintln!("Failed to open handed-off image: {}", e);
+        }
+    }
+    if let Some(window) = app.get_webview_window("main") {
+        let _ = window.unminimize();
+        let _ = window.set_focus();
+    }
+}
+
+/// Answers `options.ask` about the image without opening a window, prints
+/// the reply to stdout and exits: 0 on success, 1 on failure.
+pub fn run_headless(app: AppHandle, options: LaunchOptions) {
+    tauri::async_runtime::spawn_blocking(move || {
+        let code = match ask(&app, &options) {
+            Ok(answer) => {
+                println!("{}", answer);
+                0
+            }
+            Err(e) => {
+                eprintln!("{}", e);
+                1
+            }
+        };
+        app.exit(code);
+    });
+}
+
+/// Runs a batch without opening a window, reporting progress on stderr.
+/// Exits 0 when every image was answered, 1 otherwise.
+pub fn run_batch(app: AppHandle, options: BatchOptions) {
+    tauri::async_runtime::spawn_blocking(move || {
+        let code = match batch(&app, &options) {
+            Ok(summary) => {
+                println!(
+                    "{} answered, {} failed, {} already done. Results in {}",
+                    summary.succeeded,
+                    summary.failed,
+                    summary.skipped,
+                    options.output.display()
+                );
+                i32::from(summary.failed > 0)
+            }
+            Err(e) => {
+                eprintln!("{}", e);
+                1
+            }
+        };
+        app.exit(code);
+    });
+}
+
+fn batch(app: &AppHandle, options: &BatchOptions) -> Result<BatchSummary, String> {
+    let state = app.state::<AppState>();
+    let (model, preprocessing, pii_policy) = {
+        let settings = state.settings.lock();
+        (
+            options
+                .model
+                .clone()
+                .unwrap_or_else(|| settings.model.clone()),
+            settings.image_preprocessing.clone(),
+            settings.pii.clone(),
+        )
+    };
+    let backend = config::load_config(app, &options.provider)?.open()?;
+    let files = batch::expand_inputs(&options.inputs)?;
+
+    // Images go through the same preprocessing and PII policy as a chat,
+    // without being stored or shown in the app.
+    let prepare = |path: &Path| {
+        let bytes = batch::preprocess_file(path, &preprocessing)?;
+        pii::screen(&bytes, &pii_policy)
+    };
+    batch::run(backend.as_ref(), &model, &files, options, &prepare)
+}
+
+fn ask(app: &AppHandle, options: &LaunchOptions) -> Result<String, String> {
+    let source = options.image.as_ref().ok_or("No image given")?;
+    let question = options.ask.clone().ok_or("No question given")?;
+    load_image(app, source)?;
+
+    let state = app.state::<AppState>();
+    let (mime_type, data) = image::current_image_base64(&state)?;
+    let bytes = image::current_image_bytes(&state)?;
+    let model = match &options.overrides.model {
+        Some(model) => model.clone(),
+        None => state.settings.lock().model.clone(),
+    };
+    // Fails early on a provider that is not set up.
+    config::load_config(app, &options.provider)?;
+
+    let handle = state.chats.create(&options.provider, &model, None);
+    let message = Message {
+        role: Role::User,
+        parts: vec![
+            Part::Image { mime_type, data },
+            Part::Text { text: question },
+        ],
+    };
+    chat::first_turn(app, &handle, message, bytes, false)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn parse(args: Value) -> Result<LaunchOptions, String> {
+        let args: HashMap<String, Value> = serde_json::from_value(args).unwrap();
+        LaunchOptions::from_args(&args)
+    }
+
+    #[test]
+    fn reads_an_image_with_overrides() {
+        let options = parse(json!({
+            "image": "shot.png",
+            "prompt": "what is this?",
+            "model": "gemini-2.5-pro",
+            "new-window": false,
+            "print": false,
+            "ask": null
+        }))
+        .unwrap();
+        assert_eq!(options.image, Some(ImageSource::Path("shot.png".into())));
+        assert_eq!(options.overrides.prompt.as_deref(), Some("what is this?"));
+        assert_eq!(options.overrides.model.as_deref(), Some("gemini-2.5-pro"));
+        assert!(!options.is_headless());
+
+        let handoff = options.handoff().unwrap();
+        match handoff.request {
+            AppRequest::Open { image } => assert!(image.is_absolute()),
+            other => panic!("unexpected request {:?}", other),
+        }
+    }
+
+    #[test]
+    fn ask_with_print_runs_headless() {
+        let options = parse(json!({ "stdin": true, "ask": "why?", "print": true })).unwrap();
+        assert!(options.is_headless());
+        assert_eq!(options.image, Some(ImageSource::Stdin));
+        assert_eq!(options.provider, GEMINI_PROVIDER_ID);
+        assert_eq!(options.handoff(), None);
+
+        let options = parse(json!({
+            "stdin": true,
+            "ask": "why?",
+            "print": true,
+            "provider": "ollama"
+        }))
+        .unwrap();
+        assert_eq!(options.provider, "ollama");
+
+        let options = parse(json!({ "image": "a.png", "ask": "why?" })).unwrap();
+        assert!(!options.is_headless());
+        assert_eq!(options.overrides.prompt.as_deref(), Some("why?"));
+    }
+
+    #[test]
+    fn keeps_launches_that_need_their_own_process() {
+        assert_eq!(
+            parse(json!({ "from-clipboard": true })).unwrap().handoff(),
+            None
+        );
+        assert_eq!(
+            parse(json!({ "image": "a.png", "new-window": true }))
+                .unwrap()
+                .handoff(),
+            None
+        );
+        assert_eq!(
+            parse(json!({})).unwrap().handoff().unwrap().request,
+            AppRequest::Show
+        );
+    }
+
+    #[test]
+    fn rejects_conflicting_arguments() {
+        for args in [
+            json!({ "image": "a.png", "stdin": true }),
+            json!({ "from-clipboard": true, "stdin": true }),
+            json!({ "image": "a.png", "print": true }),
+            json!({ "ask": "why?", "print": true }),
+            json!({ "image": "a.png", "ask": "why?", "prompt": "hi" }),
+            json!({ "image": "a.png", "provider": "ollama" }),
+        ] {
+            assert!(parse(args.clone()).is_err(), "{}", args);
+        }
+    }
+
+    #[test]
+    fn reads_batch_arguments() {
+        let parse = |args: Value| {
+            let args: HashMap<String, Value> = serde_json::from_value(args).unwrap();
+            batch_options(&args)
+        };
+
+        let options = parse(json!({
+            "inputs": ["shots/", "*.png"],
+            "prompt": "Describe",
+            "output": "out.jsonl",
+            "concurrency": "8",
+            "retries": "0"
+        }))
+        .unwrap();
+        assert_eq!(options.inputs, ["shots/", "*.png"]);
+        assert_eq!(options.provider, GEMINI_PROVIDER_ID);
+        assert_eq!(options.model, None);
+        assert_eq!(options.concurrency, 8);
+        assert_eq!(options.retries, 0);
+
+        for args in [
+            json!({ "inputs": [], "prompt": "Describe", "output": "out.jsonl" }),
+            json!({ "inputs": ["a.png"], "output": "out.jsonl" }),
+            json!({ "inputs": ["a.png"], "prompt": "Describe" }),
+            json!({ "inputs": ["a.png"], "prompt": "D", "output": "o", "concurrency": "0" }),
+            json!({ "inputs": ["a.png"], "prompt": "D", "output": "o", "retries": "many" }),
+        ] {
+            assert!(parse(args.clone()).is_err(), "{}", args);
+        }
+    }
+}
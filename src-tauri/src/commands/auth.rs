@@ -5,25 +5,49 @@
  */
 
 use std::fs::{self, File};
-use std::sync::atomic::Ordering;
-use tauri::{AppHandle, State};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use tauri::{AppHandle, Emitter, State};
 
 use crate::services::auth;
-use crate::services::security;
+use crate::services::avatar;
+use crate::services::oidc::{self, ProviderConfig, GOOGLE_PROVIDER_ID};
 use crate::state::AppState;
 use crate::utils::get_app_config_dir;
 
-#[tauri::command]
-pub async fn start_google_auth(app: AppHandle, state: State<'_, AppState>) -> Result<(), String> {
-    if state.auth_running.load(Ordering::SeqCst) {
-        return Err("Authentication already in progress".into());
-    }
+const DEFAULT_AUTH_TIMEOUT_SECS: u64 = 300;
 
-    state.auth_running.store(true, Ordering::SeqCst);
-    let auth_lock = state.auth_running.clone();
+#[tauri::command]
+pub async fn start_google_auth(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    timeout_secs: Option<u64>,
+) -> Result<(), String> {
+    start_auth(app, state, GOOGLE_PROVIDER_ID.to_string(), timeout_secs).await
+}
 
+#[tauri::command]
+pub async fn start_auth(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    provider: String,
+    timeout_secs: Option<u64>,
+) -> Result<(), String> {
+    let running =
+        RunningGuard::acquire(&state.auth_running).ok_or("Authentication already in progress")?;
+    state.auth_cancel.store(false, Ordering::SeqCst);
+    let cancel_flag = state.auth_cancel.clone();
+    let timeout = Duration::from_secs(timeout_secs.unwrap_or(DEFAULT_AUTH_TIMEOUT_SECS));
+
+    let _ = app.emit("auth-started", serde_json::json!({ "provider": provider }));
+
+    let app_handle = app.clone();
     let result = tauri::async_runtime::spawn_blocking(move || {
-        let config_dir = get_app_config_dir(&app);
+        // Released when the flow ends, even if this command's future is
+        // dropped first.
+        let _running = running;
+        let config_dir = get_app_config_dir(&app_handle);
         if !config_dir.exists() {
             match fs::create_dir_all(&config_dir) {
                 Ok(_) => {}
@@ -31,13 +55,46 @@ pub async fn start_google_auth(app: AppHandle, state: State<'_, AppState>) -> Re
             }
         }
 
-        auth::start_google_auth_flow(app, config_dir)
+        auth::start_auth_flow(app_handle, config_dir, &provider, timeout, &cancel_flag)
     })
-    .await;
+    .await
+    .map_err(|e| e.to_string())
+    .and_then(|r| r);
 
-    auth_lock.store(false, Ordering::SeqCst);
+    if let Err(e) = &result {
+        if state.auth_cancel.swap(false, Ordering::SeqCst) {
+            let _ = app.emit("auth-cancelled", ());
+        } else {
+            let _ = app.emit("auth-failed", e);
+        }
+    }
 
-    result.map_err(|e| e.to_string())?
+    result
+}
+
+/// Holds `auth_running` for one sign-in and clears it when dropped, so no
+/// exit path can leave it set.
+struct RunningGuard(Arc<AtomicBool>);
+
+impl RunningGuard {
+    fn acquire(flag: &Arc<AtomicBool>) -> Option<Self> {
+        flag.compare_exchange(false, true, Ordering::SeqCst, Ordering::SeqCst)
+            .ok()
+            .map(|_| Self(flag.clone()))
+    }
+}
+
+impl Drop for RunningGuard {
+    fn drop(&mut self) {
+        self.0.store(false, Ordering::SeqCst);
+    }
+}
+
+#[tauri::command]
+pub fn cancel_google_auth(state: State<AppState>) {
+    if state.auth_running.load(Ordering::SeqCst) {
+        state.auth_cancel.store(true, Ordering::SeqCst);
+    }
 }
 
 #[tauri::command]
@@ -45,21 +102,52 @@ pub async fn logout(app: AppHandle) -> Result<(), String> {
     tauri::async_runtime::spawn_blocking(move || {
         let config_dir = get_app_config_dir(&app);
 
-        let _ = fs::remove_file(config_dir.join("profile.json")).ok();
+        auth::sign_out(&app, &config_dir);
         Ok(())
     })
     .await
     .map_err(|e| e.to_string())?
 }
 
+#[tauri::command]
+pub async fn get_access_token(app: AppHandle) -> Result<String, String> {
+    tauri::async_runtime::spawn_blocking(move || {
+        let config_dir = get_app_config_dir(&app);
+        auth::get_access_token(&app, &config_dir)
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+#[tauri::command]
+pub fn list_auth_providers(app: AppHandle) -> Vec<serde_json::Value> {
+    let config_dir = get_app_config_dir(&app);
+
+    oidc::load_providers(&config_dir)
+        .into_iter()
+        .map(|p| serde_json::json!({ "id": p.id, "name": p.name, "issuer": p.issuer }))
+        .collect()
+}
+
+#[tauri::command]
+pub fn save_auth_provider(app: AppHandle, provider: ProviderConfig) -> Result<(), String> {
+    oidc::save_provider(&get_app_config_dir(&app), provider)
+}
+
+#[tauri::command]
+pub fn remove_auth_provider(app: AppHandle, id: String) -> Result<(), String> {
+    oidc::remove_provider(&get_app_config_dir(&app), &id)
+}
+
 #[tauri::command]
 pub fn get_user_data(app: AppHandle) -> serde_json::Value {
     let config_dir = get_app_config_dir(&app);
-    let profile_path = config_dir.join("profile.json");
+    let profile_path = config_dir.join(avatar::PROFILE_FILE);
 
     if profile_path.exists() {
         if let Ok(file) = File::open(profile_path) {
-            if let Ok(json) = serde_json::from_reader(file) {
+            if let Ok(mut json) = serde_json::from_reader(file) {
+                avatar::resolve_profile_avatar(&config_dir, &mut json);
                 return json;
             }
         }
@@ -68,17 +156,9 @@ pub fn get_user_data(app: AppHandle) -> serde_json::Value {
     serde_json::json!({
         "name": "Guest User",
         "email": "Not logged in",
-        "avatar": ""
-    })
-}
-
-#[tauri::command]
-pub async fn get_api_key(app: AppHandle, provider: String) -> Result<String, String> {
-    tauri::async_runtime::spawn_blocking(move || {
-        security::get_decrypted_key_internal(&app, &provider).unwrap_or_default()
+        "avatar": "",
+        "provider": ""
     })
-    .await
-    .map_err(|e| e.to_string())
 }
 
 #[tauri::command]
@@ -88,7 +168,7 @@ pub async fn reset_api_key(app: AppHandle) -> Result<(), String> {
 
         let _ = fs::remove_file(config_dir.join("gemini_key.json")).ok();
         let _ = fs::remove_file(config_dir.join("imgbb_key.json")).ok();
-        let _ = fs::remove_file(config_dir.join("profile.json")).ok();
+        auth::sign_out(&app, &config_dir);
         Ok(())
     })
     .await
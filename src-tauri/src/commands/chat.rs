@@ -0,0 +1,300 @@
+/*
+ * @license
+ * Copyright 2025 a7mddra
+ * SPDX-License-Identifier: Apache-2.0
+ */
+
+use base64::{engine::general_purpose, Engine as _};
+use serde::Serialize;
+use std::sync::atomic::Ordering;
+use tauri::{AppHandle, Emitter, State};
+
+use crate::services::conversations::{self, NewConversation};
+use crate::services::image;
+use crate::services::image_store;
+use crate::services::llm::config::{self, BackendConfig, ProviderSummary, GEMINI_PROVIDER_ID};
+use crate::services::llm::{
+    ChatRegistry, ChatSession, Message, ModelInfo, Part, Role, SessionHandle,
+};
+use crate::services::ocr;
+use crate::services::pii::{self, Outbound};
+use crate::state::AppState;
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ChatReply {
+    pub session_id: String,
+    pub text: String,
+}
+
+/// Starts a chat about the current image. Tokens arrive as `chat-token`
+/// events; the command resolves with the full reply. With `ocr_context` the
+/// text found by offline OCR is sent along with the prompt. The image goes
+/// through the PII check first and may be held back or redacted.
+#[tauri::command]
+pub async fn chat_start(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    provider: Option<String>,
+    model: String,
+    prompt: String,
+    system_instruction: Option<String>,
+    ocr_context: Option<bool>,
+) -> Result<ChatReply, String> {
+    let (mime_type, data) = image::current_image_base64(&state)?;
+    let bytes = image::current_image_bytes(&state)?;
+    let provider = provider.unwrap_or_else(|| GEMINI_PROVIDER_ID.to_string());
+    let chats = state.chats.clone();
+    let handle = chats.create(&provider, &model, system_instruction);
+    let session_id = handle.session.lock().id.clone();
+
+    let message = Message {
+        role: Role::User,
+        parts: vec![Part::Image { mime_type, data }, Part::Text { text: prompt }],
+    };
+
+    let result = tauri::async_runtime::spawn_blocking(move || {
+        first_turn(&app, &handle, message, bytes, ocr_context.unwrap_or(false))
+    })
+    .await
+    .map_err(|e| e.to_string())?;
+
+    match result {
+        Ok(text) => Ok(ChatReply { session_id, text }),
+        Err(e) => {
+            chats.remove(&session_id);
+            Err(e)
+        }
+    }
+}
+
+/// Continues a chat. `session_id` may also be a saved conversation id, in
+/// which case the chat is restored from history first.
+#[tauri::command]
+pub async fn chat_send(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    session_id: String,
+    text: String,
+) -> Result<ChatReply, String> {
+    let chats = state.chats.clone();
+    let message = Message::text(Role::User, &text);
+    let id = session_id.clone();
+
+    let text = tauri::async_runtime::spawn_blocking(move || {
+        let handle = match chats.get(&id) {
+            Some(handle) => handle,
+            None => restore_session(&app, &chats, &id)?,
+        };
+        run_turn(&app, &handle, message, &[])
+    })
+    .await
+    .map_err(|e| e.to_string())??;
+
+    Ok(ChatReply { session_id, text })
+}
+
+#[tauri::command]
+pub fn chat_cancel(state: State<'_, AppState>, session_id: String) -> Result<(), String> {
+    let handle = state.chats.get(&session_id).ok_or("Unknown chat session")?;
+    handle.cancel.store(true, Ordering::SeqCst);
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn list_model_providers(app: AppHandle) -> Result<Vec<ProviderSummary>, String> {
+    tauri::async_runtime::spawn_blocking(move || config::list_providers(&app))
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn save_model_provider(
+    app: AppHandle,
+    id: String,
+    settings: BackendConfig,
+) -> Result<(), String> {
+    tauri::async_runtime::spawn_blocking(move || config::save_config(&app, &id, &settings))
+        .await
+        .map_err(|e| e.to_string())?
+}
+
+#[tauri::command]
+pub async fn remove_model_provider(app: AppHandle, id: String) -> Result<(), String> {
+    tauri::async_runtime::spawn_blocking(move || config::remove_config(&app, &id))
+        .await
+        .map_err(|e| e.to_string())?
+}
+
+#[tauri::command]
+pub async fn list_models(app: AppHandle, provider: String) -> Result<Vec<ModelInfo>, String> {
+    tauri::async_runtime::spawn_blocking(move || {
+        config::load_config(&app, &provider)?.open()?.list_models()
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+fn restore_session(
+    app: &AppHandle,
+    chats: &ChatRegistry,
+    id: &str,
+) -> Result<SessionHandle, String> {
+    let conversation = conversations::with_store(app, |store| store.load(id))
+        .map_err(|_| "Unknown chat session")?;
+
+    let mut history = Vec::with_capacity(conversation.messages.len());
+    for stored in conversation.messages {
+        let mut parts = Vec::new();
+        for image_id in &stored.image_ids {
+            let bytes = image_store::load(app, image_id)?;
+            let mime_type = ::image::guess_format(&bytes)
+                .map(|f| f.to_mime_type())
+                .unwrap_or("image/jpeg");
+            parts.push(Part::Image {
+                mime_type: mime_type.to_string(),
+                data: general_purpose::STANDARD.encode(&bytes),
+            });
+        }
+        parts.push(Part::Text { text: stored.text });
+        history.push(Message {
+            role: stored.role,
+            parts,
+        });
+    }
+
+    Ok(chats.insert(ChatSession {
+        id: conversation.id,
+        provider: conversation.provider,
+        model: conversation.model,
+        system_instruction: conversation.system_instruction,
+        history,
+    }))
+}
+
+/// Sends the opening message, whose first part is the image in `bytes`. The
+/// image is stored and goes through the PII check first, which may block it
+/// or swap in a redacted copy.
+pub(crate) fn first_turn(
+    app: &AppHandle,
+    handle: &SessionHandle,
+    mut message: Message,
+    mut bytes: Vec<u8>,
+    ocr_context: bool,
+) -> Result<String, String> {
+    let mut image_id = image_store::store(app, &bytes)?;
+    if let Outbound::Redacted(version) = pii::preflight(app, &image_id, &bytes)? {
+        // Edited versions are always PNG.
+        message.parts[0] = Part::Image {
+            mime_type: "image/png".to_string(),
+            data: general_purpose::STANDARD.encode(&version.bytes),
+        };
+        image_id = version.id;
+        bytes = version.bytes;
+    }
+    if ocr_context {
+        // OCR is extra context; the chat goes ahead without it.
+        match ocr::recognize_cached(app, &image_id, &bytes, ocr::DEFAULT_LANGUAGE) {
+            Ok(result) => {
+                let _ = app.emit("ocr-result", &result);
+                if let Some(text) = result.prompt_context() {
+                    message.parts.push(Part::Text { text });
+                }
+            }
+            Err(e) => eprintln!("OCR failed: {}", e),
+        }
+    }
+    run_turn(app, handle, message, &[image_id])
+}
+
+/// Sends one user message and records the exchange in the session history
+/// and the conversation store only if the reply completes.
+fn run_turn(
+    app: &AppHandle,
+    handle: &SessionHandle,
+    message: Message,
+    image_ids: &[String],
+) -> Result<String, String> {
+    let mut session = handle.session.lock();
+    handle.cancel.store(false, Ordering::SeqCst);
+
+    let session_id = session.id.clone();
+    let mut contents = session.history.clone();
+    contents.push(message.clone());
+
+    let result = config::load_config(app, &session.provider)
+        .and_then(|settings| settings.open())
+        .and_then(|backend| {
+            backend.stream_chat(
+                &session.model,
+                session.system_instruction.as_deref(),
+                &contents,
+                &handle.cancel,
+                &mut |token| {
+                    let _ = app.emit(
+                        "chat-token",
+                        serde_json::json!({ "sessionId": session_id, "token": token }),
+                    );
+                },
+            )
+        })
+        .map(|reply| reply.text);
+
+    match result {
+        Ok(text) => {
+            if let Err(e) = save_turn(app, &session, &message, image_ids, &text) {
+                eprintln!("Failed to save conversation {}: {}", session_id, e);
+            }
+            session.history.push(message);
+            session.history.push(Message::text(Role::Model, &text));
+            let _ = app.emit("chat-done", serde_json::json!({ "sessionId": session_id }));
+            Ok(text)
+        }
+        Err(e) if handle.cancel.load(Ordering::SeqCst) => {
+            let _ = app.emit(
+                "chat-cancelled",
+                serde_json::json!({ "sessionId": session_id }),
+            );
+            Err(e)
+        }
+        Err(e) => {
+            let _ = app.emit(
+                "chat-error",
+                serde_json::json!({ "sessionId": session_id, "error": e }),
+            );
+            Err(e)
+        }
+    }
+}
+
+fn save_turn(
+    app: &AppHandle,
+    session: &ChatSession,
+    message: &Message,
+    image_ids: &[String],
+    reply: &str,
+) -> Result<(), String> {
+    let prompt: String = message
+        .parts
+        .iter()
+        .filter_map(|part| match part {
+            Part::Text { text } => Some(text.as_str()),
+            Part::Image { .. } => None,
+        })
+        .collect::<Vec<_>>()
+        .join("\n\n");
+
+    conversations::with_store(app, |store| {
+        if session.history.is_empty() {
+            store.create(NewConversation {
+                id: &session.id,
+                provider: &session.provider,
+                model: &session.model,
+                prompt: &prompt,
+                system_instruction: session.system_instruction.as_deref(),
+            })?;
+        }
+        store.append_message(&session.id, Role::User, &prompt, image_ids)?;
+        store.append_message(&session.id, Role::Model, reply, &[])
+    })
+}
@@ -4,75 +4,166 @@
  * SPDX-License-Identifier: Apache-2.0
  */
 
-use std::sync::atomic::Ordering;
-use std::thread;
-use std::time::Duration;
-use tauri::{AppHandle, Emitter, State};
+use std::fs;
+use tauri::{AppHandle, Emitter, Manager, State};
 
+use crate::services::clipboard::detectors::DetectorRegistry;
+use crate::services::clipboard::image as clipboard_image;
+use crate::services::clipboard::{ClipboardEvent, ClipboardWatcher, WatchMode};
+use crate::services::image;
 use crate::state::AppState;
+use crate::utils::get_app_cache_dir;
 
 #[tauri::command]
 pub async fn start_clipboard_watcher(
     app: AppHandle,
     state: State<'_, AppState>,
 ) -> Result<(), String> {
-    if state.watcher_running.load(Ordering::SeqCst) {
-        state.watcher_running.store(false, Ordering::SeqCst);
-        thread::sleep(Duration::from_millis(500));
+    let watcher_slot = state.clipboard_watcher.clone();
+
+    tauri::async_runtime::spawn_blocking(move || {
+        let mut slot = watcher_slot.lock();
+        if let Some(previous) = slot.take() {
+            previous.stop();
+        }
+
+        let mode = WatchMode::Keys(DetectorRegistry::with_defaults());
+        let watcher = ClipboardWatcher::spawn(mode, move |event| {
+            if let ClipboardEvent::Key(found) = event {
+                let _ = app.emit(
+                    "clipboard-text",
+                    serde_json::json!({ "provider": found.provider, "key": found.key }),
+                );
+            }
+        })?;
+        *slot = Some(watcher);
+
+        Ok(())
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+#[tauri::command]
+pub async fn stop_clipboard_watcher(state: State<'_, AppState>) -> Result<(), String> {
+    let watcher = state.clipboard_watcher.lock().take();
+
+    if let Some(watcher) = watcher {
+        tauri::async_runtime::spawn_blocking(move || watcher.stop())
+            .await
+            .map_err(|e| e.to_string())?;
     }
 
-    state.watcher_running.store(true, Ordering::SeqCst);
-    let running_flag = state.watcher_running.clone();
-    let app_handle = app.clone();
-
-    thread::spawn(move || {
-        let mut clipboard = loop {
-            match arboard::Clipboard::new() {
-                Ok(cb) => break cb,
-                Err(e) => {
-                    eprintln!("Clipboard init failed, retrying in 1s: {}", e);
-                    if !running_flag.load(Ordering::SeqCst) {
-                        return;
-                    }
-                    thread::sleep(Duration::from_secs(1));
-                }
+    Ok(())
+}
+
+/// Opt-in mode that offers new clipboard screenshots for analysis through a
+/// `clipboard-image` event.
+#[tauri::command]
+pub async fn set_clipboard_auto_ingest(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    enabled: bool,
+) -> Result<(), String> {
+    let watcher_slot = state.clipboard_ingest.clone();
+    let owner = state.clipboard_owner.clone();
+    let pending = state.pending_clipboard_image.clone();
+
+    tauri::async_runtime::spawn_blocking(move || {
+        let mut slot = watcher_slot.lock();
+        if let Some(previous) = slot.take() {
+            previous.stop();
+        }
+        if !enabled {
+            pending.lock().take();
+            return Ok(());
+        }
+
+        let watcher = ClipboardWatcher::spawn(WatchMode::Images(owner), move |event| {
+            if let ClipboardEvent::Image(offered) = event {
+                let payload = serde_json::json!({
+                    "hash": offered.hash,
+                    "width": offered.width,
+                    "height": offered.height
+                });
+                *pending.lock() = Some(offered);
+                let _ = app.emit("clipboard-image", payload);
             }
-        };
+        })?;
+        *slot = Some(watcher);
+
+        Ok(())
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
 
-        let mut last_text = clipboard.get_text().unwrap_or_default().trim().to_string();
-        println!("Watcher started. Ignoring current clipboard content.");
-
-        while running_flag.load(Ordering::SeqCst) {
-            if let Ok(text) = clipboard.get_text() {
-                let trimmed = text.trim().to_string();
-
-                if !trimmed.is_empty() && trimmed != last_text {
-                    last_text = trimmed.clone();
-
-                    if trimmed.starts_with("AIzaS") {
-                        println!("Gemini Key Detected");
-                        let _ = app_handle.emit(
-                            "clipboard-text",
-                            serde_json::json!({ "provider": "gemini", "key": trimmed }),
-                        );
-                    } else if trimmed.len() == 32 && trimmed.chars().all(char::is_alphanumeric) {
-                        println!("ImgBB Key Detected");
-                        let _ = app_handle.emit(
-                            "clipboard-text",
-                            serde_json::json!({ "provider": "imgbb", "key": trimmed }),
-                        );
-                    }
+/// Loads the offered clipboard image the same way as an image passed on the
+/// command line.
+#[tauri::command]
+pub async fn ingest_clipboard_image(app: AppHandle, hash: String) -> Result<(), String> {
+    tauri::async_runtime::spawn_blocking(move || {
+        let state = app.state::<AppState>();
+        let offered = {
+            let mut pending = state.pending_clipboard_image.lock();
+            match pending.take() {
+                Some(offered) if offered.hash == hash => offered,
+                other => {
+                    *pending = other;
+                    return Err("Clipboard image is no longer available".to_string());
                 }
             }
-            thread::sleep(Duration::from_millis(2000));
-        }
-    });
+        };
 
-    Ok(())
+        let dir = get_app_cache_dir(&app).join("clipboard");
+        fs::create_dir_all(&dir).map_err(|e| e.to_string())?;
+        let path = dir.join(format!("{}.png", offered.hash));
+        fs::write(&path, &offered.png).map_err(|e| e.to_string())?;
+
+        image::load_image_path(&app, &path.to_string_lossy())
+    })
+    .await
+    .map_err(|e| e.to_string())?
 }
 
 #[tauri::command]
-pub async fn stop_clipboard_watcher(state: State<'_, AppState>) -> Result<(), String> {
-    state.watcher_running.store(false, Ordering::SeqCst);
-    Ok(())
+pub async fn paste_image_from_clipboard(app: AppHandle) -> Result<String, String> {
+    tauri::async_runtime::spawn_blocking(move || {
+        let png = clipboard_image::read_png()?;
+        image::process_bytes_internal(png, &app.state::<AppState>())
+            .map(|processed| processed.data_url)
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+#[tauri::command]
+pub async fn copy_image_to_clipboard(app: AppHandle) -> Result<(), String> {
+    tauri::async_runtime::spawn_blocking(move || {
+        let state = app.state::<AppState>();
+        let current = image::current_image(&state)?;
+        let mut owner = state.clipboard_owner.lock();
+        owner.set_image(&current)
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+#[tauri::command]
+pub async fn copy_region_to_clipboard(
+    app: AppHandle,
+    x: u32,
+    y: u32,
+    width: u32,
+    height: u32,
+) -> Result<(), String> {
+    tauri::async_runtime::spawn_blocking(move || {
+        let state = app.state::<AppState>();
+        let current = image::current_image(&state)?;
+        let region = clipboard_image::crop(&current, x, y, width, height)?;
+        let mut owner = state.clipboard_owner.lock();
+        owner.set_image(&region)
+    })
+    .await
+    .map_err(|e| e.to_string())?
 }
@@ -0,0 +1,159 @@
+/*
+ * @license
+ * Copyright 2025 a7mddra
+ * SPDX-License-Identifier: Apache-2.0
+ */
+
+use std::path::PathBuf;
+use tauri::{AppHandle, State};
+use tauri_plugin_dialog::DialogExt;
+
+use crate::services::conversations::{self, Conversation, ConversationSummary};
+use crate::services::export::{self, ExportFormat};
+use crate::services::hosting::ledger;
+use crate::services::image_store;
+use crate::state::AppState;
+
+const DEFAULT_PAGE_SIZE: u32 = 50;
+
+#[tauri::command]
+pub async fn list_conversations(
+    app: AppHandle,
+    limit: Option<u32>,
+    offset: Option<u32>,
+) -> Result<Vec<ConversationSummary>, String> {
+    tauri::async_runtime::spawn_blocking(move || {
+        conversations::with_store(&app, |store| {
+            store.list(limit.unwrap_or(DEFAULT_PAGE_SIZE), offset.unwrap_or(0))
+        })
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+#[tauri::command]
+pub async fn search_conversations(
+    app: AppHandle,
+    query: String,
+    limit: Option<u32>,
+) -> Result<Vec<ConversationSummary>, String> {
+    tauri::async_runtime::spawn_blocking(move || {
+        conversations::with_store(&app, |store| {
+            store.search(&query, limit.unwrap_or(DEFAULT_PAGE_SIZE))
+        })
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+#[tauri::command]
+pub async fn load_conversation(app: AppHandle, id: String) -> Result<Conversation, String> {
+    tauri::async_runtime::spawn_blocking(move || {
+        conversations::with_store(&app, |store| store.load(&id))
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+#[tauri::command]
+pub async fn rename_conversation(app: AppHandle, id: String, title: String) -> Result<(), String> {
+    tauri::async_runtime::spawn_blocking(move || {
+        conversations::with_store(&app, |store| store.rename(&id, &title))
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+#[tauri::command]
+pub async fn delete_conversation(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    id: String,
+) -> Result<(), String> {
+    state.chats.remove(&id);
+
+    tauri::async_runtime::spawn_blocking(move || {
+        let orphans = conversations::with_store(&app, |store| store.delete(&id))?;
+        for image_id in &orphans.image_ids {
+            if let Err(e) = image_store::remove(&app, image_id) {
+                eprintln!("Failed to delete image {}: {}", image_id, e);
+            }
+        }
+        if orphans.uploads.is_empty() {
+            return Ok(());
+        }
+        // The conversation is gone either way; uploads that fail stay
+        // listed for a later cleanup.
+        match ledger::delete_all(&app, orphans.uploads) {
+            Ok(report) => {
+                for e in report.errors {
+                    eprintln!("Failed to delete upload {}", e);
+                }
+            }
+            Err(e) => eprintln!("Failed to delete uploads: {}", e),
+        }
+        Ok(())
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+/// Exports a conversation as JSON, Markdown, HTML or PDF. Without a `path`
+/// a save dialog is shown; returns the path written, or `None` if the user
+/// cancelled the dialog.
+#[tauri::command]
+pub async fn export_conversation(
+    app: AppHandle,
+    id: String,
+    format: ExportFormat,
+    path: Option<String>,
+) -> Result<Option<String>, String> {
+    tauri::async_runtime::spawn_blocking(move || {
+        let conversation = conversations::with_store(&app, |store| store.load(&id))?;
+
+        let path = match path {
+            Some(path) => PathBuf::from(path),
+            None => {
+                let selected = app
+                    .dialog()
+                    .file()
+                    .set_title("Export conversation")
+                    .set_file_name(format!(
+                        "{}.{}",
+                        file_name_for(&conversation.title),
+                        format.extension()
+                    ))
+                    .add_filter(format.label(), &[format.extension()])
+                    .blocking_save_file();
+                match selected {
+                    Some(file) => file.into_path().map_err(|e| e.to_string())?,
+                    None => return Ok(None),
+                }
+            }
+        };
+
+        let images = export::load_images(&app, &conversation)?;
+        export::write(&conversation, &images, format, &path)?;
+        Ok(Some(path.to_string_lossy().into_owned()))
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+/// Turns a conversation title into a file name safe on every platform.
+fn file_name_for(title: &str) -> String {
+    let name: String = title
+        .chars()
+        .map(|c| match c {
+            '/' | '\\' | ':' | '*' | '?' | '"' | '<' | '>' | '|' => '_',
+            c if c.is_control() => '_',
+            c => c,
+        })
+        .collect();
+    let name = name.trim().trim_end_matches('.').trim();
+    if name.is_empty() {
+        "conversation".to_string()
+    } else {
+        name.to_string()
+    }
+}
@@ -0,0 +1,61 @@
+/*
+ * @license
+ * Copyright 2025 a7mddra
+ * SPDX-License-Identifier: Apache-2.0
+ */
+
+use base64::{engine::general_purpose, Engine as _};
+use serde_json::Value;
+use std::sync::atomic::AtomicBool;
+use tauri::AppHandle;
+
+use crate::services::extract::{self, Extraction, DEFAULT_ATTEMPTS};
+use crate::services::image_store;
+use crate::services::llm::config::{self, GEMINI_PROVIDER_ID};
+use crate::services::llm::Part;
+use crate::services::pii::{self, Outbound};
+
+/// Extracts data matching the JSON Schema `schema` from a stored image. A
+/// reply that does not validate is sent back with the errors, up to
+/// `max_attempts` replies in all. The image goes through the PII check
+/// first, like a chat.
+#[tauri::command]
+pub async fn extract_structured(
+    app: AppHandle,
+    image_id: String,
+    schema: Value,
+    model: String,
+    provider: Option<String>,
+    instructions: Option<String>,
+    max_attempts: Option<u32>,
+) -> Result<Extraction, String> {
+    let provider = provider.unwrap_or_else(|| GEMINI_PROVIDER_ID.to_string());
+
+    tauri::async_runtime::spawn_blocking(move || {
+        extract::compile(&schema)?;
+        let mut bytes = image_store::load(&app, &image_id)?;
+        if let Outbound::Redacted(version) = pii::preflight(&app, &image_id, &bytes)? {
+            bytes = version.bytes;
+        }
+        let mime_type = ::image::guess_format(&bytes)
+            .map(|f| f.to_mime_type())
+            .unwrap_or("image/png");
+        let image = Part::Image {
+            mime_type: mime_type.to_string(),
+            data: general_purpose::STANDARD.encode(&bytes),
+        };
+
+        let backend = config::load_config(&app, &provider)?.open()?;
+        extract::extract(
+            backend.as_ref(),
+            &model,
+            image,
+            &schema,
+            instructions.as_deref(),
+            max_attempts.unwrap_or(DEFAULT_ATTEMPTS),
+            &AtomicBool::new(false),
+        )
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
@@ -4,9 +4,23 @@
  * SPDX-License-Identifier: Apache-2.0
  */
 
-use tauri::State;
+use crate::services::conversations::{self, ImageVersion};
 use crate::services::image;
+use crate::services::image::edit::Edit;
+use crate::services::image_store;
 use crate::state::AppState;
+use serde::Serialize;
+use tauri::{AppHandle, Manager, State};
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EditedImage {
+    pub id: String,
+    /// The image this one was edited from, if it is an edit.
+    pub source_id: Option<String>,
+    pub data_url: String,
+    pub can_undo: bool,
+}
 
 #[tauri::command]
 pub fn get_initial_image(state: State<AppState>) -> Option<String> {
@@ -20,7 +34,9 @@ pub fn process_image_path(path: String) -> Result<serde_json::Value, String> {
     if !path_buf.exists() {
         return Err("File does not exist".into());
     }
-    let mime = mime_guess::from_path(&path).first_or_octet_stream().to_string();
+    let mime = mime_guess::from_path(&path)
+        .first_or_octet_stream()
+        .to_string();
     Ok(serde_json::json!({
         "path": path,
         "mimeType": mime
@@ -29,18 +45,91 @@ pub fn process_image_path(path: String) -> Result<serde_json::Value, String> {
 
 #[tauri::command]
 pub fn process_image_bytes(bytes: Vec<u8>, state: State<AppState>) -> Result<String, String> {
-    image::process_bytes_internal(bytes, &state)
+    image::process_bytes_internal(bytes, &state).map(|processed| processed.data_url)
 }
 
 #[tauri::command]
 pub fn read_image_file(path: String, state: State<AppState>) -> Result<serde_json::Value, String> {
-    let base64 = image::process_and_store_image(&path, &state)?;
-
-    let parts: Vec<&str> = base64.splitn(2, ",").collect();
-    let mime_type = parts[0].replace("data:", "").replace(";base64", "");
+    let processed = image::process_and_store_image(&path, &state)?;
 
     Ok(serde_json::json!({
-        "base64": base64,
-        "mimeType": mime_type
+        "base64": processed.data_url,
+        "mimeType": processed.mime_type,
+        "preprocessing": processed.preprocessing
     }))
 }
+
+/// Applies `edits` in order to `source_id`, or to the current image when no
+/// source is given, and makes the result the current image. The source is
+/// kept for `undo_image_edit`.
+#[tauri::command]
+pub async fn apply_image_edits(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    edits: Vec<Edit>,
+    source_id: Option<String>,
+) -> Result<EditedImage, String> {
+    let current = match source_id {
+        Some(_) => None,
+        None => Some(image::current_image_bytes(&state)?),
+    };
+
+    tauri::async_runtime::spawn_blocking(move || {
+        let (source_id, source) = match source_id {
+            Some(id) => {
+                let bytes = image_store::load(&app, &id)?;
+                (id, bytes)
+            }
+            None => {
+                let bytes = current.ok_or("No image loaded")?;
+                (image_store::store(&app, &bytes)?, bytes)
+            }
+        };
+
+        let version = image::save_edited_version(&app, &source_id, &source, &edits)?;
+        Ok(EditedImage {
+            id: version.id,
+            source_id: Some(source_id),
+            data_url: version.data_url,
+            can_undo: true,
+        })
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+/// Restores the image the current one was edited from. Resolves to `None`
+/// when there is nothing to undo.
+#[tauri::command]
+pub async fn undo_image_edit(app: AppHandle) -> Result<Option<EditedImage>, String> {
+    tauri::async_runtime::spawn_blocking(move || {
+        let state = app.state::<AppState>();
+        let Some(id) = state.image_history.lock().pop() else {
+            return Ok(None);
+        };
+
+        let bytes = image_store::load(&app, &id)?;
+        let version = conversations::with_store(&app, |store| store.image_version(&id))?;
+        let (data_url, _) = image::set_current_image(&bytes, &state);
+        let can_undo = !state.image_history.lock().is_empty();
+
+        Ok(Some(EditedImage {
+            id,
+            source_id: version.map(|v| v.source_id),
+            data_url,
+            can_undo,
+        }))
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+/// The source image and edit list an image was made from, if it is an edit.
+#[tauri::command]
+pub async fn get_image_version(app: AppHandle, id: String) -> Result<Option<ImageVersion>, String> {
+    tauri::async_runtime::spawn_blocking(move || {
+        conversations::with_store(&app, |store| store.image_version(&id))
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
@@ -5,7 +5,16 @@
  */
 
 pub mod auth;
+pub mod chat;
 pub mod clipboard;
+pub mod conversations;
+pub mod extract;
 pub mod image;
+pub mod ocr;
+pub mod pii;
+pub mod reverse_search;
 pub mod security;
+pub mod settings;
+pub mod templates;
+pub mod uploads;
 pub mod window;
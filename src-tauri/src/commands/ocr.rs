@@ -0,0 +1,33 @@
+/*
+ * @license
+ * Copyright 2025 a7mddra
+ * SPDX-License-Identifier: Apache-2.0
+ */
+
+use tauri::{AppHandle, Emitter, State};
+
+use crate::services::image;
+use crate::services::image_store;
+use crate::services::ocr::{self, OcrResult};
+use crate::state::AppState;
+
+/// Runs offline OCR on the current image. The result is also emitted as an
+/// `ocr-result` event.
+#[tauri::command]
+pub async fn run_ocr(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    language: Option<String>,
+) -> Result<OcrResult, String> {
+    let bytes = image::current_image_bytes(&state)?;
+    let language = language.unwrap_or_else(|| ocr::DEFAULT_LANGUAGE.to_string());
+
+    tauri::async_runtime::spawn_blocking(move || {
+        let image_id = image_store::store(&app, &bytes)?;
+        let result = ocr::recognize_cached(&app, &image_id, &bytes, &language)?;
+        let _ = app.emit("ocr-result", &result);
+        Ok(result)
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
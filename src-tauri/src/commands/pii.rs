@@ -0,0 +1,66 @@
+/*
+ * @license
+ * Copyright 2025 a7mddra
+ * SPDX-License-Identifier: Apache-2.0
+ */
+
+use tauri::{AppHandle, Manager, State};
+
+use crate::commands::image::EditedImage;
+use crate::services::image;
+use crate::services::image_store;
+use crate::services::pii::{self, PiiScan};
+use crate::state::AppState;
+
+/// Looks for personal data in the current image.
+#[tauri::command]
+pub async fn scan_pii(app: AppHandle, state: State<'_, AppState>) -> Result<PiiScan, String> {
+    let bytes = image::current_image_bytes(&state)?;
+    let policy = state.settings.lock().pii.clone();
+
+    tauri::async_runtime::spawn_blocking(move || {
+        let image_id = image_store::store(&app, &bytes)?;
+        pii::scan(&app, &image_id, &bytes, &policy)
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+/// Redacts every finding in the current image as a new, undoable version.
+/// Resolves to `None` when nothing was found.
+#[tauri::command]
+pub async fn redact_pii(
+    app: AppHandle,
+    state: State<'_, AppState>,
+) -> Result<Option<EditedImage>, String> {
+    let bytes = image::current_image_bytes(&state)?;
+    let policy = state.settings.lock().pii.clone();
+
+    tauri::async_runtime::spawn_blocking(move || {
+        let image_id = image_store::store(&app, &bytes)?;
+        let scan = pii::scan(&app, &image_id, &bytes, &policy)?;
+        if scan.findings.is_empty() {
+            return Ok(None);
+        }
+
+        let version = image::save_edited_version(&app, &image_id, &bytes, &scan.redaction_edits())?;
+        app.state::<AppState>()
+            .pii_reviewed
+            .lock()
+            .insert(version.id.clone());
+        Ok(Some(EditedImage {
+            id: version.id,
+            source_id: Some(image_id),
+            data_url: version.data_url,
+            can_undo: true,
+        }))
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+/// Clears an image to be sent even though the PII check flagged it.
+#[tauri::command]
+pub fn approve_pii_review(state: State<AppState>, image_id: String) {
+    state.pii_reviewed.lock().insert(image_id);
+}
@@ -0,0 +1,81 @@
+/*
+ * @license
+ * Copyright 2025 a7mddra
+ * SPDX-License-Identifier: Apache-2.0
+ */
+
+use tauri::{AppHandle, State};
+
+use crate::commands::window::open_external_url;
+use crate::services::hosting;
+use crate::services::hosting::config::{self, HostSummary, HostingConfig};
+use crate::services::image;
+use crate::services::image_store;
+use crate::services::reverse_search::{self, EngineInfo};
+use crate::state::AppState;
+
+/// Uploads an image, the current one unless `image_id` names a stored one,
+/// through the configured host and opens it in a reverse search `engine`
+/// (the configured default when omitted). Resolves with the opened URL.
+#[tauri::command]
+pub async fn reverse_search(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    image_id: Option<String>,
+    engine: Option<String>,
+) -> Result<String, String> {
+    let url = reverse_search_url(app, state, image_id, engine).await?;
+    open_external_url(url.clone()).await?;
+    Ok(url)
+}
+
+/// Like `reverse_search`, without opening the result. Lets the frontend
+/// upload ahead of time.
+#[tauri::command]
+pub async fn reverse_search_url(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    image_id: Option<String>,
+    engine: Option<String>,
+) -> Result<String, String> {
+    let options = state.settings.lock().reverse_search.clone();
+    let current = match image_id {
+        Some(_) => Vec::new(),
+        None => image::current_image_bytes(&state)?,
+    };
+
+    tauri::async_runtime::spawn_blocking(move || {
+        let (image_id, bytes) = match image_id {
+            Some(id) => {
+                let bytes = image_store::load(&app, &id)?;
+                (id, bytes)
+            }
+            None => (image_store::store(&app, &current)?, current),
+        };
+
+        let engine = engine.unwrap_or_else(|| options.engine().to_string());
+        let hosted = hosting::publish(&app, &image_id, &bytes)?;
+        reverse_search::search_url(&engine, &hosted.url, &reverse_search::locale(&options))
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+#[tauri::command]
+pub fn list_search_engines() -> Vec<EngineInfo> {
+    reverse_search::engines()
+}
+
+#[tauri::command]
+pub async fn get_image_host(app: AppHandle) -> Result<HostSummary, String> {
+    tauri::async_runtime::spawn_blocking(move || Ok(config::load_config(&app)?.summary()))
+        .await
+        .map_err(|e| e.to_string())?
+}
+
+#[tauri::command]
+pub async fn set_image_host(app: AppHandle, settings: HostingConfig) -> Result<(), String> {
+    tauri::async_runtime::spawn_blocking(move || config::save_config(&app, &settings))
+        .await
+        .map_err(|e| e.to_string())?
+}
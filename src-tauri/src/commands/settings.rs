@@ -0,0 +1,43 @@
+/*
+ * @license
+ * Copyright 2025 a7mddra
+ * SPDX-License-Identifier: Apache-2.0
+ */
+
+use serde_json::Value;
+use tauri::{AppHandle, Emitter, State};
+
+use crate::cli::LaunchOverrides;
+use crate::services::settings::{self, Settings};
+use crate::state::AppState;
+use crate::utils::get_app_config_dir;
+
+#[tauri::command]
+pub fn get_settings(state: State<AppState>) -> Settings {
+    state.settings.lock().clone()
+}
+
+/// Prompt and model from the command line, which take precedence over the
+/// settings for this session. Later handoffs arrive as `launch-overrides`.
+#[tauri::command]
+pub fn get_launch_overrides(state: State<AppState>) -> LaunchOverrides {
+    state.launch_overrides.lock().clone()
+}
+
+/// Merges `patch` into the settings, saves them and emits `settings-changed`
+/// with the result. Nothing changes if the patched settings are invalid.
+#[tauri::command]
+pub fn update_settings(
+    app: AppHandle,
+    state: State<AppState>,
+    patch: Value,
+) -> Result<Settings, String> {
+    let mut current = state.settings.lock();
+    let updated = settings::patched(&current, patch)?;
+    settings::save(&get_app_config_dir(&app), &updated)?;
+    *current = updated.clone();
+    drop(current);
+
+    let _ = app.emit("settings-changed", &updated);
+    Ok(updated)
+}
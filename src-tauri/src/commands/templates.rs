@@ -0,0 +1,263 @@
+/*
+ * @license
+ * Copyright 2025 a7mddra
+ * SPDX-License-Identifier: Apache-2.0
+ */
+
+use base64::{engine::general_purpose, Engine as _};
+use std::collections::{BTreeSet, HashMap};
+use std::fs;
+use std::path::PathBuf;
+use tauri::{AppHandle, Emitter, State};
+use tauri_plugin_dialog::DialogExt;
+
+use crate::commands::chat::{first_turn, ChatReply};
+use crate::services::image;
+use crate::services::image_store;
+use crate::services::llm::config::GEMINI_PROVIDER_ID;
+use crate::services::llm::{Message, Part, Role};
+use crate::services::ocr;
+use crate::services::pii::{self, Outbound};
+use crate::services::templates::{
+    self, PromptTemplate, RenderedPrompt, APP_NAME, CLIPBOARD, DATE, OCR_TEXT,
+};
+use crate::state::AppState;
+use crate::utils::get_app_config_dir;
+
+#[tauri::command]
+pub async fn list_prompt_templates(app: AppHandle) -> Result<Vec<PromptTemplate>, String> {
+    tauri::async_runtime::spawn_blocking(move || templates::load(&get_app_config_dir(&app)))
+        .await
+        .map_err(|e| e.to_string())?
+}
+
+/// Adds a template, or replaces the one with the same id. Resolves with the
+/// template as saved, including its new id.
+#[tauri::command]
+pub async fn save_prompt_template(
+    app: AppHandle,
+    template: PromptTemplate,
+) -> Result<PromptTemplate, String> {
+    tauri::async_runtime::spawn_blocking(move || {
+        templates::update(&get_app_config_dir(&app), |library| {
+            templates::upsert(library, template)
+        })
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+#[tauri::command]
+pub async fn delete_prompt_template(app: AppHandle, id: String) -> Result<(), String> {
+    tauri::async_runtime::spawn_blocking(move || {
+        templates::update(&get_app_config_dir(&app), |library| {
+            templates::remove(library, &id)
+        })
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+/// Writes the library to `path`, or to a file the user picks. Resolves to
+/// `None` if the dialog is cancelled.
+#[tauri::command]
+pub async fn export_prompt_templates(
+    app: AppHandle,
+    path: Option<String>,
+) -> Result<Option<String>, String> {
+    tauri::async_runtime::spawn_blocking(move || {
+        let json = templates::export(&templates::load(&get_app_config_dir(&app))?)?;
+        let path = match path {
+            Some(path) => PathBuf::from(path),
+            None => {
+                let selected = app
+                    .dialog()
+                    .file()
+                    .set_title("Export prompt templates")
+                    .set_file_name(templates::TEMPLATES_FILE)
+                    .add_filter("JSON", &["json"])
+                    .blocking_save_file();
+                match selected {
+                    Some(file) => file.into_path().map_err(|e| e.to_string())?,
+                    None => return Ok(None),
+                }
+            }
+        };
+
+        fs::write(&path, json).map_err(|e| format!("Failed to write file: {}", e))?;
+        Ok(Some(path.to_string_lossy().into_owned()))
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+/// Merges templates from `path`, or from a file the user picks, and
+/// resolves with the whole library. Resolves to `None` if the dialog is
+/// cancelled.
+#[tauri::command]
+pub async fn import_prompt_templates(
+    app: AppHandle,
+    path: Option<String>,
+) -> Result<Option<Vec<PromptTemplate>>, String> {
+    tauri::async_runtime::spawn_blocking(move || {
+        let path = match path {
+            Some(path) => PathBuf::from(path),
+            None => {
+                let selected = app
+                    .dialog()
+                    .file()
+                    .set_title("Import prompt templates")
+                    .add_filter("JSON", &["json"])
+                    .blocking_pick_file();
+                match selected {
+                    Some(file) => file.into_path().map_err(|e| e.to_string())?,
+                    None => return Ok(None),
+                }
+            }
+        };
+
+        let json = fs::read_to_string(&path).map_err(|e| format!("Failed to read file: {}", e))?;
+        templates::update(&get_app_config_dir(&app), |library| {
+            templates::import(library, &json)?;
+            Ok(library.clone())
+        })
+        .map(Some)
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+/// Fills in a template against the current image, e.g. for a preview. No
+/// image is needed unless the template uses `{{ocr_text}}`.
+#[tauri::command]
+pub async fn render_prompt_template(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    id: String,
+) -> Result<RenderedPrompt, String> {
+    let bytes = image::current_image_bytes(&state).ok();
+
+    tauri::async_runtime::spawn_blocking(move || {
+        let template = templates::find(&get_app_config_dir(&app), &id)?;
+        let needed = template.variables();
+        let image = match bytes {
+            Some(bytes) if needed.contains(OCR_TEXT) => {
+                Some((image_store::store(&app, &bytes)?, bytes))
+            }
+            _ => None,
+        };
+        let values = template_values(&app, &needed, image.as_ref());
+        Ok(template.render(&values))
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+/// Starts a chat about the current image with a saved template. The
+/// template's provider and model win over `provider` and `model`. Tokens
+/// arrive as `chat-token` events, as with `chat_start`.
+#[tauri::command]
+pub async fn chat_start_template(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    template_id: String,
+    provider: Option<String>,
+    model: Option<String>,
+) -> Result<ChatReply, String> {
+    let mut bytes = image::current_image_bytes(&state)?;
+    let default_model = state.settings.lock().model.clone();
+    let chats = state.chats.clone();
+
+    tauri::async_runtime::spawn_blocking(move || {
+        let template = templates::find(&get_app_config_dir(&app), &template_id)?;
+        let needed = template.variables();
+
+        let mut image_id = image_store::store(&app, &bytes)?;
+        if needed.contains(OCR_TEXT) {
+            // Checked before OCR so the text never holds what a redaction
+            // removed. `first_turn` then finds the image already checked.
+            if let Outbound::Redacted(version) = pii::preflight(&app, &image_id, &bytes)? {
+                image_id = version.id;
+                bytes = version.bytes;
+            }
+        }
+        let image = (image_id, bytes);
+        let rendered = template.render(&template_values(&app, &needed, Some(&image)));
+        let (_, bytes) = image;
+
+        let provider = rendered
+            .provider
+            .or(provider)
+            .unwrap_or_else(|| GEMINI_PROVIDER_ID.to_string());
+        let model = rendered.model.or(model).unwrap_or(default_model);
+        let handle = chats.create(&provider, &model, rendered.system_instruction);
+        let session_id = handle.session.lock().id.clone();
+
+        let mime_type = ::image::guess_format(&bytes)
+            .map(|f| f.to_mime_type())
+            .unwrap_or("image/png");
+        let message = Message {
+            role: Role::User,
+            parts: vec![
+                Part::Image {
+                    mime_type: mime_type.to_string(),
+                    data: general_purpose::STANDARD.encode(&bytes),
+                },
+                Part::Text {
+                    text: rendered.prompt,
+                },
+            ],
+        };
+
+        match first_turn(&app, &handle, message, bytes, false) {
+            Ok(text) => Ok(ChatReply { session_id, text }),
+            Err(e) => {
+                chats.remove(&session_id);
+                Err(e)
+            }
+        }
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+/// Values for the variables in `needed`. OCR is extra context, so a failed
+/// run leaves `{{ocr_text}}` empty instead of failing the chat.
+fn template_values(
+    app: &AppHandle,
+    needed: &BTreeSet<String>,
+    image: Option<&(String, Vec<u8>)>,
+) -> HashMap<&'static str, String> {
+    let mut values = HashMap::new();
+    if needed.contains(OCR_TEXT) {
+        let text = match image {
+            Some((id, bytes)) => {
+                match ocr::recognize_cached(app, id, bytes, ocr::DEFAULT_LANGUAGE) {
+                    Ok(result) => {
+                        let _ = app.emit("ocr-result", &result);
+                        result.text
+                    }
+                    Err(e) => {
+                        eprintln!("OCR failed: {}", e);
+                        String::new()
+                    }
+                }
+            }
+            None => String::new(),
+        };
+        values.insert(OCR_TEXT, text);
+    }
+    if needed.contains(APP_NAME) {
+        values.insert(APP_NAME, app.package_info().name.clone());
+    }
+    if needed.contains(DATE) {
+        values.insert(DATE, chrono::Local::now().format("%Y-%m-%d").to_string());
+    }
+    if needed.contains(CLIPBOARD) {
+        let text = arboard::Clipboard::new()
+            .and_then(|mut clipboard| clipboard.get_text())
+            .unwrap_or_default();
+        values.insert(CLIPBOARD, text);
+    }
+    values
+}
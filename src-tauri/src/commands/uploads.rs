@@ -0,0 +1,32 @@
+/*
+ * @license
+ * Copyright 2025 a7mddra
+ * SPDX-License-Identifier: Apache-2.0
+ */
+
+use tauri::AppHandle;
+
+use crate::services::conversations::Upload;
+use crate::services::hosting::ledger::{self, CleanupReport};
+
+#[tauri::command]
+pub async fn list_uploads(app: AppHandle) -> Result<Vec<Upload>, String> {
+    tauri::async_runtime::spawn_blocking(move || ledger::list(&app))
+        .await
+        .map_err(|e| e.to_string())?
+}
+
+/// Deletes an uploaded image from its host and forgets it.
+#[tauri::command]
+pub async fn delete_upload(app: AppHandle, id: i64) -> Result<(), String> {
+    tauri::async_runtime::spawn_blocking(move || ledger::delete(&app, id))
+        .await
+        .map_err(|e| e.to_string())?
+}
+
+#[tauri::command]
+pub async fn delete_uploads_older_than(app: AppHandle, days: u32) -> Result<CleanupReport, String> {
+    tauri::async_runtime::spawn_blocking(move || ledger::delete_older_than(&app, days))
+        .await
+        .map_err(|e| e.to_string())?
+}
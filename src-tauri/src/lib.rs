@@ -4,48 +4,134 @@
  * SPDX-License-Identifier: Apache-2.0
  */
 
-use tauri::{Builder, Emitter, Manager};
+use tauri::{Builder, Manager, RunEvent};
 
+pub mod cli;
 pub mod state;
 pub mod utils;
 
 pub mod commands;
 pub mod services;
 
-use commands::auth::{get_api_key, get_user_data, logout, reset_api_key, start_google_auth};
-use commands::clipboard::{start_clipboard_watcher, stop_clipboard_watcher};
+#[cfg(test)]
+pub(crate) mod testing;
+
+use cli::{Invocation, LaunchOptions};
+use commands::auth::{
+    cancel_google_auth, get_access_token, get_user_data, list_auth_providers, logout,
+    remove_auth_provider, reset_api_key, save_auth_provider, start_auth, start_google_auth,
+};
+use commands::chat::{
+    chat_cancel, chat_send, chat_start, list_model_providers, list_models, remove_model_provider,
+    save_model_provider,
+};
+use commands::clipboard::{
+    copy_image_to_clipboard, copy_region_to_clipboard, ingest_clipboard_image,
+    paste_image_from_clipboard, set_clipboard_auto_ingest, start_clipboard_watcher,
+    stop_clipboard_watcher,
+};
+use commands::conversations::{
+    delete_conversation, export_conversation, list_conversations, load_conversation,
+    rename_conversation, search_conversations,
+};
+use commands::extract::extract_structured;
 use commands::image::{
-    get_initial_image, process_image_bytes, process_image_path, read_image_file,
+    apply_image_edits, get_image_version, get_initial_image, process_image_bytes,
+    process_image_path, read_image_file, undo_image_edit,
+};
+use commands::ocr::run_ocr;
+use commands::pii::{approve_pii_review, redact_pii, scan_pii};
+use commands::reverse_search::{
+    get_image_host, list_search_engines, reverse_search, reverse_search_url, set_image_host,
 };
 use commands::security::{check_file_exists, encrypt_and_save};
+use commands::settings::{get_launch_overrides, get_settings, update_settings};
+use commands::templates::{
+    chat_start_template, delete_prompt_template, export_prompt_templates, import_prompt_templates,
+    list_prompt_templates, render_prompt_template, save_prompt_template,
+};
+use commands::uploads::{delete_upload, delete_uploads_older_than, list_uploads};
 use commands::window::{
     clear_cache, close_imgbb_window, open_external_url, open_imgbb_window, resize_window,
 };
-use services::image::process_and_store_image;
+use services::oauth;
+use spatialshot_core::{ipc, paths};
 use state::AppState;
+use tauri_plugin_cli::CliExt;
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
     Builder::default()
+        .plugin(tauri_plugin_cli::init())
         .plugin(tauri_plugin_updater::Builder::new().build())
         .plugin(tauri_plugin_process::init())
         .plugin(tauri_plugin_opener::init())
         .plugin(tauri_plugin_fs::init())
+        .plugin(tauri_plugin_dialog::init())
         .manage(AppState::new())
         .invoke_handler(tauri::generate_handler![
             process_image_path,
             process_image_bytes,
             read_image_file,
+            apply_image_edits,
+            undo_image_edit,
+            get_image_version,
             get_initial_image,
             start_clipboard_watcher,
             stop_clipboard_watcher,
+            paste_image_from_clipboard,
+            copy_image_to_clipboard,
+            copy_region_to_clipboard,
+            set_clipboard_auto_ingest,
+            ingest_clipboard_image,
             encrypt_and_save,
             check_file_exists,
-            get_api_key,
             reset_api_key,
             start_google_auth,
+            start_auth,
+            cancel_google_auth,
+            list_auth_providers,
+            save_auth_provider,
+            remove_auth_provider,
+            get_access_token,
             logout,
             get_user_data,
+            chat_start,
+            chat_send,
+            chat_cancel,
+            list_model_providers,
+            save_model_provider,
+            remove_model_provider,
+            list_models,
+            list_conversations,
+            search_conversations,
+            load_conversation,
+            rename_conversation,
+            delete_conversation,
+            export_conversation,
+            run_ocr,
+            extract_structured,
+            scan_pii,
+            redact_pii,
+            approve_pii_review,
+            reverse_search,
+            reverse_search_url,
+            list_search_engines,
+            get_settings,
+            get_launch_overrides,
+            list_prompt_templates,
+            save_prompt_template,
+            delete_prompt_template,
+            export_prompt_templates,
+            import_prompt_templates,
+            render_prompt_template,
+            chat_start_template,
+            update_settings,
+            get_image_host,
+            set_image_host,
+            list_uploads,
+            delete_upload,
+            delete_uploads_older_than,
             open_imgbb_window,
             close_imgbb_window,
             open_external_url,
@@ -55,13 +141,40 @@ pub fn run() {
         .setup(|app| {
             let handle = app.handle().clone();
 
-            let args: Vec<String> = std::env::args().collect();
+            match services::settings::load(&utils::get_app_config_dir(&handle)) {
+                Ok(settings) => *handle.state::<AppState>().settings.lock() = settings,
+                Err(e) => eprintln!("Using default settings: {}", e),
+            }
+
+            let options = match parse_cli(&handle) {
+                Ok(Invocation::Launch(options)) => options,
+                Ok(Invocation::Batch(options)) => {
+                    cli::run_batch(handle, options);
+                    return Ok(());
+                }
+                Err(e) => {
+                    eprintln!("{}", e);
+                    std::process::exit(2);
+                }
+            };
+
+            if options.is_headless() {
+                cli::run_headless(handle, options);
+                return Ok(());
+            }
+
+            if let Some(handoff) = options.handoff() {
+                if ipc::send(&paths::instance_file(), &handoff).is_ok() {
+                    println!("Handed off to the running instance");
+                    std::process::exit(0);
+                }
+            }
+            listen_for_launches(&handle);
 
-            if let Some(path) = args.iter().skip(1).find(|arg| !arg.starts_with("-")) {
-                println!("CLI Image argument detected: {}", path);
-                let state = handle.state::<AppState>();
-                if let Ok(_data_url) = process_and_store_image(path, &state) {
-                    let _ = handle.emit("image-path", path);
+            *handle.state::<AppState>().launch_overrides.lock() = options.overrides.clone();
+            if let Some(source) = &options.image {
+                if let Err(e) = cli::load_image(&handle, source) {
+                    eprintln!("Failed to load image {:?}: {}", source, e);
                 }
             }
 
@@ -77,6 +190,58 @@ pub fn run() {
 
             Ok(())
         })
-        .run(tauri::generate_context!())
-        .expect("error while running tauri application");
+        .build(tauri::generate_context!())
+        .expect("error while building tauri application")
+        .run(|app, event| {
+            if let RunEvent::Exit = event {
+                let state = app.state::<AppState>();
+                if let Some(watcher) = state.clipboard_ingest.lock().take() {
+                    watcher.stop();
+                }
+                state.clipboard_owner.lock().release();
+                if let Some(token) = state.instance_token.lock().take() {
+                    ipc::release(&paths::instance_file(), &token);
+                }
+            }
+        });
+}
+
+/// Reads the command line, printing `--help` or `--version` and exiting if
+/// either was asked for.
+fn parse_cli(app: &tauri::AppHandle) -> Result<Invocation, String> {
+    let matches = app.cli().matches().map_err(|e| e.to_string())?;
+    let (subcommand, matches) = match matches.subcommand {
+        Some(subcommand) => (Some(subcommand.name), subcommand.matches),
+        None => (None, matches),
+    };
+    for (name, arg) in &matches.args {
+        if name == "help" || name == "version" {
+            println!("{}", arg.value.as_str().unwrap_or_default());
+            std::process::exit(0);
+        }
+    }
+
+    let args = matches
+        .args
+        .into_iter()
+        .map(|(name, arg)| (name, arg.value))
+        .collect();
+    match subcommand.as_deref() {
+        Some("batch") => cli::batch_options(&args).map(Invocation::Batch),
+        Some(other) => Err(format!("Unknown command: {}", other)),
+        None => LaunchOptions::from_args(&args).map(Invocation::Launch),
+    }
+}
+
+/// Lets later launches open their image here instead of in a new window.
+fn listen_for_launches(app: &tauri::AppHandle) {
+    let token = oauth::random_token(16);
+    let handle = app.clone();
+    let listening = ipc::listen(&paths::instance_file(), token.clone(), move |handoff| {
+        cli::apply_handoff(&handle, handoff)
+    });
+    match listening {
+        Ok(()) => *app.state::<AppState>().instance_token.lock() = Some(token),
+        Err(e) => eprintln!("Not accepting handoffs: {}", e),
+    }
 }
@@ -4,196 +4,268 @@
  * SPDX-License-Identifier: Apache-2.0
  */
 
+use parking_lot::Mutex;
 use serde::{Deserialize, Serialize};
-use std::fs::File;
+use std::fs::{self, File};
 use std::io::Write;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::AtomicBool;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 use tauri::{AppHandle, Emitter};
-use tiny_http::{Header, Response, Server};
-use url::Url;
+use tiny_http::{Header, Response};
 
-const SECRETS_JSON: &str = include_str!("../data/credentials.json");
+use crate::services::avatar;
+use crate::services::oauth::{self, LoopbackServer, Pkce, TokenResponse};
+use crate::services::oidc::{self, OidcClient, GOOGLE_PROVIDER_ID};
+use crate::services::security;
 
 const HTML_TEMPLATE: &str = include_str!("../data/success.html");
 
-const REDIRECT_PORT: u16 = 3000;
-const REDIRECT_URI: &str = "http://localhost:3000";
-const USER_INFO_URL: &str =
-    "https://people.googleapis.com/v1/people/me?personFields=names,emailAddresses,photos";
+const TOKEN_FILE: &str = "oauth_token.json";
+const REFRESH_MARGIN_SECS: u64 = 60;
 
-#[derive(Deserialize, Debug)]
-struct GoogleCredentials {
-    installed: Option<OAuthConfig>,
-    web: Option<OAuthConfig>,
-}
+static REFRESH_LOCK: Mutex<()> = Mutex::new(());
 
-#[derive(Deserialize, Debug, Clone)]
-struct OAuthConfig {
-    client_id: String,
-    client_secret: String,
-    auth_uri: String,
-    token_uri: String,
+#[derive(Serialize)]
+struct SavedProfile {
+    name: String,
+    email: String,
+    /// `avatar.png` when a local copy is cached, otherwise empty.
+    avatar: String,
+    #[serde(rename = "avatarUrl")]
+    avatar_url: String,
+    provider: String,
+    issuer: String,
+    subject: String,
 }
 
-#[derive(Deserialize)]
-struct TokenResponse {
+#[derive(Serialize, Deserialize)]
+struct StoredToken {
+    #[serde(default = "default_provider")]
+    provider: String,
     access_token: String,
+    refresh_token: Option<String>,
+    expires_at: u64,
 }
 
-#[derive(Deserialize)]
-struct UserProfile {
-    names: Option<Vec<Name>>,
-    #[serde(rename = "emailAddresses")]
-    email_addresses: Option<Vec<Email>>,
-    photos: Option<Vec<Photo>>,
+fn default_provider() -> String {
+    GOOGLE_PROVIDER_ID.to_string()
 }
 
-#[derive(Deserialize)]
-struct Name {
-    #[serde(rename = "displayName")]
-    display_name: Option<String>,
-}
-#[derive(Deserialize)]
-struct Email {
-    value: Option<String>,
+fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
 }
-#[derive(Deserialize)]
-struct Photo {
-    url: Option<String>,
+
+fn load_token(app: &AppHandle) -> Option<StoredToken> {
+    let json = security::load_decrypted(app, TOKEN_FILE)?;
+    serde_json::from_str(&json).ok()
 }
 
-#[derive(Serialize)]
-struct SavedProfile {
-    name: String,
-    email: String,
-    avatar: String,
+/// Persists a token response, keeping the previous refresh token when the
+/// provider does not rotate it.
+fn store_token(
+    app: &AppHandle,
+    provider: &str,
+    token: TokenResponse,
+    previous_refresh: Option<String>,
+) -> Result<StoredToken, String> {
+    let stored = StoredToken {
+        provider: provider.to_string(),
+        access_token: token.access_token,
+        refresh_token: token.refresh_token.or(previous_refresh),
+        expires_at: unix_now() + token.expires_in.unwrap_or(3600),
+    };
+
+    let json = serde_json::to_string(&stored).map_err(|e| e.to_string())?;
+    security::encrypt_and_store(app, TOKEN_FILE, &json)?;
+
+    Ok(stored)
 }
 
-pub fn start_google_auth_flow(app: AppHandle, config_dir: PathBuf) -> Result<(), String> {
-    let wrapper: GoogleCredentials = serde_json::from_str(SECRETS_JSON)
-        .map_err(|e| format!("Failed to parse credentials.json: {}", e))?;
-
-    let secrets = wrapper
-        .installed
-        .or(wrapper.web)
-        .ok_or("Invalid credentials.json: missing 'installed' or 'web' object")?;
-
-    let server = Server::http(format!("127.0.0.1:{}", REDIRECT_PORT)).map_err(|e| {
-        format!(
-            "Failed to start auth server on port {}: {}",
-            REDIRECT_PORT, e
-        )
-    })?;
-
-    let auth_url_full = format!(
-        "{}?client_id={}&redirect_uri={}&response_type=code&scope=profile email&access_type=offline&prompt=consent",
-        secrets.auth_uri, secrets.client_id, REDIRECT_URI
-    );
+/// Returns a valid access token, refreshing it first if it expires within
+/// `REFRESH_MARGIN_SECS`.
+pub fn get_access_token(app: &AppHandle, config_dir: &Path) -> Result<String, String> {
+    let _guard = REFRESH_LOCK.lock();
 
-    opener::open(&auth_url_full).map_err(|e| e.to_string())?;
+    let token = load_token(app).ok_or("Not signed in")?;
+    if token.expires_at > unix_now() + REFRESH_MARGIN_SECS {
+        return Ok(token.access_token);
+    }
 
-    loop {
-        let request = match server.recv() {
-            Ok(rq) => rq,
-            Err(e) => {
-                println!("Server receive error: {}", e);
-                break;
-            }
-        };
+    let refresh_token = token
+        .refresh_token
+        .ok_or("Session expired and no refresh token is stored. Please sign in again.")?;
 
-        let url_string = format!("http://localhost:{}{}", REDIRECT_PORT, request.url());
+    let provider = oidc::find_provider(config_dir, &token.provider)?;
+    let client = OidcClient::discover(provider)?;
+    let refreshed = client.oauth().refresh(&refresh_token)?;
+    let stored = store_token(app, &token.provider, refreshed, Some(refresh_token))?;
 
-        if url_string.contains("favicon.ico") {
-            let _ = request.respond(Response::empty(404));
-            continue;
-        }
+    Ok(stored.access_token)
+}
 
-        let url = Url::parse(&url_string).map_err(|_| "Failed to parse callback URL")?;
-        let code_pair = url.query_pairs().find(|(key, _)| key == "code");
-
-        if let Some((_, code)) = code_pair {
-            let client = reqwest::blocking::Client::new();
-            let token_res = client
-                .post(&secrets.token_uri)
-                .form(&[
-                    ("client_id", &secrets.client_id),
-                    ("client_secret", &secrets.client_secret),
-                    ("code", &code.to_string()),
-                    ("grant_type", &"authorization_code".to_string()),
-                    ("redirect_uri", &REDIRECT_URI.to_string()),
-                ])
-                .send()
-                .map_err(|e| format!("Token Exchange Failed: {}", e))?;
-
-            if !token_res.status().is_success() {
-                return respond_html(
-                    request,
-                    "Auth Failed",
-                    "Google refused the code exchange.",
-                    true,
-                );
-            }
+/// Revokes the stored grant at the provider and removes local session data.
+/// Local data is removed even when the provider cannot be reached.
+pub fn sign_out(app: &AppHandle, config_dir: &Path) {
+    let _guard = REFRESH_LOCK.lock();
+
+    if let Some(token) = load_token(app) {
+        let revoked = oidc::find_provider(config_dir, &token.provider)
+            .and_then(OidcClient::discover)
+            .and_then(|client| {
+                client
+                    .oauth()
+                    .revoke(token.refresh_token.as_ref().unwrap_or(&token.access_token))
+            });
+        if let Err(e) = revoked {
+            eprintln!("Failed to revoke token: {}", e);
+        }
+    }
 
-            let token_data: TokenResponse = token_res.json().map_err(|e| e.to_string())?;
-
-            let profile_res = client
-                .get(USER_INFO_URL)
-                .bearer_auth(token_data.access_token)
-                .send()
-                .map_err(|e| format!("Profile Fetch Failed: {}", e))?;
-
-            let profile: UserProfile = profile_res.json().map_err(|e| e.to_string())?;
-
-            let name = profile
-                .names
-                .and_then(|n| n.first().and_then(|x| x.display_name.clone()))
-                .unwrap_or("Spatial User".to_string());
-            let email = profile
-                .email_addresses
-                .and_then(|e| e.first().and_then(|x| x.value.clone()))
-                .unwrap_or_default();
-
-            let mut avatar = profile
-                .photos
-                .and_then(|p| p.first().and_then(|x| x.url.clone()))
-                .unwrap_or_default();
-            if avatar.starts_with("http://") {
-                avatar = avatar.replace("http://", "https://");
-            }
+    let _ = fs::remove_file(config_dir.join(TOKEN_FILE));
+    let _ = fs::remove_file(config_dir.join(avatar::PROFILE_FILE));
+    avatar::remove_avatar(config_dir);
+}
 
-            let user_data = SavedProfile {
-                name,
-                email,
-                avatar,
-            };
-            let profile_path = config_dir.join("profile.json");
-            let mut file = File::create(profile_path).map_err(|e| e.to_string())?;
-            serde_json::to_writer_pretty(&mut file, &user_data).map_err(|e| e.to_string())?;
-            file.flush().map_err(|e| e.to_string())?;
+pub fn start_auth_flow(
+    app: AppHandle,
+    config_dir: PathBuf,
+    provider_id: &str,
+    timeout: Duration,
+    cancel: &AtomicBool,
+) -> Result<(), String> {
+    let provider = oidc::find_provider(&config_dir, provider_id)?;
+    let client = OidcClient::discover(provider)?;
+    let oauth_client = client.oauth();
+    let provider_name = client.provider.name.clone();
+
+    let server = LoopbackServer::bind()?;
+    let redirect_uri = server.redirect_uri();
+    let pkce = Pkce::generate();
+    let state = oauth::random_token(16);
+    let nonce = oauth::random_token(16);
+
+    let mut params: Vec<(&str, &str)> = client
+        .provider
+        .auth_params
+        .iter()
+        .map(|(key, value)| (key.as_str(), value.as_str()))
+        .collect();
+    params.push(("nonce", nonce.as_str()));
+
+    let auth_url = oauth_client.authorize_url(
+        &redirect_uri,
+        &client.provider.scopes,
+        &state,
+        &pkce,
+        &params,
+    )?;
+
+    opener::open(auth_url.as_str()).map_err(|e| e.to_string())?;
+
+    let _ = app.emit(
+        "auth-waiting",
+        serde_json::json!({
+            "provider": provider_id,
+            "redirectUri": redirect_uri,
+            "timeoutSecs": timeout.as_secs()
+        }),
+    );
 
-            let _ = app.emit("auth-success", &user_data);
+    let callback = server.wait_for_callback(&state, timeout, cancel)?;
+    let request = callback.request;
 
-            let _ = respond_html(
-                request,
-                "Authentication Successful",
-                "<p>Spatialshot is now connected to your Google Account.</p><p>You can close this tab.</p>",
-                false,
-            );
+    let code = match callback.code {
+        Ok(code) => code,
+        Err(e) => {
+            let _ = respond_html(request, "Authentication Failed", &e, true);
+            return Err(e);
+        }
+    };
 
-            return Ok(());
-        } else {
+    let token_data = match oauth_client.exchange_code(&code, &pkce.verifier, &redirect_uri) {
+        Ok(token) => token,
+        Err(e) => {
             let _ = respond_html(
                 request,
-                "Authentication Failed",
-                "No authorization code found.",
+                "Auth Failed",
+                &format!("{} refused the code exchange.", provider_name),
                 true,
             );
-
-            return Ok(());
+            return Err(e);
+        }
+    };
+
+    let claims = match token_data
+        .id_token
+        .as_deref()
+        .ok_or_else(|| "Provider did not return an ID token".to_string())
+        .and_then(|id_token| client.validate_id_token(id_token, Some(&nonce)))
+    {
+        Ok(claims) => claims,
+        Err(e) => {
+            let _ = respond_html(request, "Authentication Failed", &e, true);
+            return Err(e);
         }
+    };
+
+    store_token(&app, provider_id, token_data, None)?;
+
+    let name = claims
+        .name
+        .or(claims.preferred_username)
+        .unwrap_or("Spatial User".to_string());
+    let email = claims.email.unwrap_or_default();
+
+    let mut avatar_url = claims.picture.unwrap_or_default();
+    if avatar_url.starts_with("http://") {
+        avatar_url = avatar_url.replace("http://", "https://");
     }
 
-    Ok(())
+    let avatar = if avatar_url.is_empty() {
+        avatar::remove_avatar(&config_dir);
+        String::new()
+    } else {
+        match avatar::cache_avatar(&config_dir, &avatar_url) {
+            Ok(()) => avatar::AVATAR_FILE.to_string(),
+            Err(e) => {
+                eprintln!("{}", e);
+                avatar::remove_avatar(&config_dir);
+                String::new()
+            }
+        }
+    };
+
+    let user_data = SavedProfile {
+        name,
+        email,
+        avatar,
+        avatar_url,
+        provider: provider_id.to_string(),
+        issuer: claims.iss,
+        subject: claims.sub,
+    };
+    let profile_path = config_dir.join(avatar::PROFILE_FILE);
+    let mut file = File::create(profile_path).map_err(|e| e.to_string())?;
+    serde_json::to_writer_pretty(&mut file, &user_data).map_err(|e| e.to_string())?;
+    file.flush().map_err(|e| e.to_string())?;
+
+    let mut payload = serde_json::to_value(&user_data).map_err(|e| e.to_string())?;
+    avatar::resolve_profile_avatar(&config_dir, &mut payload);
+    let _ = app.emit("auth-success", payload);
+
+    respond_html(
+        request,
+        "Authentication Successful",
+        &format!(
+            "<p>Spatialshot is now connected to your {} account.</p><p>You can close this tab.</p>",
+            provider_name
+        ),
+        false,
+    )
 }
 
 fn respond_html(
@@ -0,0 +1,120 @@
+/*
+ * @license
+ * Copyright 2025 a7mddra
+ * SPDX-License-Identifier: Apache-2.0
+ */
+
+use base64::{engine::general_purpose, Engine as _};
+use image::imageops::FilterType;
+use image::ImageFormat;
+use serde_json::Value;
+use std::fs;
+use std::path::Path;
+use std::time::Duration;
+
+pub const AVATAR_FILE: &str = "avatar.png";
+pub const PROFILE_FILE: &str = "profile.json";
+const AVATAR_SIZE: u32 = 128;
+const DOWNLOAD_TIMEOUT: Duration = Duration::from_secs(15);
+
+/// Downloads the profile photo, crops it to a square thumbnail and stores it
+/// as `avatar.png` in the config dir, replacing any previous copy.
+pub fn cache_avatar(config_dir: &Path, url: &str) -> Result<(), String> {
+    let bytes = reqwest::blocking::Client::builder()
+        .timeout(DOWNLOAD_TIMEOUT)
+        .build()
+        .map_err(|e| e.to_string())?
+        .get(url)
+        .send()
+        .and_then(|res| res.error_for_status())
+        .and_then(|res| res.bytes())
+        .map_err(|e| format!("Avatar download failed: {}", e))?;
+
+    let thumbnail = image::load_from_memory(&bytes)
+        .map_err(|e| format!("Avatar decode failed: {}", e))?
+        .resize_to_fill(AVATAR_SIZE, AVATAR_SIZE, FilterType::Lanczos3);
+
+    let tmp_path = config_dir.join(format!("{}.tmp", AVATAR_FILE));
+    thumbnail
+        .save_with_format(&tmp_path, ImageFormat::Png)
+        .map_err(|e| format!("Failed to save avatar: {}", e))?;
+    fs::rename(&tmp_path, config_dir.join(AVATAR_FILE)).map_err(|e| e.to_string())
+}
+
+pub fn avatar_data_url(config_dir: &Path) -> Option<String> {
+    let bytes = fs::read(config_dir.join(AVATAR_FILE)).ok()?;
+    Some(format!(
+        "data:image/png;base64,{}",
+        general_purpose::STANDARD.encode(bytes)
+    ))
+}
+
+pub fn remove_avatar(config_dir: &Path) {
+    let _ = fs::remove_file(config_dir.join(AVATAR_FILE));
+}
+
+/// Swaps the stored avatar reference in a profile for something the webview
+/// can display without network access. Profiles saved before avatars were
+/// cached hold the photo's URL in `avatar`; that photo is cached and the
+/// profile saved again the first time it is resolved.
+pub fn resolve_profile_avatar(config_dir: &Path, profile: &mut Value) {
+    let Some(fields) = profile.as_object_mut() else {
+        return;
+    };
+    let Some(avatar) = fields.get("avatar").and_then(Value::as_str) else {
+        return;
+    };
+
+    if avatar.starts_with("https://") || avatar.starts_with("http://") {
+        let url = avatar.to_string();
+        if let Err(e) = cache_avatar(config_dir, &url) {
+            eprintln!("{}", e);
+            return;
+        }
+        fields.insert("avatar".into(), AVATAR_FILE.into());
+        fields.insert("avatarUrl".into(), url.into());
+        if let Err(e) = save_profile(config_dir, profile) {
+            eprintln!("Failed to save profile: {}", e);
+        }
+        resolve_profile_avatar(config_dir, profile);
+    } else if avatar == AVATAR_FILE {
+        fields.insert(
+            "avatar".into(),
+            avatar_data_url(config_dir).unwrap_or_default().into(),
+        );
+    }
+}
+
+fn save_profile(config_dir: &Path, profile: &Value) -> Result<(), String> {
+    let json = serde_json::to_string_pretty(profile).map_err(|e| e.to_string())?;
+    fs::write(config_dir.join(PROFILE_FILE), json).map_err(|e| e.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn resolves_only_profile_objects() {
+        let dir = std::env::temp_dir().join(format!("spatialshot_avatar_{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join(AVATAR_FILE), b"png").unwrap();
+
+        let mut not_a_profile = json!(["avatar.png"]);
+        resolve_profile_avatar(&dir, &mut not_a_profile);
+        assert_eq!(not_a_profile, json!(["avatar.png"]));
+
+        let mut profile = json!({ "name": "A", "avatar": AVATAR_FILE });
+        resolve_profile_avatar(&dir, &mut profile);
+        assert_eq!(profile["avatar"], "data:image/png;base64,cG5n");
+
+        // An unreachable photo leaves the old profile as it was.
+        let mut legacy = json!({ "avatar": "http://127.0.0.1:1/photo.png" });
+        resolve_profile_avatar(&dir, &mut legacy);
+        assert_eq!(legacy, json!({ "avatar": "http://127.0.0.1:1/photo.png" }));
+        assert!(!dir.join(PROFILE_FILE).exists());
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,531 @@
+/*
+ * @license
+ * Copyright 2025 a7mddra
+ * SPDX-License-Identifier: Apache-2.0
+ */
+
+//! Headless batch runs: one prompt over many images, written as one JSON
+//! line per image. Each line is flushed as soon as its image is done, so an
+//! interrupted run resumes by skipping the files that already succeeded.
+
+use base64::{engine::general_purpose, Engine as _};
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::fs::{self, File, OpenOptions};
+use std::io::{BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::services::image::preprocess::{self, PreprocessOptions};
+use crate::services::llm::{Message, ModelBackend, Part, Role, TokenUsage};
+
+pub const DEFAULT_CONCURRENCY: usize = 4;
+pub const DEFAULT_RETRIES: u32 = 3;
+const DEFAULT_BACKOFF: Duration = Duration::from_secs(1);
+const MAX_BACKOFF: Duration = Duration::from_secs(60);
+
+/// Extensions picked up when a directory is given as an input.
+const IMAGE_EXTENSIONS: &[&str] = &["png", "jpg", "jpeg", "webp", "gif", "bmp", "tif", "tiff"];
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct BatchOptions {
+    /// Files, directories (searched recursively) and glob patterns.
+    pub inputs: Vec<String>,
+    pub prompt: String,
+    pub provider: String,
+    /// `None` uses the model from the settings.
+    pub model: Option<String>,
+    pub output: PathBuf,
+    pub concurrency: usize,
+    /// Further attempts after a failed request.
+    pub retries: u32,
+    /// Wait before the first retry. It doubles with each one after.
+    pub backoff: Duration,
+}
+
+impl BatchOptions {
+    pub fn new(inputs: Vec<String>, prompt: String, provider: String, output: PathBuf) -> Self {
+        Self {
+            inputs,
+            prompt,
+            provider,
+            model: None,
+            output,
+            concurrency: DEFAULT_CONCURRENCY,
+            retries: DEFAULT_RETRIES,
+            backoff: DEFAULT_BACKOFF,
+        }
+    }
+}
+
+/// One line of the output file.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct BatchRecord {
+    pub file: String,
+    pub model: String,
+    pub answer: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    /// Time taken by the last request, in milliseconds.
+    pub latency_ms: u64,
+    pub usage: Option<TokenUsage>,
+    pub attempts: u32,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct BatchSummary {
+    pub succeeded: usize,
+    pub failed: usize,
+    /// Files a previous run already answered.
+    pub skipped: usize,
+}
+
+/// Turns an image file into the bytes to send, e.g. by preprocessing it.
+pub type Prepare<'a> = dyn Fn(&Path) -> Result<Vec<u8>, String> + Sync + 'a;
+
+/// Reads an image and runs it through the preprocessing pipeline.
+pub fn preprocess_file(path: &Path, options: &PreprocessOptions) -> Result<Vec<u8>, String> {
+    let bytes = fs::read(path).map_err(|e| format!("Failed to read file: {}", e))?;
+    preprocess::preprocess(bytes, options).map(|(bytes, _)| bytes)
+}
+
+/// Resolves the inputs to absolute file paths, in order and without
+/// duplicates.
+pub fn expand_inputs(inputs: &[String]) -> Result<Vec<PathBuf>, String> {
+    let mut files = Vec::new();
+    for input in inputs {
+        if input.contains(&['*', '?', '['][..]) {
+            let entries =
+                glob::glob(input).map_err(|e| format!("Invalid pattern {}: {}", input, e))?;
+            files.extend(entries.filter_map(Result::ok).filter(|path| path.is_file()));
+            continue;
+        }
+
+        let path = PathBuf::from(input);
+        if path.is_dir() {
+            walk_images(&path, &mut files)?;
+        } else if path.is_file() {
+            files.push(path);
+        } else {
+            return Err(format!("No such file or directory: {}", input));
+        }
+    }
+
+    let mut seen = HashSet::new();
+    let mut unique = Vec::new();
+    for file in files {
+        let file = fs::canonicalize(&file)
+            .map_err(|e| format!("Failed to resolve {}: {}", file.display(), e))?;
+        if seen.insert(file.clone()) {
+            unique.push(file);
+        }
+    }
+    if unique.is_empty() {
+        return Err("No images matched the inputs".into());
+    }
+    Ok(unique)
+}
+
+fn walk_images(dir: &Path, files: &mut Vec<PathBuf>) -> Result<(), String> {
+    let mut entries: Vec<PathBuf> = fs::read_dir(dir)
+        .map_err(|e| format!("Failed to read {}: {}", dir.display(), e))?
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .collect();
+    entries.sort();
+
+    for path in entries {
+        if path.is_dir() {
+            walk_images(&path, files)?;
+        } else if is_image(&path) {
+            files.push(path);
+        }
+    }
+    Ok(())
+}
+
+fn is_image(path: &Path) -> bool {
+    path.extension()
+        .and_then(|ext| ext.to_str())
+        .is_some_and(|ext| IMAGE_EXTENSIONS.contains(&ext.to_ascii_lowercase().as_str()))
+}
+
+/// Files that already have an answer in `output`. Failed lines do not
+/// count, so they are tried again, and a line cut short by an interrupted
+/// run is ignored.
+pub fn completed(output: &Path) -> Result<HashSet<String>, String> {
+    let file = match File::open(output) {
+        Ok(file) => file,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(HashSet::new()),
+        Err(e) => return Err(format!("Failed to read {}: {}", output.display(), e)),
+    };
+
+    Ok(BufReader::new(file)
+        .lines()
+        .map_while(Result::ok)
+        .filter_map(|line| serde_json::from_str::<BatchRecord>(&line).ok())
+        .filter(|record| record.error.is_none())
+        .map(|record| record.file)
+        .collect())
+}
+
+/// Sends `files` to `backend` with up to `options.concurrency` requests in
+/// flight and appends a record for each to `options.output`.
+pub fn run(
+    backend: &dyn ModelBackend,
+    model: &str,
+    files: &[PathBuf],
+    options: &BatchOptions,
+    prepare: &Prepare,
+) -> Result<BatchSummary, String> {
+    let done = completed(&options.output)?;
+    let pending: Vec<&PathBuf> = files
+        .iter()
+        .filter(|file| !done.contains(file.to_string_lossy().as_ref()))
+        .collect();
+    let mut summary = BatchSummary {
+        skipped: files.len() - pending.len(),
+        ..BatchSummary::default()
+    };
+    if pending.is_empty() {
+        return Ok(summary);
+    }
+
+    let output = open_output(&options.output)?;
+    let writer = Mutex::new(output);
+    let next = AtomicUsize::new(0);
+    let finished = AtomicUsize::new(0);
+    let failed = AtomicUsize::new(0);
+    let write_error = Mutex::new(None);
+
+    thread::scope(|scope| {
+        for _ in 0..options.concurrency.clamp(1, pending.len()) {
+            scope.spawn(|| {
+                while let Some(path) = pending.get(next.fetch_add(1, Ordering::SeqCst)) {
+                    let record = process(backend, model, path, options, prepare);
+                    let line = serde_json::to_string(&record).map_err(|e| e.to_string());
+
+                    let mut output = writer.lock();
+                    let written = line.and_then(|line| {
+                        writeln!(output, "{}", line)
+                            .and_then(|_| output.flush())
+                            .map_err(|e| {
+                                format!("Failed to write {}: {}", options.output.display(), e)
+                            })
+                    });
+                    if let Err(e) = written {
+                        write_error.lock().get_or_insert(e);
+                        break;
+                    }
+
+                    let count = finished.fetch_add(1, Ordering::SeqCst) + 1;
+                    match &record.error {
+                        Some(e) => {
+                            failed.fetch_add(1, Ordering::SeqCst);
+                            eprintln!(
+                                "[{}/{}] {} failed: {}",
+                                count,
+                                pending.len(),
+                                record.file,
+                                e
+                            );
+                        }
+                        None => eprintln!(
+                            "[{}/{}] {} ({} ms)",
+                            count,
+                            pending.len(),
+                            record.file,
+                            record.latency_ms
+                        ),
+                    }
+                }
+            });
+        }
+    });
+
+    if let Some(e) = write_error.into_inner() {
+        return Err(e);
+    }
+    summary.failed = failed.into_inner();
+    summary.succeeded = finished.into_inner() - summary.failed;
+    Ok(summary)
+}
+
+fn open_output(path: &Path) -> Result<File, String> {
+    if let Some(dir) = path.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+        fs::create_dir_all(dir)
+            .map_err(|e| format!("Failed to create {}: {}", dir.display(), e))?;
+    }
+    OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .map_err(|e| format!("Failed to open {}: {}", path.display(), e))
+}
+
+/// Asks about one image, retrying failed requests with exponential backoff.
+fn process(
+    backend: &dyn ModelBackend,
+    model: &str,
+    path: &Path,
+    options: &BatchOptions,
+    prepare: &Prepare,
+) -> BatchRecord {
+    let mut record = BatchRecord {
+        file: path.to_string_lossy().into_owned(),
+        model: model.to_string(),
+        answer: None,
+        error: None,
+        latency_ms: 0,
+        usage: None,
+        attempts: 0,
+    };
+    let contents = match prepare(path).and_then(|bytes| image_message(&bytes, &options.prompt)) {
+        Ok(message) => [message],
+        Err(e) => {
+            record.error = Some(e);
+            return record;
+        }
+    };
+
+    let cancel = AtomicBool::new(false);
+    loop {
+        record.attempts += 1;
+        let started = Instant::now();
+        let result = backend.stream_chat(model, None, &contents, &cancel, &mut |_| {});
+        record.latency_ms = started.elapsed().as_millis() as u64;
+
+        match result {
+            Ok(reply) => {
+                record.answer = Some(reply.text);
+                record.usage = reply.usage;
+                record.error = None;
+                return record;
+            }
+            Err(e) => {
+                let retry = record.attempts <= options.retries && is_retryable(&e);
+                record.error = Some(e);
+                if !retry {
+                    return record;
+                }
+                thread::sleep(backoff(options.backoff, record.attempts));
+            }
+        }
+    }
+}
+
+fn image_message(bytes: &[u8], prompt: &str) -> Result<Message, String> {
+    let format = image::guess_format(bytes).map_err(|e| format!("Unsupported image: {}", e))?;
+    Ok(Message {
+        role: Role::User,
+        parts: vec![
+            Part::Image {
+                mime_type: format.to_mime_type().to_string(),
+                data: general_purpose::STANDARD.encode(bytes),
+            },
+            Part::Text {
+                text: prompt.to_string(),
+            },
+        ],
+    })
+}
+
+/// The wait before retry number `attempt`, starting at one.
+fn backoff(base: Duration, attempt: u32) -> Duration {
+    base.saturating_mul(1 << attempt.saturating_sub(1).min(16))
+        .min(MAX_BACKOFF)
+}
+
+/// Whether a backend error may go away on its own. Client errors other
+/// than timeouts and rate limits would only fail again.
+fn is_retryable(error: &str) -> bool {
+    let status = error
+        .split_once(" API error ")
+        .and_then(|(_, rest)| rest.get(..3))
+        .and_then(|code| code.parse::<u16>().ok());
+    match status {
+        Some(status) => status >= 500 || status == 408 || status == 429,
+        None => true,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::llm::gemini::GeminiBackend;
+    use crate::testing::mock_server_sequence;
+    use image::{ImageFormat, RgbImage};
+
+    const ANSWER: &str =
+        "data: {\"candidates\":[{\"content\":{\"parts\":[{\"text\":\"A square\"}]}}],\
+        \"usageMetadata\":{\"promptTokenCount\":258,\"candidatesTokenCount\":2}}\n\n";
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir =
+            std::env::temp_dir().join(format!("spatialshot_batch_{}_{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    fn write_png(path: &Path) {
+        RgbImage::new(4, 4)
+            .save_with_format(path, ImageFormat::Png)
+            .unwrap();
+    }
+
+    fn options(dir: &Path, retries: u32) -> BatchOptions {
+        BatchOptions {
+            retries,
+            backoff: Duration::from_millis(1),
+            concurrency: 2,
+            ..BatchOptions::new(
+                vec![dir.join("shots").to_string_lossy().into_owned()],
+                "What is this?".into(),
+                "gemini".into(),
+                dir.join("out.jsonl"),
+            )
+        }
+    }
+
+    fn records(path: &Path) -> Vec<BatchRecord> {
+        fs::read_to_string(path)
+            .unwrap()
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect()
+    }
+
+    fn read_bytes(path: &Path) -> Result<Vec<u8>, String> {
+        fs::read(path).map_err(|e| e.to_string())
+    }
+
+    #[test]
+    fn expands_directories_and_globs() {
+        let dir = temp_dir("expand");
+        fs::create_dir_all(dir.join("shots/nested")).unwrap();
+        write_png(&dir.join("shots/a.png"));
+        write_png(&dir.join("shots/nested/b.PNG"));
+        fs::write(dir.join("shots/notes.txt"), "not an image").unwrap();
+
+        let files = expand_inputs(&[
+            dir.join("shots").to_string_lossy().into_owned(),
+            dir.join("shots/*.png").to_string_lossy().into_owned(),
+        ])
+        .unwrap();
+        let names: Vec<_> = files
+            .iter()
+            .map(|file| file.file_name().unwrap().to_string_lossy().into_owned())
+            .collect();
+        assert_eq!(names, ["a.png", "b.PNG"]);
+
+        assert!(expand_inputs(&[dir.join("missing.png").to_string_lossy().into_owned()]).is_err());
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn retries_and_resumes() {
+        let dir = temp_dir("run");
+        fs::create_dir_all(dir.join("shots")).unwrap();
+        write_png(&dir.join("shots/a.png"));
+        fs::write(dir.join("shots/b.png"), "not an image").unwrap();
+        let options = options(&dir, 2);
+        let files = expand_inputs(&options.inputs).unwrap();
+
+        // The first request is rate limited, then answered.
+        let (base_url, server) = mock_server_sequence(vec![
+            (
+                429,
+                "application/json",
+                "{\"error\":{\"message\":\"Slow down\"}}",
+            ),
+            (200, "text/event-stream", ANSWER),
+        ]);
+        let backend = GeminiBackend::new(&base_url, "test-key").unwrap();
+        let summary = run(&backend, "gemini-test", &files, &options, &read_bytes).unwrap();
+        assert_eq!(
+            summary,
+            BatchSummary {
+                succeeded: 1,
+                failed: 1,
+                skipped: 0
+            }
+        );
+        let received = server.join().unwrap();
+        assert_eq!(
+            received[1].body["contents"][0]["parts"][1]["text"],
+            "What is this?"
+        );
+
+        let mut lines = records(&options.output);
+        lines.sort_by(|a, b| a.file.cmp(&b.file));
+        assert_eq!(lines[0].answer.as_deref(), Some("A square"));
+        assert_eq!(lines[0].attempts, 2);
+        assert_eq!(lines[0].usage.unwrap().input_tokens, 258);
+        assert!(lines[1]
+            .error
+            .as_deref()
+            .unwrap()
+            .starts_with("Unsupported image"));
+
+        // A second run only tries the file that failed.
+        write_png(&dir.join("shots/b.png"));
+        let (base_url, server) = mock_server_sequence(vec![(200, "text/event-stream", ANSWER)]);
+        let backend = GeminiBackend::new(&base_url, "test-key").unwrap();
+        let summary = run(&backend, "gemini-test", &files, &options, &read_bytes).unwrap();
+        assert_eq!(
+            summary,
+            BatchSummary {
+                succeeded: 1,
+                failed: 0,
+                skipped: 1
+            }
+        );
+        server.join().unwrap();
+        assert_eq!(completed(&options.output).unwrap().len(), 2);
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn gives_up_on_client_errors() {
+        let dir = temp_dir("fail");
+        fs::create_dir_all(dir.join("shots")).unwrap();
+        write_png(&dir.join("shots/a.png"));
+        let options = options(&dir, 3);
+        let files = expand_inputs(&options.inputs).unwrap();
+
+        let (base_url, server) = mock_server_sequence(vec![(
+            400,
+            "application/json",
+            "{\"error\":{\"message\":\"Bad model\"}}",
+        )]);
+        let backend = GeminiBackend::new(&base_url, "test-key").unwrap();
+        let summary = run(&backend, "gemini-test", &files, &options, &read_bytes).unwrap();
+        assert_eq!(summary.failed, 1);
+        server.join().unwrap();
+
+        let lines = records(&options.output);
+        assert_eq!(lines[0].attempts, 1);
+        assert_eq!(
+            lines[0].error.as_deref(),
+            Some("Gemini API error 400: Bad model")
+        );
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn backs_off_exponentially() {
+        let base = Duration::from_secs(1);
+        assert_eq!(backoff(base, 1), Duration::from_secs(1));
+        assert_eq!(backoff(base, 3), Duration::from_secs(4));
+        assert_eq!(backoff(base, 40), MAX_BACKOFF);
+        assert!(is_retryable("Gemini API error 503: Unavailable"));
+        assert!(is_retryable("Gemini request failed: connection refused"));
+        assert!(!is_retryable(
+            "OpenAI-compatible API error 401: Unauthorized"
+        ));
+    }
+}
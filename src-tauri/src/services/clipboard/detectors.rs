@@ -0,0 +1,94 @@
+/*
+ * @license
+ * Copyright 2025 a7mddra
+ * SPDX-License-Identifier: Apache-2.0
+ */
+
+use regex::Regex;
+
+/// Recognizes an API key for one provider in copied text.
+pub trait KeyDetector: Send + Sync {
+    fn provider(&self) -> &str;
+
+    /// Pattern the whole (trimmed) clipboard text must match.
+    fn pattern(&self) -> &Regex;
+
+    fn detect(&self, text: &str) -> bool {
+        self.pattern().is_match(text)
+    }
+}
+
+pub struct RegexDetector {
+    provider: String,
+    pattern: Regex,
+}
+
+impl RegexDetector {
+    /// `pattern` is anchored on both ends, so it describes the key itself.
+    pub fn new(provider: &str, pattern: &str) -> Result<Self, String> {
+        let pattern = Regex::new(&format!("^(?:{})$", pattern))
+            .map_err(|e| format!("Invalid pattern for {}: {}", provider, e))?;
+
+        Ok(Self {
+            provider: provider.to_string(),
+            pattern,
+        })
+    }
+}
+
+impl KeyDetector for RegexDetector {
+    fn provider(&self) -> &str {
+        &self.provider
+    }
+
+    fn pattern(&self) -> &Regex {
+        &self.pattern
+    }
+}
+
+/// Ordered set of detectors; the first match wins.
+pub struct DetectorRegistry {
+    detectors: Vec<Box<dyn KeyDetector>>,
+}
+
+impl DetectorRegistry {
+    pub fn new() -> Self {
+        Self {
+            detectors: Vec::new(),
+        }
+    }
+
+    pub fn with_defaults() -> Self {
+        let mut registry = Self::new();
+        for (provider, pattern) in DEFAULT_PATTERNS {
+            registry.register(Box::new(
+                RegexDetector::new(provider, pattern).expect("built-in key pattern"),
+            ));
+        }
+        registry
+    }
+
+    pub fn register(&mut self, detector: Box<dyn KeyDetector>) {
+        self.detectors.push(detector);
+    }
+
+    pub fn detect(&self, text: &str) -> Option<&str> {
+        self.detectors
+            .iter()
+            .find(|d| d.detect(text))
+            .map(|d| d.provider())
+    }
+}
+
+impl Default for DetectorRegistry {
+    fn default() -> Self {
+        Self::with_defaults()
+    }
+}
+
+/// Built-in key formats: Google API keys are `AIza` followed by 35
+/// URL-safe characters; ImgBB keys are 32 alphanumerics.
+pub const DEFAULT_PATTERNS: &[(&str, &str)] = &[
+    ("gemini", r"AIza[0-9A-Za-z_\-]{35}"),
+    ("imgbb", r"[0-9A-Za-z]{32}"),
+];
@@ -0,0 +1,174 @@
+/*
+ * @license
+ * Copyright 2025 a7mddra
+ * SPDX-License-Identifier: Apache-2.0
+ */
+
+use arboard::{Clipboard, ImageData};
+use image::{DynamicImage, ImageFormat, RgbaImage};
+use sha2::{Digest, Sha256};
+use std::borrow::Cow;
+use std::collections::VecDeque;
+use std::io::Cursor;
+
+/// An image read from the clipboard, identified by a hash of its pixels.
+pub struct ClipboardImage {
+    pub hash: String,
+    pub width: u32,
+    pub height: u32,
+    pub png: Vec<u8>,
+}
+
+pub fn read_image() -> Result<RgbaImage, String> {
+    let data = Clipboard::new()
+        .map_err(|e| e.to_string())?
+        .get_image()
+        .map_err(|e| format!("No image on the clipboard: {}", e))?;
+
+    RgbaImage::from_raw(
+        data.width as u32,
+        data.height as u32,
+        data.bytes.into_owned(),
+    )
+    .ok_or_else(|| "Clipboard image has an unexpected size".to_string())
+}
+
+/// Content hash over the decoded RGBA pixels, so the same picture hashes
+/// equally whichever format the source application offered.
+pub fn image_hash(image: &RgbaImage) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(image.width().to_le_bytes());
+    hasher.update(image.height().to_le_bytes());
+    hasher.update(image.as_raw());
+    hex::encode(hasher.finalize())
+}
+
+pub fn encode_png(image: &RgbaImage) -> Result<Vec<u8>, String> {
+    let mut png = Cursor::new(Vec::new());
+    image
+        .write_to(&mut png, ImageFormat::Png)
+        .map_err(|e| e.to_string())?;
+    Ok(png.into_inner())
+}
+
+/// Reads the clipboard image and returns it encoded as PNG.
+pub fn read_png() -> Result<Vec<u8>, String> {
+    encode_png(&read_image()?)
+}
+
+pub fn crop(
+    image: &DynamicImage,
+    x: u32,
+    y: u32,
+    width: u32,
+    height: u32,
+) -> Result<DynamicImage, String> {
+    if width == 0 || height == 0 {
+        return Err("Region must not be empty".into());
+    }
+    let fits = x
+        .checked_add(width)
+        .zip(y.checked_add(height))
+        .is_some_and(|(right, bottom)| right <= image.width() && bottom <= image.height());
+    if !fits {
+        return Err("Region is outside the image".into());
+    }
+
+    Ok(image.crop_imm(x, y, width, height))
+}
+
+/// Hashes remembered per set, so a long session does not grow it without
+/// limit. Older hashes are forgotten first.
+const RECENT_HASHES: usize = 64;
+
+/// The most recent image hashes, oldest first.
+#[derive(Default)]
+pub struct RecentHashes(VecDeque<String>);
+
+impl RecentHashes {
+    /// Remembers `hash`. Returns false if it was already remembered.
+    pub fn insert(&mut self, hash: String) -> bool {
+        if self.contains(&hash) {
+            return false;
+        }
+        if self.0.len() == RECENT_HASHES {
+            self.0.pop_front();
+        }
+        self.0.push_back(hash);
+        true
+    }
+
+    pub fn contains(&self, hash: &str) -> bool {
+        self.0.iter().any(|h| h == hash)
+    }
+}
+
+/// Keeps this process the clipboard owner for images it copies.
+///
+/// On X11 the clipboard is served by the owning process, so the contents
+/// vanish when it exits unless they are handed to a clipboard manager. The
+/// owner holds one `Clipboard` for the whole session and `release` drops it,
+/// which performs that handoff.
+#[derive(Default)]
+pub struct ClipboardOwner {
+    #[cfg(target_os = "linux")]
+    clipboard: Option<Clipboard>,
+    copied: RecentHashes,
+}
+
+impl ClipboardOwner {
+    pub fn set_image(&mut self, image: &DynamicImage) -> Result<(), String> {
+        let rgba = image.to_rgba8();
+        self.copied.insert(image_hash(&rgba));
+        let data = ImageData {
+            width: rgba.width() as usize,
+            height: rgba.height() as usize,
+            bytes: Cow::Owned(rgba.into_raw()),
+        };
+
+        #[cfg(target_os = "linux")]
+        {
+            if self.clipboard.is_none() {
+                self.clipboard = Some(Clipboard::new().map_err(|e| e.to_string())?);
+            }
+            let clipboard = self.clipboard.as_mut().unwrap();
+            clipboard.set_image(data).map_err(|e| e.to_string())
+        }
+
+        #[cfg(not(target_os = "linux"))]
+        {
+            Clipboard::new()
+                .and_then(|mut clipboard| clipboard.set_image(data))
+                .map_err(|e| e.to_string())
+        }
+    }
+
+    /// Whether `hash` is an image this process put on the clipboard.
+    pub fn copied(&self, hash: &str) -> bool {
+        self.copied.contains(hash)
+    }
+
+    pub fn release(&mut self) {
+        #[cfg(target_os = "linux")]
+        drop(self.clipboard.take());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn recent_hashes_forget_the_oldest() {
+        let mut recent = RecentHashes::default();
+        for i in 0..RECENT_HASHES {
+            assert!(recent.insert(i.to_string()));
+        }
+        assert!(!recent.insert("0".into()));
+
+        assert!(recent.insert("new".into()));
+        assert!(!recent.contains("0"));
+        assert!(recent.contains("1"));
+        assert!(recent.contains("new"));
+    }
+}
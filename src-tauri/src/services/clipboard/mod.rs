@@ -0,0 +1,174 @@
+/*
+ * @license
+ * Copyright 2025 a7mddra
+ * SPDX-License-Identifier: Apache-2.0
+ */
+
+pub mod detectors;
+pub mod image;
+pub mod source;
+
+#[cfg(target_os = "linux")]
+mod wayland;
+#[cfg(target_os = "linux")]
+mod x11;
+
+use parking_lot::Mutex;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+
+use detectors::DetectorRegistry;
+use image::{ClipboardImage, ClipboardOwner, RecentHashes};
+use source::ChangeSource;
+
+/// How long a single wait may block, which bounds how quickly `stop` returns.
+const STOP_CHECK_INTERVAL: Duration = Duration::from_millis(250);
+
+pub struct KeyMatch {
+    pub provider: String,
+    pub key: String,
+}
+
+pub enum ClipboardEvent {
+    Key(KeyMatch),
+    Image(ClipboardImage),
+}
+
+pub enum WatchMode {
+    /// Report copied text that one of the detectors recognizes as an API key.
+    Keys(DetectorRegistry),
+    /// Report new images, skipping ones `owner` copied itself.
+    Images(Arc<Mutex<ClipboardOwner>>),
+}
+
+/// Background thread that reports interesting clipboard changes.
+/// Dropping the watcher stops the thread and waits for it to exit.
+pub struct ClipboardWatcher {
+    running: Arc<AtomicBool>,
+    handle: Option<JoinHandle<()>>,
+}
+
+impl ClipboardWatcher {
+    pub fn spawn<F>(mode: WatchMode, on_event: F) -> Result<Self, String>
+    where
+        F: Fn(ClipboardEvent) + Send + 'static,
+    {
+        let running = Arc::new(AtomicBool::new(true));
+        let running_flag = running.clone();
+
+        let handle = thread::Builder::new()
+            .name("clipboard-watcher".into())
+            .spawn(move || run(running_flag, source::open_best(), mode, on_event))
+            .map_err(|e| e.to_string())?;
+
+        Ok(Self {
+            running,
+            handle: Some(handle),
+        })
+    }
+
+    pub fn stop(mut self) {
+        self.shutdown();
+    }
+
+    fn shutdown(&mut self) {
+        self.running.store(false, Ordering::SeqCst);
+        if let Some(handle) = self.handle.take() {
+            let _ = handle.join();
+        }
+    }
+}
+
+impl Drop for ClipboardWatcher {
+    fn drop(&mut self) {
+        self.shutdown();
+    }
+}
+
+fn run<F>(running: Arc<AtomicBool>, mut source: Box<dyn ChangeSource>, mode: WatchMode, on_event: F)
+where
+    F: Fn(ClipboardEvent),
+{
+    let mut clipboard = loop {
+        match arboard::Clipboard::new() {
+            Ok(cb) => break cb,
+            Err(e) => {
+                eprintln!("Clipboard init failed, retrying in 1s: {}", e);
+                if !running.load(Ordering::SeqCst) {
+                    return;
+                }
+                thread::sleep(Duration::from_secs(1));
+            }
+        }
+    };
+
+    let mut last_text = clipboard.get_text().unwrap_or_default().trim().to_string();
+    let mut seen_images = RecentHashes::default();
+    if let WatchMode::Images(_) = &mode {
+        if let Ok(current) = image::read_image() {
+            seen_images.insert(image::image_hash(&current));
+        }
+    }
+    println!(
+        "Watcher started ({}). Ignoring current clipboard content.",
+        source.name()
+    );
+
+    while running.load(Ordering::SeqCst) {
+        match source.wait(STOP_CHECK_INTERVAL) {
+            Ok(false) => continue,
+            Ok(true) => {}
+            Err(e) => {
+                eprintln!(
+                    "Clipboard notifications from {} failed, falling back to polling: {}",
+                    source.name(),
+                    e
+                );
+                source = Box::new(source::PollingSource::new());
+                continue;
+            }
+        }
+
+        match &mode {
+            WatchMode::Keys(registry) => {
+                if let Ok(text) = clipboard.get_text() {
+                    let trimmed = text.trim().to_string();
+
+                    if !trimmed.is_empty() && trimmed != last_text {
+                        last_text = trimmed.clone();
+
+                        if let Some(provider) = registry.detect(&trimmed) {
+                            println!("{} Key Detected", provider);
+                            on_event(ClipboardEvent::Key(KeyMatch {
+                                provider: provider.to_string(),
+                                key: trimmed,
+                            }));
+                        }
+                    }
+                }
+            }
+            WatchMode::Images(owner) => {
+                let Ok(current) = image::read_image() else {
+                    continue;
+                };
+
+                let hash = image::image_hash(&current);
+                if !seen_images.insert(hash.clone()) || owner.lock().copied(&hash) {
+                    continue;
+                }
+
+                match image::encode_png(&current) {
+                    Ok(png) => on_event(ClipboardEvent::Image(ClipboardImage {
+                        hash,
+                        width: current.width(),
+                        height: current.height(),
+                        png,
+                    })),
+                    Err(e) => eprintln!("Failed to encode clipboard image: {}", e),
+                }
+            }
+        }
+    }
+}
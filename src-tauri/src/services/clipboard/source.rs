@@ -0,0 +1,79 @@
+/*
+ * @license
+ * Copyright 2025 a7mddra
+ * SPDX-License-Identifier: Apache-2.0
+ */
+
+use std::thread;
+use std::time::{Duration, Instant};
+
+const POLL_INTERVAL: Duration = Duration::from_millis(2000);
+
+/// Something that can tell the watcher the clipboard may have changed.
+pub trait ChangeSource: Send {
+    fn name(&self) -> &'static str;
+
+    /// Waits at most `timeout`. Returns `Ok(true)` when the clipboard may have
+    /// changed and `Ok(false)` when the timeout elapsed without news.
+    fn wait(&mut self, timeout: Duration) -> Result<bool, String>;
+}
+
+/// Fallback for platforms and sessions without change notifications.
+pub struct PollingSource {
+    next_poll: Instant,
+}
+
+impl PollingSource {
+    pub fn new() -> Self {
+        Self {
+            next_poll: Instant::now() + POLL_INTERVAL,
+        }
+    }
+}
+
+impl Default for PollingSource {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ChangeSource for PollingSource {
+    fn name(&self) -> &'static str {
+        "polling"
+    }
+
+    fn wait(&mut self, timeout: Duration) -> Result<bool, String> {
+        let remaining = self.next_poll.saturating_duration_since(Instant::now());
+        if remaining > timeout {
+            thread::sleep(timeout);
+            return Ok(false);
+        }
+
+        thread::sleep(remaining);
+        self.next_poll = Instant::now() + POLL_INTERVAL;
+        Ok(true)
+    }
+}
+
+/// Picks the best notification mechanism for the current session: Wayland
+/// data-control, then X11 XFixes, then polling.
+pub fn open_best() -> Box<dyn ChangeSource> {
+    #[cfg(target_os = "linux")]
+    {
+        if std::env::var_os("WAYLAND_DISPLAY").is_some() {
+            match super::wayland::DataControlSource::connect() {
+                Ok(source) => return Box::new(source),
+                Err(e) => eprintln!("Wayland clipboard notifications unavailable: {}", e),
+            }
+        }
+
+        if std::env::var_os("DISPLAY").is_some() {
+            match super::x11::XFixesSource::connect() {
+                Ok(source) => return Box::new(source),
+                Err(e) => eprintln!("X11 clipboard notifications unavailable: {}", e),
+            }
+        }
+    }
+
+    Box::new(PollingSource::new())
+}
@@ -0,0 +1,175 @@
+/*
+ * @license
+ * Copyright 2025 a7mddra
+ * SPDX-License-Identifier: Apache-2.0
+ */
+
+use std::os::fd::AsRawFd;
+use std::time::Duration;
+use wayland_client::globals::{registry_queue_init, GlobalListContents};
+use wayland_client::protocol::{wl_registry::WlRegistry, wl_seat::WlSeat};
+use wayland_client::{event_created_child, Connection, Dispatch, EventQueue, Proxy, QueueHandle};
+use wayland_protocols_wlr::data_control::v1::client::{
+    zwlr_data_control_device_v1::{self, ZwlrDataControlDeviceV1},
+    zwlr_data_control_manager_v1::ZwlrDataControlManagerV1,
+    zwlr_data_control_offer_v1::ZwlrDataControlOfferV1,
+};
+
+use super::source::ChangeSource;
+
+#[derive(Default)]
+struct State {
+    changed: bool,
+    offer: Option<ZwlrDataControlOfferV1>,
+}
+
+/// Listens for `selection` events from the wlr data-control protocol, which
+/// wlroots compositors and KDE expose to clipboard managers.
+pub struct DataControlSource {
+    conn: Connection,
+    queue: EventQueue<State>,
+    state: State,
+    _device: ZwlrDataControlDeviceV1,
+}
+
+impl DataControlSource {
+    pub fn connect() -> Result<Self, String> {
+        let conn = Connection::connect_to_env().map_err(|e| e.to_string())?;
+        let (globals, mut queue) =
+            registry_queue_init::<State>(&conn).map_err(|e| e.to_string())?;
+        let qh = queue.handle();
+
+        let seat: WlSeat = globals
+            .bind(&qh, 1..=8, ())
+            .map_err(|e| format!("No wl_seat: {}", e))?;
+        let manager: ZwlrDataControlManagerV1 = globals
+            .bind(&qh, 1..=2, ())
+            .map_err(|e| format!("Compositor lacks wlr-data-control: {}", e))?;
+        let device = manager.get_data_device(&seat, &qh, ());
+
+        let mut state = State::default();
+        queue.roundtrip(&mut state).map_err(|e| e.to_string())?;
+        // The compositor announces the current selection on bind; that is not
+        // a change.
+        state.changed = false;
+
+        Ok(Self {
+            conn,
+            queue,
+            state,
+            _device: device,
+        })
+    }
+}
+
+impl ChangeSource for DataControlSource {
+    fn name(&self) -> &'static str {
+        "wayland-data-control"
+    }
+
+    fn wait(&mut self, timeout: Duration) -> Result<bool, String> {
+        self.queue
+            .dispatch_pending(&mut self.state)
+            .map_err(|e| e.to_string())?;
+        self.conn.flush().map_err(|e| e.to_string())?;
+
+        if !self.state.changed {
+            if let Some(guard) = self.queue.prepare_read() {
+                let mut pollfd = libc::pollfd {
+                    fd: guard.connection_fd().as_raw_fd(),
+                    events: libc::POLLIN,
+                    revents: 0,
+                };
+                let timeout_ms = timeout.as_millis().min(i32::MAX as u128) as i32;
+
+                // SAFETY: `pollfd` is a valid, initialized array of length 1.
+                let ready = unsafe { libc::poll(&mut pollfd, 1, timeout_ms) };
+                if ready > 0 {
+                    guard.read().map_err(|e| e.to_string())?;
+                }
+            }
+
+            self.queue
+                .dispatch_pending(&mut self.state)
+                .map_err(|e| e.to_string())?;
+        }
+
+        Ok(std::mem::take(&mut self.state.changed))
+    }
+}
+
+impl Dispatch<WlRegistry, GlobalListContents> for State {
+    fn event(
+        _: &mut Self,
+        _: &WlRegistry,
+        _: <WlRegistry as Proxy>::Event,
+        _: &GlobalListContents,
+        _: &Connection,
+        _: &QueueHandle<Self>,
+    ) {
+    }
+}
+
+impl Dispatch<WlSeat, ()> for State {
+    fn event(
+        _: &mut Self,
+        _: &WlSeat,
+        _: <WlSeat as Proxy>::Event,
+        _: &(),
+        _: &Connection,
+        _: &QueueHandle<Self>,
+    ) {
+    }
+}
+
+impl Dispatch<ZwlrDataControlManagerV1, ()> for State {
+    fn event(
+        _: &mut Self,
+        _: &ZwlrDataControlManagerV1,
+        _: <ZwlrDataControlManagerV1 as Proxy>::Event,
+        _: &(),
+        _: &Connection,
+        _: &QueueHandle<Self>,
+    ) {
+    }
+}
+
+impl Dispatch<ZwlrDataControlDeviceV1, ()> for State {
+    fn event(
+        state: &mut Self,
+        _: &ZwlrDataControlDeviceV1,
+        event: zwlr_data_control_device_v1::Event,
+        _: &(),
+        _: &Connection,
+        _: &QueueHandle<Self>,
+    ) {
+        match event {
+            zwlr_data_control_device_v1::Event::Selection { id } => {
+                if let Some(old) = std::mem::replace(&mut state.offer, id) {
+                    old.destroy();
+                }
+                state.changed = true;
+            }
+            zwlr_data_control_device_v1::Event::PrimarySelection { id: Some(offer) } => {
+                offer.destroy();
+            }
+            _ => {}
+        }
+    }
+
+    event_created_child!(State, ZwlrDataControlDeviceV1, [
+        zwlr_data_control_device_v1::EVT_DATA_OFFER_OPCODE => (ZwlrDataControlOfferV1, ()),
+    ]);
+}
+
+impl Dispatch<ZwlrDataControlOfferV1, ()> for State {
+    fn event(
+        _: &mut Self,
+        _: &ZwlrDataControlOfferV1,
+        _: <ZwlrDataControlOfferV1 as Proxy>::Event,
+        _: &(),
+        _: &Connection,
+        _: &QueueHandle<Self>,
+    ) {
+    }
+}
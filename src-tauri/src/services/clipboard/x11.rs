@@ -0,0 +1,108 @@
+/*
+ * @license
+ * Copyright 2025 a7mddra
+ * SPDX-License-Identifier: Apache-2.0
+ */
+
+use std::os::fd::AsRawFd;
+use std::time::Duration;
+use x11rb::connection::Connection;
+use x11rb::protocol::xfixes::{ConnectionExt as _, SelectionEventMask};
+use x11rb::protocol::xproto::{ConnectionExt as _, CreateWindowAux, WindowClass};
+use x11rb::protocol::Event;
+use x11rb::rust_connection::RustConnection;
+
+use super::source::ChangeSource;
+
+/// Listens for `XFixesSelectionNotify` on the `CLIPBOARD` selection.
+pub struct XFixesSource {
+    conn: RustConnection,
+}
+
+impl XFixesSource {
+    pub fn connect() -> Result<Self, String> {
+        let (conn, screen_num) = x11rb::connect(None).map_err(|e| e.to_string())?;
+
+        conn.xfixes_query_version(5, 0)
+            .map_err(|e| e.to_string())?
+            .reply()
+            .map_err(|e| format!("XFixes not available: {}", e))?;
+
+        let root = conn.setup().roots[screen_num].root;
+        let window = conn.generate_id().map_err(|e| e.to_string())?;
+        conn.create_window(
+            x11rb::COPY_DEPTH_FROM_PARENT,
+            window,
+            root,
+            0,
+            0,
+            1,
+            1,
+            0,
+            WindowClass::INPUT_ONLY,
+            x11rb::COPY_FROM_PARENT,
+            &CreateWindowAux::new(),
+        )
+        .map_err(|e| e.to_string())?;
+
+        let clipboard = conn
+            .intern_atom(false, b"CLIPBOARD")
+            .map_err(|e| e.to_string())?
+            .reply()
+            .map_err(|e| e.to_string())?
+            .atom;
+
+        conn.xfixes_select_selection_input(
+            window,
+            clipboard,
+            SelectionEventMask::SET_SELECTION_OWNER
+                | SelectionEventMask::SELECTION_WINDOW_DESTROY
+                | SelectionEventMask::SELECTION_CLIENT_CLOSE,
+        )
+        .map_err(|e| e.to_string())?;
+        conn.flush().map_err(|e| e.to_string())?;
+
+        Ok(Self { conn })
+    }
+
+    fn drain(&self) -> Result<bool, String> {
+        let mut changed = false;
+        while let Some(event) = self.conn.poll_for_event().map_err(|e| e.to_string())? {
+            if let Event::XfixesSelectionNotify(_) = event {
+                changed = true;
+            }
+        }
+        Ok(changed)
+    }
+}
+
+impl ChangeSource for XFixesSource {
+    fn name(&self) -> &'static str {
+        "x11-xfixes"
+    }
+
+    fn wait(&mut self, timeout: Duration) -> Result<bool, String> {
+        if self.drain()? {
+            return Ok(true);
+        }
+
+        let mut pollfd = libc::pollfd {
+            fd: self.conn.stream().as_raw_fd(),
+            events: libc::POLLIN,
+            revents: 0,
+        };
+        let timeout_ms = timeout.as_millis().min(i32::MAX as u128) as i32;
+
+        // SAFETY: `pollfd` is a valid, initialized array of length 1.
+        let ready = unsafe { libc::poll(&mut pollfd, 1, timeout_ms) };
+        if ready < 0 {
+            let err = std::io::Error::last_os_error();
+            if err.kind() == std::io::ErrorKind::Interrupted {
+                return Ok(false);
+            }
+            return Err(err.to_string());
+        }
+
+        self.drain()
+    }
+}
@@ -0,0 +1,895 @@
+/*
+ * @license
+ * Copyright 2025 a7mddra
+ * SPDX-License-Identifier: Apache-2.0
+ */
+
+use rusqlite::{params, Connection, OptionalExtension, Row};
+use serde::Serialize;
+use std::fs;
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+use tauri::{AppHandle, Manager};
+
+use crate::services::hosting::HostedImage;
+use crate::services::image::edit::Edit;
+use crate::services::llm::Role;
+use crate::state::AppState;
+use crate::utils::get_app_data_dir;
+
+pub const DATABASE_FILE: &str = "conversations.db";
+
+/// Schema migrations, applied in order. The database's `user_version` is the
+/// number of entries already applied, so append here and never edit a
+/// shipped entry.
+const MIGRATIONS: &[&str] = &[
+    r#"
+    CREATE TABLE conversations (
+        id TEXT PRIMARY KEY,
+        title TEXT NOT NULL,
+        provider TEXT NOT NULL,
+        model TEXT NOT NULL,
+        prompt TEXT NOT NULL,
+        system_instruction TEXT,
+        created_at INTEGER NOT NULL,
+        updated_at INTEGER NOT NULL
+    );
+
+    CREATE TABLE messages (
+        id INTEGER PRIMARY KEY AUTOINCREMENT,
+        conversation_id TEXT NOT NULL REFERENCES conversations(id) ON DELETE CASCADE,
+        role TEXT NOT NULL,
+        text TEXT NOT NULL,
+        created_at INTEGER NOT NULL
+    );
+    CREATE INDEX messages_by_conversation ON messages(conversation_id, id);
+
+    CREATE TABLE message_images (
+        message_id INTEGER NOT NULL REFERENCES messages(id) ON DELETE CASCADE,
+        position INTEGER NOT NULL,
+        image_id TEXT NOT NULL,
+        PRIMARY KEY (message_id, position)
+    );
+    CREATE INDEX message_images_by_image ON message_images(image_id);
+"#,
+    r#"
+    CREATE TABLE image_versions (
+        id TEXT PRIMARY KEY,
+        source_id TEXT NOT NULL,
+        edits TEXT NOT NULL,
+        created_at INTEGER NOT NULL
+    );
+    CREATE INDEX image_versions_by_source ON image_versions(source_id);
+"#,
+    r#"
+    CREATE TABLE uploads (
+        id INTEGER PRIMARY KEY AUTOINCREMENT,
+        image_id TEXT NOT NULL,
+        host TEXT NOT NULL,
+        url TEXT NOT NULL,
+        delete_url TEXT,
+        expires_at INTEGER,
+        created_at INTEGER NOT NULL
+    );
+    CREATE INDEX uploads_by_image ON uploads(image_id);
+"#,
+];
+
+const TITLE_MAX_CHARS: usize = 60;
+
+#[derive(Serialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct ConversationSummary {
+    pub id: String,
+    pub title: String,
+    pub provider: String,
+    pub model: String,
+    pub created_at: i64,
+    pub updated_at: i64,
+    pub message_count: i64,
+}
+
+#[derive(Serialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct StoredMessage {
+    pub role: Role,
+    pub text: String,
+    pub image_ids: Vec<String>,
+    pub created_at: i64,
+}
+
+#[derive(Serialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct Conversation {
+    pub id: String,
+    pub title: String,
+    pub provider: String,
+    pub model: String,
+    pub prompt: String,
+    pub system_instruction: Option<String>,
+    pub created_at: i64,
+    pub updated_at: i64,
+    pub messages: Vec<StoredMessage>,
+}
+
+/// How an edited image was made, so the edit can be replayed or undone.
+#[derive(Serialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct ImageVersion {
+    pub id: String,
+    pub source_id: String,
+    pub edits: Vec<Edit>,
+    pub created_at: i64,
+}
+
+/// An image uploaded to a public host, kept so it can be deleted later.
+#[derive(Serialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct Upload {
+    pub id: i64,
+    /// Hash of the uploaded bytes, as in the image store.
+    pub image_id: String,
+    pub host: String,
+    pub url: String,
+    pub delete_url: Option<String>,
+    pub expires_at: Option<i64>,
+    pub created_at: i64,
+}
+
+impl Upload {
+    pub fn hosted(&self) -> HostedImage {
+        HostedImage {
+            url: self.url.clone(),
+            delete_url: self.delete_url.clone(),
+            expires_at: self.expires_at,
+        }
+    }
+}
+
+/// Images a deleted conversation was the last to show, for the caller to
+/// remove from the image store and from their hosts.
+#[derive(Debug, Default)]
+pub struct Orphans {
+    pub image_ids: Vec<String>,
+    pub uploads: Vec<Upload>,
+}
+
+pub struct NewConversation<'a> {
+    pub id: &'a str,
+    pub provider: &'a str,
+    pub model: &'a str,
+    pub prompt: &'a str,
+    pub system_instruction: Option<&'a str>,
+}
+
+pub struct ConversationStore {
+    conn: Connection,
+}
+
+impl ConversationStore {
+    pub fn open(path: &Path) -> Result<Self, String> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+        }
+        let conn = Connection::open(path).map_err(|e| e.to_string())?;
+        Self::init(conn)
+    }
+
+    fn init(conn: Connection) -> Result<Self, String> {
+        conn.pragma_update(None, "foreign_keys", true)
+            .map_err(|e| e.to_string())?;
+        let mut store = Self { conn };
+        store.migrate()?;
+        Ok(store)
+    }
+
+    fn migrate(&mut self) -> Result<(), String> {
+        let version: usize = self
+            .conn
+            .pragma_query_value(None, "user_version", |row| row.get(0))
+            .map_err(|e| e.to_string())?;
+        if version > MIGRATIONS.len() {
+            return Err(format!(
+                "Conversation database is version {}, newer than this build supports",
+                version
+            ));
+        }
+
+        for (index, sql) in MIGRATIONS.iter().enumerate().skip(version) {
+            let tx = self.conn.transaction().map_err(|e| e.to_string())?;
+            tx.execute_batch(sql)
+                .map_err(|e| format!("Migration {} failed: {}", index + 1, e))?;
+            tx.pragma_update(None, "user_version", index + 1)
+                .map_err(|e| e.to_string())?;
+            tx.commit().map_err(|e| e.to_string())?;
+        }
+        Ok(())
+    }
+
+    pub fn create(&mut self, new: NewConversation) -> Result<(), String> {
+        let now = now_millis();
+        self.conn
+            .execute(
+                "INSERT INTO conversations
+                    (id, title, provider, model, prompt, system_instruction, created_at, updated_at)
+                 VALUES (?1, '', ?2, ?3, ?4, ?5, ?6, ?6)",
+                params![
+                    new.id,
+                    new.provider,
+                    new.model,
+                    new.prompt,
+                    new.system_instruction,
+                    now
+                ],
+            )
+            .map_err(|e| e.to_string())?;
+        Ok(())
+    }
+
+    /// Appends a message. The first model reply also titles a conversation
+    /// that has no title yet.
+    pub fn append_message(
+        &mut self,
+        conversation_id: &str,
+        role: Role,
+        text: &str,
+        image_ids: &[String],
+    ) -> Result<(), String> {
+        let now = now_millis();
+        let tx = self.conn.transaction().map_err(|e| e.to_string())?;
+
+        tx.execute(
+            "INSERT INTO messages (conversation_id, role, text, created_at)
+             VALUES (?1, ?2, ?3, ?4)",
+            params![conversation_id, role_name(role), text, now],
+        )
+        .map_err(|e| e.to_string())?;
+        let message_id = tx.last_insert_rowid();
+
+        for (position, image_id) in image_ids.iter().enumerate() {
+            tx.execute(
+                "INSERT INTO message_images (message_id, position, image_id) VALUES (?1, ?2, ?3)",
+                params![message_id, position as i64, image_id],
+            )
+            .map_err(|e| e.to_string())?;
+        }
+
+        tx.execute(
+            "UPDATE conversations SET updated_at = ?2 WHERE id = ?1",
+            params![conversation_id, now],
+        )
+        .map_err(|e| e.to_string())?;
+        if role == Role::Model {
+            tx.execute(
+                "UPDATE conversations SET title = ?2 WHERE id = ?1 AND title = ''",
+                params![conversation_id, title_from(text)],
+            )
+            .map_err(|e| e.to_string())?;
+        }
+
+        tx.commit().map_err(|e| e.to_string())
+    }
+
+    /// Most recently updated first.
+    pub fn list(&self, limit: u32, offset: u32) -> Result<Vec<ConversationSummary>, String> {
+        let mut stmt = self
+            .conn
+            .prepare(&format!(
+                "{} ORDER BY c.updated_at DESC, c.id LIMIT ?1 OFFSET ?2",
+                SUMMARY_SELECT
+            ))
+            .map_err(|e| e.to_string())?;
+        let rows = stmt
+            .query_map(params![limit, offset], summary_from_row)
+            .map_err(|e| e.to_string())?;
+        rows.collect::<Result<_, _>>().map_err(|e| e.to_string())
+    }
+
+    /// Case-insensitive substring search over titles and message text.
+    pub fn search(&self, query: &str, limit: u32) -> Result<Vec<ConversationSummary>, String> {
+        let pattern = format!("%{}%", escape_like(query.trim()));
+        let mut stmt = self
+            .conn
+            .prepare(&format!(
+                "{} WHERE c.title LIKE ?1 ESCAPE '\\'
+                    OR EXISTS (SELECT 1 FROM messages s
+                               WHERE s.conversation_id = c.id AND s.text LIKE ?1 ESCAPE '\\')
+                 ORDER BY c.updated_at DESC, c.id LIMIT ?2",
+                SUMMARY_SELECT
+            ))
+            .map_err(|e| e.to_string())?;
+        let rows = stmt
+            .query_map(params![pattern, limit], summary_from_row)
+            .map_err(|e| e.to_string())?;
+        rows.collect::<Result<_, _>>().map_err(|e| e.to_string())
+    }
+
+    pub fn load(&self, id: &str) -> Result<Conversation, String> {
+        let mut conversation = self
+            .conn
+            .query_row(
+                "SELECT id, title, provider, model, prompt, system_instruction, created_at, updated_at
+                 FROM conversations WHERE id = ?1",
+                params![id],
+                |row| {
+                    Ok(Conversation {
+                        id: row.get(0)?,
+                        title: row.get(1)?,
+                        provider: row.get(2)?,
+                        model: row.get(3)?,
+                        prompt: row.get(4)?,
+                        system_instruction: row.get(5)?,
+                        created_at: row.get(6)?,
+                        updated_at: row.get(7)?,
+                        messages: Vec::new(),
+                    })
+                },
+            )
+            .optional()
+            .map_err(|e| e.to_string())?
+            .ok_or("Conversation not found")?;
+
+        let mut stmt = self
+            .conn
+            .prepare(
+                "SELECT id, role, text, created_at FROM messages
+                 WHERE conversation_id = ?1 ORDER BY id",
+            )
+            .map_err(|e| e.to_string())?;
+        let rows = stmt
+            .query_map(params![id], |row| {
+                let role: String = row.get(1)?;
+                Ok((
+                    row.get::<_, i64>(0)?,
+                    StoredMessage {
+                        role: if role == "model" {
+                            Role::Model
+                        } else {
+                            Role::User
+                        },
+                        text: row.get(2)?,
+                        image_ids: Vec::new(),
+                        created_at: row.get(3)?,
+                    },
+                ))
+            })
+            .map_err(|e| e.to_string())?;
+        let mut messages: Vec<(i64, StoredMessage)> =
+            rows.collect::<Result<_, _>>().map_err(|e| e.to_string())?;
+
+        let mut stmt = self
+            .conn
+            .prepare(
+                "SELECT i.message_id, i.image_id FROM message_images i
+                 JOIN messages m ON m.id = i.message_id
+                 WHERE m.conversation_id = ?1 ORDER BY i.message_id, i.position",
+            )
+            .map_err(|e| e.to_string())?;
+        let images = stmt
+            .query_map(params![id], |row| {
+                Ok((row.get::<_, i64>(0)?, row.get::<_, String>(1)?))
+            })
+            .map_err(|e| e.to_string())?;
+        for image in images {
+            let (message_id, image_id) = image.map_err(|e| e.to_string())?;
+            if let Some((_, message)) = messages.iter_mut().find(|(id, _)| *id == message_id) {
+                message.image_ids.push(image_id);
+            }
+        }
+
+        conversation.messages = messages.into_iter().map(|(_, message)| message).collect();
+        Ok(conversation)
+    }
+
+    pub fn rename(&mut self, id: &str, title: &str) -> Result<(), String> {
+        let title = title.trim();
+        if title.is_empty() {
+            return Err("Title must not be empty".into());
+        }
+        let changed = self
+            .conn
+            .execute(
+                "UPDATE conversations SET title = ?2 WHERE id = ?1",
+                params![id, title],
+            )
+            .map_err(|e| e.to_string())?;
+        if changed == 0 {
+            return Err("Conversation not found".into());
+        }
+        Ok(())
+    }
+
+    /// Deletes a conversation and its messages, along with the edit records
+    /// of images no other conversation shows and of unshown versions made
+    /// from them. Their files and uploads are returned rather than removed,
+    /// as they live outside the database.
+    pub fn delete(&mut self, id: &str) -> Result<Orphans, String> {
+        let tx = self.conn.transaction().map_err(|e| e.to_string())?;
+        let mut pending: Vec<String> = {
+            let mut stmt = tx
+                .prepare(
+                    "SELECT DISTINCT i.image_id FROM message_images i
+                     JOIN messages m ON m.id = i.message_id
+                     WHERE m.conversation_id = ?1",
+                )
+                .map_err(|e| e.to_string())?;
+            let rows = stmt
+                .query_map(params![id], |row| row.get(0))
+                .map_err(|e| e.to_string())?;
+            rows.collect::<Result<_, _>>().map_err(|e| e.to_string())?
+        };
+
+        let changed = tx
+            .execute("DELETE FROM conversations WHERE id = ?1", params![id])
+            .map_err(|e| e.to_string())?;
+        if changed == 0 {
+            return Err("Conversation not found".into());
+        }
+
+        let mut orphaned: Vec<String> = Vec::new();
+        while let Some(image_id) = pending.pop() {
+            let shown: bool = tx
+                .query_row(
+                    "SELECT EXISTS (SELECT 1 FROM message_images WHERE image_id = ?1)",
+                    params![image_id],
+                    |row| row.get(0),
+                )
+                .map_err(|e| e.to_string())?;
+            if shown || orphaned.contains(&image_id) {
+                continue;
+            }
+
+            let derived: Vec<String> = {
+                let mut stmt = tx
+                    .prepare("SELECT id FROM image_versions WHERE source_id = ?1")
+                    .map_err(|e| e.to_string())?;
+                let rows = stmt
+                    .query_map(params![image_id], |row| row.get(0))
+                    .map_err(|e| e.to_string())?;
+                rows.collect::<Result<_, _>>().map_err(|e| e.to_string())?
+            };
+            tx.execute(
+                "DELETE FROM image_versions WHERE id = ?1",
+                params![image_id],
+            )
+            .map_err(|e| e.to_string())?;
+            pending.extend(derived);
+            orphaned.push(image_id);
+        }
+        tx.commit().map_err(|e| e.to_string())?;
+
+        let mut uploads = Vec::new();
+        for image_id in &orphaned {
+            uploads.extend(self.query_uploads(
+                &format!("{} WHERE image_id = ?1 ORDER BY id", UPLOAD_SELECT),
+                params![image_id],
+            )?);
+        }
+        Ok(Orphans {
+            image_ids: orphaned,
+            uploads,
+        })
+    }
+
+    /// Records that image `id` was produced by applying `edits` to
+    /// `source_id`. The first recorded recipe for an image is kept.
+    pub fn record_image_version(
+        &mut self,
+        id: &str,
+        source_id: &str,
+        edits: &[Edit],
+    ) -> Result<(), String> {
+        let edits = serde_json::to_string(edits).map_err(|e| e.to_string())?;
+        self.conn
+            .execute(
+                "INSERT OR IGNORE INTO image_versions (id, source_id, edits, created_at)
+                 VALUES (?1, ?2, ?3, ?4)",
+                params![id, source_id, edits, now_millis()],
+            )
+            .map_err(|e| e.to_string())?;
+        Ok(())
+    }
+
+    pub fn image_version(&self, id: &str) -> Result<Option<ImageVersion>, String> {
+        let row = self
+            .conn
+            .query_row(
+                "SELECT id, source_id, edits, created_at FROM image_versions WHERE id = ?1",
+                params![id],
+                |row| {
+                    Ok((
+                        row.get::<_, String>(0)?,
+                        row.get::<_, String>(1)?,
+                        row.get::<_, String>(2)?,
+                        row.get::<_, i64>(3)?,
+                    ))
+                },
+            )
+            .optional()
+            .map_err(|e| e.to_string())?;
+
+        row.map(|(id, source_id, edits, created_at)| {
+            Ok(ImageVersion {
+                id,
+                source_id,
+                edits: serde_json::from_str(&edits).map_err(|e| e.to_string())?,
+                created_at,
+            })
+        })
+        .transpose()
+    }
+
+    /// Remembers where an image was uploaded and how to delete it.
+    pub fn record_upload(
+        &mut self,
+        image_id: &str,
+        host: &str,
+        hosted: &HostedImage,
+    ) -> Result<Upload, String> {
+        let created_at = now_millis();
+        self.conn
+            .execute(
+                "INSERT INTO uploads (image_id, host, url, delete_url, expires_at, created_at)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+                params![
+                    image_id,
+                    host,
+                    hosted.url,
+                    hosted.delete_url,
+                    hosted.expires_at,
+                    created_at
+                ],
+            )
+            .map_err(|e| e.to_string())?;
+        Ok(Upload {
+            id: self.conn.last_insert_rowid(),
+            image_id: image_id.to_string(),
+            host: host.to_string(),
+            url: hosted.url.clone(),
+            delete_url: hosted.delete_url.clone(),
+            expires_at: hosted.expires_at,
+            created_at,
+        })
+    }
+
+    /// Every recorded upload, newest first.
+    pub fn list_uploads(&self) -> Result<Vec<Upload>, String> {
+        self.query_uploads(
+            &format!("{} ORDER BY created_at DESC, id DESC", UPLOAD_SELECT),
+            params![],
+        )
+    }
+
+    pub fn upload(&self, id: i64) -> Result<Option<Upload>, String> {
+        self.conn
+            .query_row(
+                &format!("{} WHERE id = ?1", UPLOAD_SELECT),
+                params![id],
+                upload_from_row,
+            )
+            .optional()
+            .map_err(|e| e.to_string())
+    }
+
+    /// The newest upload of `image_id` to `host` whose URL still works at
+    /// `valid_at`.
+    pub fn reusable_upload(
+        &self,
+        image_id: &str,
+        host: &str,
+        valid_at: i64,
+    ) -> Result<Option<Upload>, String> {
+        self.conn
+            .query_row(
+                &format!(
+                    "{} WHERE image_id = ?1 AND host = ?2
+                       AND (expires_at IS NULL OR expires_at > ?3)
+                     ORDER BY id DESC LIMIT 1",
+                    UPLOAD_SELECT
+                ),
+                params![image_id, host, valid_at],
+                upload_from_row,
+            )
+            .optional()
+            .map_err(|e| e.to_string())
+    }
+
+    /// Uploads made before `cutoff`, oldest first.
+    pub fn uploads_before(&self, cutoff: i64) -> Result<Vec<Upload>, String> {
+        self.query_uploads(
+            &format!(
+                "{} WHERE created_at < ?1 ORDER BY created_at, id",
+                UPLOAD_SELECT
+            ),
+            params![cutoff],
+        )
+    }
+
+    pub fn remove_upload(&mut self, id: i64) -> Result<(), String> {
+        self.conn
+            .execute("DELETE FROM uploads WHERE id = ?1", params![id])
+            .map_err(|e| e.to_string())?;
+        Ok(())
+    }
+
+    fn query_uploads(
+        &self,
+        sql: &str,
+        params: impl rusqlite::Params,
+    ) -> Result<Vec<Upload>, String> {
+        let mut stmt = self.conn.prepare(sql).map_err(|e| e.to_string())?;
+        let rows = stmt
+            .query_map(params, upload_from_row)
+            .map_err(|e| e.to_string())?;
+        rows.collect::<Result<_, _>>().map_err(|e| e.to_string())
+    }
+}
+
+/// Runs `f` against the app's conversation store, opening the database on
+/// first use.
+pub fn with_store<T>(
+    app: &AppHandle,
+    f: impl FnOnce(&mut ConversationStore) -> Result<T, String>,
+) -> Result<T, String> {
+    let state = app.state::<AppState>();
+    let mut slot = state.conversations.lock();
+    if slot.is_none() {
+        let path = get_app_data_dir(app).join(DATABASE_FILE);
+        *slot = Some(ConversationStore::open(&path)?);
+    }
+    f(slot.as_mut().unwrap())
+}
+
+const SUMMARY_SELECT: &str =
+    "SELECT c.id, c.title, c.provider, c.model, c.created_at, c.updated_at,
+        (SELECT COUNT(*) FROM messages m WHERE m.conversation_id = c.id)
+     FROM conversations c";
+
+fn summary_from_row(row: &Row) -> rusqlite::Result<ConversationSummary> {
+    Ok(ConversationSummary {
+        id: row.get(0)?,
+        title: row.get(1)?,
+        provider: row.get(2)?,
+        model: row.get(3)?,
+        created_at: row.get(4)?,
+        updated_at: row.get(5)?,
+        message_count: row.get(6)?,
+    })
+}
+
+const UPLOAD_SELECT: &str =
+    "SELECT id, image_id, host, url, delete_url, expires_at, created_at FROM uploads";
+
+fn upload_from_row(row: &Row) -> rusqlite::Result<Upload> {
+    Ok(Upload {
+        id: row.get(0)?,
+        image_id: row.get(1)?,
+        host: row.get(2)?,
+        url: row.get(3)?,
+        delete_url: row.get(4)?,
+        expires_at: row.get(5)?,
+        created_at: row.get(6)?,
+    })
+}
+
+fn role_name(role: Role) -> &'static str {
+    match role {
+        Role::User => "user",
+        Role::Model => "model",
+    }
+}
+
+/// First non-empty line of a reply, without Markdown heading, bold and code
+/// markers, shortened to fit a list row.
+fn title_from(text: &str) -> String {
+    let line = text
+        .lines()
+        .map(|line| line.trim_start_matches('#').replace(['*', '`'], ""))
+        .map(|line| line.trim().to_string())
+        .find(|line| !line.is_empty())
+        .unwrap_or_else(|| "Untitled".to_string());
+
+    let mut title: String = line.chars().take(TITLE_MAX_CHARS).collect();
+    if line.chars().count() > TITLE_MAX_CHARS {
+        title.push('…');
+    }
+    title
+}
+
+fn escape_like(query: &str) -> String {
+    query
+        .replace('\\', "\\\\")
+        .replace('%', "\\%")
+        .replace('_', "\\_")
+}
+
+fn now_millis() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as i64)
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn store() -> ConversationStore {
+        ConversationStore::init(Connection::open_in_memory().unwrap()).unwrap()
+    }
+
+    fn create(store: &mut ConversationStore, id: &str) {
+        store
+            .create(NewConversation {
+                id,
+                provider: "gemini",
+                model: "gemini-2.5-flash",
+                prompt: "Describe",
+                system_instruction: None,
+            })
+            .unwrap();
+    }
+
+    fn count(store: &ConversationStore, table: &str) -> i64 {
+        store
+            .conn
+            .query_row(&format!("SELECT COUNT(*) FROM {}", table), [], |row| {
+                row.get(0)
+            })
+            .unwrap()
+    }
+
+    fn user_version(conn: &Connection) -> usize {
+        conn.pragma_query_value(None, "user_version", |row| row.get(0))
+            .unwrap()
+    }
+
+    #[test]
+    fn migrates_fresh_and_older_databases() {
+        let fresh = store();
+        assert_eq!(user_version(&fresh.conn), MIGRATIONS.len());
+
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(MIGRATIONS[0]).unwrap();
+        conn.pragma_update(None, "user_version", 1).unwrap();
+        conn.execute(
+            "INSERT INTO conversations VALUES ('old', 'Kept', 'gemini', 'm', 'p', NULL, 1, 1)",
+            [],
+        )
+        .unwrap();
+        let mut upgraded = ConversationStore::init(conn).unwrap();
+        assert_eq!(user_version(&upgraded.conn), MIGRATIONS.len());
+        assert_eq!(upgraded.load("old").unwrap().title, "Kept");
+        upgraded.record_image_version("b", "a", &[]).unwrap();
+        let hosted = HostedImage {
+            url: "https://i.example/a".into(),
+            delete_url: None,
+            expires_at: None,
+        };
+        upgraded.record_upload("a", "imgbb", &hosted).unwrap();
+
+        let conn = Connection::open_in_memory().unwrap();
+        conn.pragma_update(None, "user_version", MIGRATIONS.len() + 1)
+            .unwrap();
+        assert!(ConversationStore::init(conn).is_err());
+    }
+
+    #[test]
+    fn titles_from_the_first_model_reply() {
+        let mut store = store();
+        create(&mut store, "c1");
+
+        store
+            .append_message("c1", Role::User, "What is this?", &["img1".into()])
+            .unwrap();
+        assert_eq!(store.load("c1").unwrap().title, "");
+        store
+            .append_message("c1", Role::Model, "\n## **A `Cargo.toml`** file\nMore", &[])
+            .unwrap();
+        store
+            .append_message("c1", Role::Model, "Something else", &[])
+            .unwrap();
+
+        let conversation = store.load("c1").unwrap();
+        assert_eq!(conversation.title, "A Cargo.toml file");
+        assert_eq!(conversation.messages.len(), 3);
+        assert_eq!(conversation.messages[0].image_ids, ["img1"]);
+        assert_eq!(store.list(10, 0).unwrap()[0].message_count, 3);
+
+        let long = "x".repeat(TITLE_MAX_CHARS + 5);
+        assert_eq!(title_from(&long).chars().count(), TITLE_MAX_CHARS + 1);
+        assert!(title_from(&long).ends_with('…'));
+        assert_eq!(title_from("  \n"), "Untitled");
+    }
+
+    #[test]
+    fn search_treats_wildcards_literally() {
+        let mut store = store();
+        for (id, reply) in [
+            ("percent", "100% sure"),
+            ("underscore", "snake_case"),
+            ("plain", "Snake case, 100 percent"),
+        ] {
+            create(&mut store, id);
+            store.append_message(id, Role::Model, reply, &[]).unwrap();
+        }
+        let ids = |query: &str| -> Vec<String> {
+            let mut ids: Vec<String> = store
+                .search(query, 10)
+                .unwrap()
+                .into_iter()
+                .map(|c| c.id)
+                .collect();
+            ids.sort();
+            ids
+        };
+
+        assert_eq!(ids("%"), ["percent"]);
+        assert_eq!(ids("e_c"), ["underscore"]);
+        assert_eq!(ids("SNAKE"), ["plain", "underscore"]);
+        assert!(ids("\\").is_empty());
+    }
+
+    #[test]
+    fn renames() {
+        let mut store = store();
+        create(&mut store, "c1");
+
+        store.rename("c1", "  Receipts  ").unwrap();
+        assert_eq!(store.load("c1").unwrap().title, "Receipts");
+        assert!(store.rename("c1", " ").is_err());
+        assert!(store.rename("missing", "Title").is_err());
+    }
+
+    #[test]
+    fn delete_cascades_to_unshared_images() {
+        let mut store = store();
+        create(&mut store, "c1");
+        create(&mut store, "c2");
+        store
+            .append_message("c1", Role::User, "Two", &["own".into(), "shared".into()])
+            .unwrap();
+        store
+            .append_message("c2", Role::User, "One", &["shared".into()])
+            .unwrap();
+        store.record_image_version("own", "raw", &[]).unwrap();
+        store.record_image_version("shared", "raw", &[]).unwrap();
+        let hosted = |url: &str| HostedImage {
+            url: url.into(),
+            delete_url: Some(format!("{}/delete", url)),
+            expires_at: None,
+        };
+        let own = store.record_upload("own", "imgbb", &hosted("u1")).unwrap();
+        store
+            .record_upload("shared", "imgbb", &hosted("u2"))
+            .unwrap();
+
+        store.record_image_version("own-crop", "own", &[]).unwrap();
+        store.record_image_version("own-shown", "own", &[]).unwrap();
+        store
+            .append_message("c2", Role::User, "Cropped", &["own-shown".into()])
+            .unwrap();
+
+        let orphans = store.delete("c1").unwrap();
+
+        let mut image_ids = orphans.image_ids.clone();
+        image_ids.sort();
+        assert_eq!(image_ids, ["own", "own-crop"]);
+        assert_eq!(
+            orphans.uploads.iter().map(|u| u.id).collect::<Vec<_>>(),
+            [own.id]
+        );
+        assert!(store.load("c1").is_err());
+        assert_eq!(count(&store, "messages"), 2);
+        assert_eq!(count(&store, "message_images"), 2);
+        assert!(store.image_version("own").unwrap().is_none());
+        assert!(store.image_version("own-crop").unwrap().is_none());
+        assert!(store.image_version("own-shown").unwrap().is_some());
+        assert!(store.image_version("shared").unwrap().is_some());
+        assert!(store.delete("c1").is_err());
+
+        assert_eq!(store.delete("c2").unwrap().uploads.len(), 1);
+        assert_eq!(count(&store, "messages"), 0);
+        assert_eq!(count(&store, "image_versions"), 0);
+    }
+}
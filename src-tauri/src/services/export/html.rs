@@ -0,0 +1,103 @@
+/*
+ * @license
+ * Copyright 2025 a7mddra
+ * SPDX-License-Identifier: Apache-2.0
+ */
+
+use base64::{engine::general_purpose, Engine as _};
+use pulldown_cmark::{html, Event, Options, Parser};
+use std::fmt::Write;
+
+use super::{format_timestamp, role_label, visible_text, ImageSet};
+use crate::services::conversations::Conversation;
+use crate::services::llm::Role;
+
+const STYLE: &str = "body{font-family:system-ui,sans-serif;max-width:48rem;margin:2rem auto;\
+padding:0 1rem;line-height:1.5;color:#1f2328}\
+header p{color:#59636e;margin:0}\
+section{border-top:1px solid #d1d9e0;padding:1rem 0}\
+h2.role{font-size:.85rem;text-transform:uppercase;letter-spacing:.05em;color:#59636e}\
+section.user{background:#f6f8fa;padding:1rem}\
+img{max-width:100%;border:1px solid #d1d9e0;border-radius:4px}\
+pre{background:#f6f8fa;padding:.75rem;overflow-x:auto}\
+code{font-family:ui-monospace,monospace}";
+
+/// Renders a conversation as a single HTML file with images inlined as data
+/// URLs. Markdown in messages is rendered; raw HTML in them is escaped.
+pub fn render(conversation: &Conversation, images: &ImageSet) -> String {
+    let mut out = String::new();
+
+    let _ = write!(
+        out,
+        "<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">\n\
+         <meta name=\"generator\" content=\"Spatialshot\">\n<title>{}</title>\n\
+         <style>{}</style>\n</head>\n<body>\n",
+        escape(&conversation.title),
+        STYLE
+    );
+    let _ = write!(
+        out,
+        "<header>\n<h1>{}</h1>\n<p>{} ({}) &middot; {}</p>\n</header>\n",
+        escape(&conversation.title),
+        escape(&conversation.model),
+        escape(&conversation.provider),
+        format_timestamp(conversation.created_at)
+    );
+
+    for message in &conversation.messages {
+        let text = visible_text(conversation, message);
+        if text.is_none() && message.image_ids.is_empty() {
+            continue;
+        }
+
+        let class = match message.role {
+            Role::User => "user",
+            Role::Model => "model",
+        };
+        let _ = write!(
+            out,
+            "<section class=\"{}\">\n<h2 class=\"role\">{}</h2>\n",
+            class,
+            role_label(message.role)
+        );
+        for id in &message.image_ids {
+            if let Some(image) = images.get(id) {
+                let _ = writeln!(
+                    out,
+                    "<p><img alt=\"Screenshot\" src=\"data:{};base64,{}\"></p>",
+                    image.mime_type,
+                    general_purpose::STANDARD.encode(&image.bytes)
+                );
+            }
+        }
+        if let Some(text) = text {
+            out.push_str(&render_markdown(text));
+            if !out.ends_with('\n') {
+                out.push('\n');
+            }
+        }
+        out.push_str("</section>\n");
+    }
+
+    out.push_str("</body>\n</html>\n");
+    out
+}
+
+fn render_markdown(text: &str) -> String {
+    let options = Options::ENABLE_TABLES | Options::ENABLE_STRIKETHROUGH;
+    let events = Parser::new_ext(text, options).map(|event| match event {
+        Event::Html(raw) | Event::InlineHtml(raw) => Event::Text(raw),
+        other => other,
+    });
+
+    let mut rendered = String::new();
+    html::push_html(&mut rendered, events);
+    rendered
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
@@ -0,0 +1,53 @@
+/*
+ * @license
+ * Copyright 2025 a7mddra
+ * SPDX-License-Identifier: Apache-2.0
+ */
+
+use std::fmt::Write;
+
+use super::{format_timestamp, role_label, visible_text, ImageSet};
+use crate::services::conversations::Conversation;
+
+/// Renders a conversation as Markdown. Images are linked relative to the
+/// document, inside `image_folder`.
+pub fn render(conversation: &Conversation, images: &ImageSet, image_folder: &str) -> String {
+    let mut out = String::new();
+
+    let _ = writeln!(out, "# {}\n", conversation.title);
+    let _ = writeln!(
+        out,
+        "- Model: {} ({})",
+        conversation.model, conversation.provider
+    );
+    let _ = writeln!(
+        out,
+        "- Created: {}",
+        format_timestamp(conversation.created_at)
+    );
+
+    for message in &conversation.messages {
+        let text = visible_text(conversation, message);
+        if text.is_none() && message.image_ids.is_empty() {
+            continue;
+        }
+
+        let _ = writeln!(out, "\n## {}", role_label(message.role));
+        for id in &message.image_ids {
+            if let Some(image) = images.get(id) {
+                let _ = writeln!(
+                    out,
+                    "\n![Screenshot]({}/{}.{})",
+                    image_folder.replace(' ', "%20"),
+                    id,
+                    image.extension
+                );
+            }
+        }
+        if let Some(text) = text {
+            let _ = writeln!(out, "\n{}", text);
+        }
+    }
+
+    out
+}
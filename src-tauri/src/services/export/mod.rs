@@ -0,0 +1,274 @@
+/*
+ * @license
+ * Copyright 2025 a7mddra
+ * SPDX-License-Identifier: Apache-2.0
+ */
+
+mod html;
+mod markdown;
+mod pdf;
+
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+use tauri::AppHandle;
+
+use crate::services::conversations::{Conversation, StoredMessage};
+use crate::services::image_store;
+use crate::services::llm::Role;
+
+/// Output formats. Every renderer is a pure function of the conversation and
+/// its images, so the same input always produces the same bytes. The one
+/// exception is a PDF with text outside Windows-1252, which embeds whichever
+/// system font is found.
+#[derive(Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum ExportFormat {
+    Json,
+    Markdown,
+    Html,
+    Pdf,
+}
+
+impl ExportFormat {
+    pub fn extension(self) -> &'static str {
+        match self {
+            ExportFormat::Json => "json",
+            ExportFormat::Markdown => "md",
+            ExportFormat::Html => "html",
+            ExportFormat::Pdf => "pdf",
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            ExportFormat::Json => "JSON",
+            ExportFormat::Markdown => "Markdown",
+            ExportFormat::Html => "HTML",
+            ExportFormat::Pdf => "PDF",
+        }
+    }
+}
+
+pub struct ExportImage {
+    pub mime_type: &'static str,
+    pub extension: &'static str,
+    pub bytes: Vec<u8>,
+}
+
+/// Images referenced by a conversation, keyed by image id.
+pub type ImageSet = BTreeMap<String, ExportImage>;
+
+pub fn load_images(app: &AppHandle, conversation: &Conversation) -> Result<ImageSet, String> {
+    let mut images = ImageSet::new();
+    for message in &conversation.messages {
+        for id in &message.image_ids {
+            if images.contains_key(id) {
+                continue;
+            }
+            let bytes = image_store::load(app, id)?;
+            let format = image::guess_format(&bytes)
+                .map_err(|e| format!("Unsupported image {}: {}", id, e))?;
+            images.insert(
+                id.clone(),
+                ExportImage {
+                    mime_type: format.to_mime_type(),
+                    extension: format.extensions_str().first().copied().unwrap_or("bin"),
+                    bytes,
+                },
+            );
+        }
+    }
+    Ok(images)
+}
+
+/// Writes the export to `path`. Markdown also writes its images to a
+/// `<name>_images` folder next to the file.
+pub fn write(
+    conversation: &Conversation,
+    images: &ImageSet,
+    format: ExportFormat,
+    path: &Path,
+) -> Result<(), String> {
+    let bytes = match format {
+        ExportFormat::Json => serde_json::to_vec_pretty(conversation).map_err(|e| e.to_string())?,
+        ExportFormat::Markdown => {
+            let stem = path
+                .file_stem()
+                .map(|s| s.to_string_lossy().into_owned())
+                .ok_or("Export path has no file name")?;
+            let folder = format!("{}_images", stem);
+
+            if !images.is_empty() {
+                let dir = path.with_file_name(&folder);
+                fs::create_dir_all(&dir).map_err(|e| e.to_string())?;
+                for (id, image) in images {
+                    fs::write(
+                        dir.join(format!("{}.{}", id, image.extension)),
+                        &image.bytes,
+                    )
+                    .map_err(|e| e.to_string())?;
+                }
+            }
+
+            markdown::render(conversation, images, &folder).into_bytes()
+        }
+        ExportFormat::Html => html::render(conversation, images).into_bytes(),
+        ExportFormat::Pdf => pdf::render(conversation, images)?,
+    };
+
+    fs::write(path, bytes).map_err(|e| format!("Failed to write {}: {}", path.display(), e))
+}
+
+/// The text of a message as it should appear in an export. The first turn
+/// carries the system instruction as its prompt; that is an implementation
+/// detail, not something the user wrote, so it is left out.
+fn visible_text<'a>(conversation: &Conversation, message: &'a StoredMessage) -> Option<&'a str> {
+    let text = message.text.trim();
+    let hidden = message.role == Role::User
+        && conversation.system_instruction.as_deref().map(str::trim) == Some(text);
+    (!text.is_empty() && !hidden).then_some(text)
+}
+
+fn role_label(role: Role) -> &'static str {
+    match role {
+        Role::User => "User",
+        Role::Model => "Assistant",
+    }
+}
+
+/// Formats a Unix timestamp in milliseconds as `YYYY-MM-DD HH:MM UTC`.
+fn format_timestamp(millis: i64) -> String {
+    let secs = millis.div_euclid(1000);
+    let days = secs.div_euclid(86_400);
+    let rem = secs.rem_euclid(86_400);
+
+    // Civil-from-days, after Howard Hinnant's date algorithms.
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+
+    format!(
+        "{:04}-{:02}-{:02} {:02}:{:02} UTC",
+        year,
+        month,
+        day,
+        rem / 3600,
+        rem % 3600 / 60
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample_conversation() -> Conversation {
+        let image_id = "ab".repeat(32);
+        Conversation {
+            id: "0123456789abcdef".into(),
+            title: "A <cat> & a mat".into(),
+            provider: "gemini".into(),
+            model: "gemini-2.5-flash".into(),
+            prompt: "SYSTEM".into(),
+            system_instruction: Some("SYSTEM".into()),
+            created_at: 1_735_787_045_000,
+            updated_at: 1_735_787_105_000,
+            messages: vec![
+                StoredMessage {
+                    role: Role::User,
+                    text: "SYSTEM".into(),
+                    image_ids: vec![image_id],
+                    created_at: 1_735_787_045_000,
+                },
+                StoredMessage {
+                    role: Role::Model,
+                    text: "## Summary\n\nA **cat** sits on a mat.\n\n- fur: grey\n- mood: calm\n\n```\nlet cat = 1;\n```\n<script>alert(1)</script>".into(),
+                    image_ids: Vec::new(),
+                    created_at: 1_735_787_050_000,
+                },
+                StoredMessage {
+                    role: Role::User,
+                    text: "Is it café-coloured?".into(),
+                    image_ids: Vec::new(),
+                    created_at: 1_735_787_100_000,
+                },
+                StoredMessage {
+                    role: Role::Model,
+                    text: "No, grey.".into(),
+                    image_ids: Vec::new(),
+                    created_at: 1_735_787_105_000,
+                },
+            ],
+        }
+    }
+
+    fn sample_images() -> ImageSet {
+        let mut png = Vec::new();
+        image::RgbImage::from_pixel(4, 3, image::Rgb([200, 40, 40]))
+            .write_to(&mut std::io::Cursor::new(&mut png), image::ImageFormat::Png)
+            .unwrap();
+
+        let mut images = ImageSet::new();
+        images.insert(
+            "ab".repeat(32),
+            ExportImage {
+                mime_type: "image/png",
+                extension: "png",
+                bytes: png,
+            },
+        );
+        images
+    }
+
+    #[test]
+    fn markdown_matches_golden_file() {
+        let rendered = markdown::render(&sample_conversation(), &sample_images(), "chat_images");
+        assert_eq!(rendered, include_str!("golden/conversation.md"));
+    }
+
+    #[test]
+    fn html_matches_golden_file() {
+        let rendered = html::render(&sample_conversation(), &sample_images());
+        assert_eq!(rendered, include_str!("golden/conversation.html"));
+    }
+
+    #[test]
+    fn pdf_is_deterministic() {
+        let first = pdf::render(&sample_conversation(), &sample_images()).unwrap();
+        let second = pdf::render(&sample_conversation(), &sample_images()).unwrap();
+        assert!(first.starts_with(b"%PDF-1.7"));
+        assert_eq!(first, second);
+    }
+
+    #[test]
+    fn pdf_embeds_a_font_for_other_scripts() {
+        let contains = |pdf: &[u8], needle: &[u8]| pdf.windows(needle.len()).any(|w| w == needle);
+        let latin = pdf::render(&sample_conversation(), &sample_images()).unwrap();
+        assert!(!contains(&latin, b"Type0"));
+
+        let mut conversation = sample_conversation();
+        conversation.messages[3].text = "Нет, серый 灰色.".into();
+        match crate::services::fonts::unicode_font() {
+            Ok(_) => {
+                let pdf = pdf::render(&conversation, &sample_images()).unwrap();
+                assert!(contains(&pdf, b"/Type0"));
+                assert!(contains(&pdf, b"/ToUnicode"));
+            }
+            Err(_) => assert!(pdf::render(&conversation, &sample_images()).is_err()),
+        }
+    }
+
+    #[test]
+    fn formats_timestamps_in_utc() {
+        assert_eq!(format_timestamp(0), "1970-01-01 00:00 UTC");
+        assert_eq!(format_timestamp(1_735_787_045_000), "2025-01-02 03:04 UTC");
+        assert_eq!(format_timestamp(951_782_400_000), "2000-02-29 00:00 UTC");
+    }
+}
@@ -0,0 +1,596 @@
+/*
+ * @license
+ * Copyright 2025 a7mddra
+ * SPDX-License-Identifier: Apache-2.0
+ */
+
+use ab_glyph::{Font as _, FontVec};
+use lopdf::content::{Content, Operation};
+use lopdf::{dictionary, Dictionary, Document, Object, Stream, StringFormat};
+use pulldown_cmark::{Event, Parser, Tag, TagEnd};
+use std::collections::BTreeMap;
+
+use super::{format_timestamp, role_label, visible_text, ExportImage, ImageSet};
+use crate::services::conversations::Conversation;
+use crate::services::fonts;
+
+const PAGE_WIDTH: f32 = 595.0;
+const PAGE_HEIGHT: f32 = 842.0;
+const MARGIN: f32 = 56.0;
+const CONTENT_WIDTH: f32 = PAGE_WIDTH - 2.0 * MARGIN;
+const MAX_IMAGE_HEIGHT: f32 = 360.0;
+
+const TITLE_SIZE: f32 = 18.0;
+const BODY_SIZE: f32 = 10.5;
+const SMALL_SIZE: f32 = 9.0;
+const LEADING: f32 = 1.4;
+const LIST_INDENT: f32 = 14.0;
+const UNICODE_FONT_NAME: &str = "SpatialshotUnicode";
+
+/// Advance widths of Helvetica for ASCII 32..=126, in 1/1000 em.
+const HELVETICA_WIDTHS: [u16; 95] = [
+    278, 278, 355, 556, 556, 889, 667, 191, 333, 333, 389, 584, 278, 333, 278, 278, 556, 556, 556,
+    556, 556, 556, 556, 556, 556, 556, 278, 278, 584, 584, 584, 556, 1015, 667, 667, 722, 722, 667,
+    611, 778, 722, 278, 500, 667, 556, 833, 722, 778, 667, 778, 722, 667, 611, 722, 667, 944, 667,
+    667, 611, 278, 278, 278, 469, 556, 333, 556, 556, 500, 556, 556, 278, 556, 556, 222, 222, 500,
+    222, 833, 556, 556, 556, 556, 333, 500, 278, 556, 500, 722, 500, 500, 500, 334, 260, 334, 584,
+];
+
+#[derive(Clone, Copy)]
+enum Font {
+    Regular,
+    Bold,
+    Mono,
+    /// The embedded system font, for text WinAnsiEncoding cannot hold.
+    Unicode,
+}
+
+impl Font {
+    fn resource(self) -> &'static str {
+        match self {
+            Font::Regular => "F1",
+            Font::Bold => "F2",
+            Font::Mono => "F3",
+            Font::Unicode => "F4",
+        }
+    }
+
+    fn width(self, text: &str, size: f32) -> f32 {
+        let unicode = fonts::unicode_font().ok();
+        let units: f32 = text
+            .chars()
+            .map(|c| match (self, unicode) {
+                (Font::Mono, _) => 600.0,
+                (Font::Unicode, Some(font)) => glyph_width(font, font.glyph_id(c).0),
+                _ => {
+                    let base = match c as u32 {
+                        code @ 32..=126 => HELVETICA_WIDTHS[(code - 32) as usize] as f32,
+                        _ => 556.0,
+                    };
+                    // Helvetica-Bold runs slightly wider; close enough for wrapping.
+                    if matches!(self, Font::Bold) {
+                        base * 1.08
+                    } else {
+                        base
+                    }
+                }
+            })
+            .sum();
+        units * size / 1000.0
+    }
+}
+
+enum Block {
+    Heading(String),
+    Paragraph(String),
+    Item(String),
+    Code(String),
+}
+
+/// Renders a conversation to PDF. Text in Windows-1252 uses the standard 14
+/// fonts; a line with anything else is set in a system Unicode font, which
+/// is then embedded. Without such a font that fails rather than losing text.
+pub fn render(conversation: &Conversation, images: &ImageSet) -> Result<Vec<u8>, String> {
+    let mut doc = Document::with_version("1.7");
+    let mut writer = PageWriter::new();
+
+    writer.wrapped(Font::Bold, TITLE_SIZE, 0.0, &conversation.title)?;
+    writer.gap(4.0);
+    writer.set_gray(0.35);
+    writer.wrapped(
+        Font::Regular,
+        SMALL_SIZE,
+        0.0,
+        &format!(
+            "{} ({}) - {}",
+            conversation.model,
+            conversation.provider,
+            format_timestamp(conversation.created_at)
+        ),
+    )?;
+    writer.set_gray(0.0);
+
+    let mut xobjects = Dictionary::new();
+    for message in &conversation.messages {
+        let text = visible_text(conversation, message);
+        if text.is_none() && message.image_ids.is_empty() {
+            continue;
+        }
+
+        writer.gap(14.0);
+        writer.set_gray(0.35);
+        writer.wrapped(
+            Font::Bold,
+            SMALL_SIZE,
+            0.0,
+            &role_label(message.role).to_uppercase(),
+        )?;
+        writer.set_gray(0.0);
+        writer.gap(4.0);
+
+        for id in &message.image_ids {
+            let Some(image) = images.get(id) else {
+                continue;
+            };
+            let name = format!("Im{}", xobjects.len());
+            let (width, height, stream) = image_xobject(image)?;
+            xobjects.set(name.clone(), doc.add_object(stream));
+            writer.image(&name, width, height);
+            writer.gap(6.0);
+        }
+
+        if let Some(text) = text {
+            for block in blocks(text) {
+                match block {
+                    Block::Heading(line) => {
+                        writer.gap(4.0);
+                        writer.wrapped(Font::Bold, BODY_SIZE + 1.5, 0.0, &line)?;
+                    }
+                    Block::Paragraph(line) => {
+                        writer.wrapped(Font::Regular, BODY_SIZE, 0.0, &line)?
+                    }
+                    Block::Item(line) => writer.wrapped(
+                        Font::Regular,
+                        BODY_SIZE,
+                        LIST_INDENT,
+                        &format!("• {}", line),
+                    )?,
+                    Block::Code(code) => {
+                        for line in code.lines() {
+                            writer.wrapped(Font::Mono, SMALL_SIZE, 0.0, line)?;
+                        }
+                    }
+                }
+                writer.gap(4.0);
+            }
+        }
+    }
+
+    let mut fonts = dictionary! {
+        "F1" => standard_font(&mut doc, "Helvetica"),
+        "F2" => standard_font(&mut doc, "Helvetica-Bold"),
+        "F3" => standard_font(&mut doc, "Courier"),
+    };
+    if writer.unicode {
+        let font = unicode_font(&mut doc, fonts::unicode_font()?, &writer.glyphs)?;
+        fonts.set(Font::Unicode.resource(), font);
+    }
+    let resources_id = doc.add_object(dictionary! {
+        "Font" => fonts,
+        "XObject" => xobjects,
+    });
+
+    let pages_id = doc.new_object_id();
+    let mut kids = Vec::new();
+    for operations in writer.finish() {
+        let content = Content { operations }.encode().map_err(|e| e.to_string())?;
+        let content_id = doc.add_object(Stream::new(dictionary! {}, content));
+        kids.push(Object::from(doc.add_object(dictionary! {
+            "Type" => "Page",
+            "Parent" => pages_id,
+            "Contents" => content_id,
+        })));
+    }
+
+    let count = kids.len() as i64;
+    doc.objects.insert(
+        pages_id,
+        Object::Dictionary(dictionary! {
+            "Type" => "Pages",
+            "Kids" => kids,
+            "Count" => count,
+            "Resources" => resources_id,
+            "MediaBox" => vec![0.into(), 0.into(), PAGE_WIDTH.into(), PAGE_HEIGHT.into()],
+        }),
+    );
+    let catalog_id = doc.add_object(dictionary! {
+        "Type" => "Catalog",
+        "Pages" => pages_id,
+    });
+    let info_id = doc.add_object(dictionary! {
+        "Title" => text_string(&conversation.title),
+        "Producer" => Object::string_literal("Spatialshot"),
+    });
+    doc.trailer.set("Root", catalog_id);
+    doc.trailer.set("Info", info_id);
+    doc.compress();
+
+    let mut bytes = Vec::new();
+    doc.save_to(&mut bytes).map_err(|e| e.to_string())?;
+    Ok(bytes)
+}
+
+fn standard_font(doc: &mut Document, name: &str) -> Object {
+    doc.add_object(dictionary! {
+        "Type" => "Font",
+        "Subtype" => "Type1",
+        "BaseFont" => name,
+        "Encoding" => "WinAnsiEncoding",
+    })
+    .into()
+}
+
+/// A Type0 font over the whole TrueType file. Text is written as glyph ids
+/// (Identity-H), and `glyphs` maps each id used back to its character so the
+/// text can be copied and searched.
+fn unicode_font(
+    doc: &mut Document,
+    font: &FontVec,
+    glyphs: &BTreeMap<u16, char>,
+) -> Result<Object, String> {
+    let scale = 1000.0 / font.units_per_em().unwrap_or(1000.0);
+    let ascent = (font.ascent_unscaled() * scale).round() as i64;
+    let descent = (font.descent_unscaled() * scale).round() as i64;
+
+    let data = font.as_slice().to_vec();
+    let length = data.len() as i64;
+    let mut file = Stream::new(dictionary! { "Length1" => length }, data);
+    file.compress().map_err(|e| e.to_string())?;
+    let file_id = doc.add_object(file);
+    let descriptor_id = doc.add_object(dictionary! {
+        "Type" => "FontDescriptor",
+        "FontName" => UNICODE_FONT_NAME,
+        "Flags" => 32,
+        "FontBBox" => vec![0.into(), descent.into(), 1000.into(), ascent.into()],
+        "ItalicAngle" => 0,
+        "Ascent" => ascent,
+        "Descent" => descent,
+        "CapHeight" => ascent,
+        "StemV" => 80,
+        "FontFile2" => file_id,
+    });
+
+    let mut widths = Vec::new();
+    for &glyph in glyphs.keys() {
+        widths.push(Object::from(glyph as i64));
+        widths.push(vec![Object::from(glyph_width(font, glyph).round() as i64)].into());
+    }
+    let descendant_id = doc.add_object(dictionary! {
+        "Type" => "Font",
+        "Subtype" => "CIDFontType2",
+        "BaseFont" => UNICODE_FONT_NAME,
+        "CIDSystemInfo" => dictionary! {
+            "Registry" => Object::string_literal("Adobe"),
+            "Ordering" => Object::string_literal("Identity"),
+            "Supplement" => 0,
+        },
+        "FontDescriptor" => descriptor_id,
+        "W" => widths,
+        "CIDToGIDMap" => "Identity",
+    });
+
+    let mut to_unicode = Stream::new(dictionary! {}, to_unicode_cmap(glyphs).into_bytes());
+    to_unicode.compress().map_err(|e| e.to_string())?;
+    let to_unicode_id = doc.add_object(to_unicode);
+
+    Ok(doc
+        .add_object(dictionary! {
+            "Type" => "Font",
+            "Subtype" => "Type0",
+            "BaseFont" => UNICODE_FONT_NAME,
+            "Encoding" => "Identity-H",
+            "DescendantFonts" => vec![descendant_id.into()],
+            "ToUnicode" => to_unicode_id,
+        })
+        .into())
+}
+
+/// Advance width of `glyph`, in 1/1000 em.
+fn glyph_width(font: &FontVec, glyph: u16) -> f32 {
+    let units_per_em = font.units_per_em().unwrap_or(1000.0);
+    font.h_advance_unscaled(ab_glyph::GlyphId(glyph)) * 1000.0 / units_per_em
+}
+
+fn to_unicode_cmap(glyphs: &BTreeMap<u16, char>) -> String {
+    let mut cmap = String::from(
+        "/CIDInit /ProcSet findresource begin\n12 dict begin\nbegincmap\n\
+         /CIDSystemInfo << /Registry (Adobe) /Ordering (UCS) /Supplement 0 >> def\n\
+         /CMapName /Adobe-Identity-UCS def\n/CMapType 2 def\n\
+         1 begincodespacerange\n<0000> <FFFF>\nendcodespacerange\n",
+    );
+    let entries: Vec<(&u16, &char)> = glyphs.iter().collect();
+    // A bfchar section may hold at most 100 entries.
+    for chunk in entries.chunks(100) {
+        cmap.push_str(&format!("{} beginbfchar\n", chunk.len()));
+        for (glyph, c) in chunk {
+            let utf16: String = c
+                .encode_utf16(&mut [0; 2])
+                .iter()
+                .map(|unit| format!("{:04X}", unit))
+                .collect();
+            cmap.push_str(&format!("<{:04X}> <{}>\n", glyph, utf16));
+        }
+        cmap.push_str("endbfchar\n");
+    }
+    cmap.push_str("endcmap\nCMapName currentdict /CMap defineresource pop\nend\nend\n");
+    cmap
+}
+
+fn image_xobject(image: &ExportImage) -> Result<(u32, u32, Stream), String> {
+    let rgb = image::load_from_memory(&image.bytes)
+        .map_err(|e| format!("Failed to decode image: {}", e))?
+        .to_rgb8();
+    let (width, height) = rgb.dimensions();
+
+    let mut stream = Stream::new(
+        dictionary! {
+            "Type" => "XObject",
+            "Subtype" => "Image",
+            "Width" => width as i64,
+            "Height" => height as i64,
+            "ColorSpace" => "DeviceRGB",
+            "BitsPerComponent" => 8,
+        },
+        rgb.into_raw(),
+    );
+    stream.compress().map_err(|e| e.to_string())?;
+    Ok((width, height, stream))
+}
+
+/// Splits Markdown into the few block kinds the PDF renderer lays out.
+fn blocks(text: &str) -> Vec<Block> {
+    let mut blocks = Vec::new();
+    let mut buffer = String::new();
+    let mut in_item = false;
+    let mut in_code = false;
+
+    for event in Parser::new(text) {
+        match event {
+            Event::Start(Tag::Item) => {
+                flush(&mut buffer, Block::Item, &mut blocks);
+                in_item = true;
+            }
+            Event::End(TagEnd::Item) => {
+                flush(&mut buffer, Block::Item, &mut blocks);
+                in_item = false;
+            }
+            Event::Start(Tag::CodeBlock(_)) => in_code = true,
+            Event::End(TagEnd::CodeBlock) => {
+                flush(&mut buffer, Block::Code, &mut blocks);
+                in_code = false;
+            }
+            Event::End(TagEnd::Heading(_)) => flush(&mut buffer, Block::Heading, &mut blocks),
+            Event::End(TagEnd::Paragraph) if !in_item => {
+                flush(&mut buffer, Block::Paragraph, &mut blocks)
+            }
+            Event::End(TagEnd::Paragraph) => buffer.push(' '),
+            Event::Text(chunk)
+            | Event::Code(chunk)
+            | Event::Html(chunk)
+            | Event::InlineHtml(chunk) => buffer.push_str(&chunk),
+            Event::SoftBreak | Event::HardBreak if !in_code => buffer.push(' '),
+            Event::SoftBreak | Event::HardBreak => buffer.push('\n'),
+            _ => {}
+        }
+    }
+    flush(&mut buffer, Block::Paragraph, &mut blocks);
+
+    blocks
+}
+
+fn flush(buffer: &mut String, kind: fn(String) -> Block, blocks: &mut Vec<Block>) {
+    let text = buffer.trim_end().to_string();
+    if !text.trim().is_empty() {
+        blocks.push(kind(text));
+    }
+    buffer.clear();
+}
+
+/// Lays out lines top to bottom, starting a new page when one fills up.
+struct PageWriter {
+    pages: Vec<Vec<Operation>>,
+    y: f32,
+    gray: f32,
+    /// Whether any line is set in the Unicode font.
+    unicode: bool,
+    /// Glyphs set in the Unicode font, with the character each one shows.
+    glyphs: BTreeMap<u16, char>,
+}
+
+impl PageWriter {
+    fn new() -> Self {
+        Self {
+            pages: vec![Vec::new()],
+            y: PAGE_HEIGHT - MARGIN,
+            gray: 0.0,
+            unicode: false,
+            glyphs: BTreeMap::new(),
+        }
+    }
+
+    fn ops(&mut self) -> &mut Vec<Operation> {
+        self.pages.last_mut().unwrap()
+    }
+
+    fn ensure_space(&mut self, height: f32) {
+        if self.y - height < MARGIN && self.y < PAGE_HEIGHT - MARGIN {
+            self.pages.push(Vec::new());
+            self.y = PAGE_HEIGHT - MARGIN;
+            if self.gray != 0.0 {
+                let gray = self.gray;
+                self.ops().push(Operation::new("g", vec![gray.into()]));
+            }
+        }
+    }
+
+    fn gap(&mut self, height: f32) {
+        self.y -= height;
+    }
+
+    fn set_gray(&mut self, gray: f32) {
+        self.gray = gray;
+        self.ops().push(Operation::new("g", vec![gray.into()]));
+    }
+
+    fn wrapped(&mut self, font: Font, size: f32, indent: f32, text: &str) -> Result<(), String> {
+        let (font, unicode) = if text.chars().all(|c| win_ansi_byte(c).is_some()) {
+            (font, None)
+        } else {
+            let unicode = fonts::unicode_font().map_err(|e| {
+                format!(
+                    "This conversation has characters the built-in PDF fonts cannot show: {}",
+                    e
+                )
+            })?;
+            (Font::Unicode, Some(unicode))
+        };
+
+        let line_height = size * LEADING;
+        for line in wrap(font, size, CONTENT_WIDTH - indent, text) {
+            self.ensure_space(line_height);
+            self.y -= line_height;
+            let (x, y) = (MARGIN + indent, self.y + (line_height - size) / 2.0);
+            let shown = match unicode {
+                Some(unicode) => {
+                    Object::String(self.glyph_ids(unicode, &line), StringFormat::Hexadecimal)
+                }
+                None => Object::string_literal(win_ansi(&line)),
+            };
+            self.ops().extend([
+                Operation::new("BT", vec![]),
+                Operation::new("Tf", vec![font.resource().into(), size.into()]),
+                Operation::new("Td", vec![x.into(), y.into()]),
+                Operation::new("Tj", vec![shown]),
+                Operation::new("ET", vec![]),
+            ]);
+        }
+        Ok(())
+    }
+
+    /// Encodes a line for the Unicode font as big-endian glyph ids.
+    /// Characters the font lacks are drawn as its missing-glyph box.
+    fn glyph_ids(&mut self, font: &FontVec, line: &str) -> Vec<u8> {
+        self.unicode = true;
+        let mut bytes = Vec::new();
+        for c in line.chars() {
+            let glyph = font.glyph_id(c).0;
+            if glyph != 0 {
+                self.glyphs.entry(glyph).or_insert(c);
+            }
+            bytes.extend(glyph.to_be_bytes());
+        }
+        bytes
+    }
+
+    fn image(&mut self, name: &str, width: u32, height: u32) {
+        let scale = (CONTENT_WIDTH / width as f32)
+            .min(MAX_IMAGE_HEIGHT / height as f32)
+            .min(1.0);
+        let (w, h) = (width as f32 * scale, height as f32 * scale);
+
+        self.ensure_space(h);
+        self.y -= h;
+        let y = self.y;
+        self.ops().extend([
+            Operation::new("q", vec![]),
+            Operation::new(
+                "cm",
+                vec![
+                    w.into(),
+                    0.into(),
+                    0.into(),
+                    h.into(),
+                    MARGIN.into(),
+                    y.into(),
+                ],
+            ),
+            Operation::new("Do", vec![Object::Name(name.as_bytes().to_vec())]),
+            Operation::new("Q", vec![]),
+        ]);
+    }
+
+    fn finish(self) -> Vec<Vec<Operation>> {
+        self.pages
+    }
+}
+
+/// Greedy word wrap. Words wider than a line are broken by character.
+fn wrap(font: Font, size: f32, max_width: f32, text: &str) -> Vec<String> {
+    let text = text.replace('\t', "    ");
+    let mut lines = Vec::new();
+    let mut line = String::new();
+
+    for word in text.split(' ') {
+        let candidate = if line.is_empty() {
+            word.to_string()
+        } else {
+            format!("{} {}", line, word)
+        };
+        if font.width(&candidate, size) <= max_width {
+            line = candidate;
+            continue;
+        }
+
+        if !line.is_empty() {
+            lines.push(std::mem::take(&mut line));
+        }
+        for c in word.chars() {
+            line.push(c);
+            if font.width(&line, size) > max_width && line.chars().count() > 1 {
+                line.pop();
+                lines.push(std::mem::replace(&mut line, c.to_string()));
+            }
+        }
+    }
+    if !line.is_empty() || lines.is_empty() {
+        lines.push(line);
+    }
+    lines
+}
+
+/// Encodes text for the standard fonts' WinAnsiEncoding. Callers check
+/// that every character has a code first.
+fn win_ansi(text: &str) -> Vec<u8> {
+    text.chars()
+        .map(|c| win_ansi_byte(c).unwrap_or(b'?'))
+        .collect()
+}
+
+fn win_ansi_byte(c: char) -> Option<u8> {
+    match c {
+        ' '..='~' => Some(c as u8),
+        '\u{a0}'..='\u{ff}' => Some(c as u32 as u8),
+        '€' => Some(0x80),
+        '…' => Some(0x85),
+        '‘' => Some(0x91),
+        '’' => Some(0x92),
+        '“' => Some(0x93),
+        '”' => Some(0x94),
+        '•' => Some(0x95),
+        '–' => Some(0x96),
+        '—' => Some(0x97),
+        _ => None,
+    }
+}
+
+/// A PDF text string, in UTF-16 when Windows-1252 is not enough.
+fn text_string(text: &str) -> Object {
+    if text.chars().all(|c| win_ansi_byte(c).is_some()) {
+        return Object::string_literal(win_ansi(text));
+    }
+    let mut bytes = vec![0xfe, 0xff];
+    for unit in text.encode_utf16() {
+        bytes.extend(unit.to_be_bytes());
+    }
+    Object::String(bytes, StringFormat::Literal)
+}
@@ -0,0 +1,258 @@
+/*
+ * @license
+ * Copyright 2025 a7mddra
+ * SPDX-License-Identifier: Apache-2.0
+ */
+
+//! Structured extraction: asks the model for JSON matching a JSON Schema and
+//! checks the reply against it. A reply that does not match is sent back
+//! with the validation errors so the model can correct it.
+
+use jsonschema::Validator;
+use serde::Serialize;
+use serde_json::Value;
+use std::sync::atomic::AtomicBool;
+
+use crate::services::llm::{Message, ModelBackend, Part, Role};
+
+pub const DEFAULT_ATTEMPTS: u32 = 3;
+const DEFAULT_INSTRUCTIONS: &str = "Extract the data in this image.";
+/// Errors reported back to the model per attempt.
+const MAX_REPORTED_ERRORS: usize = 10;
+
+#[derive(Serialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct Extraction {
+    /// The model's reply, valid against the schema.
+    pub data: Value,
+    pub attempts: u32,
+}
+
+/// Compiles `schema`, rejecting documents that are not valid schemas.
+pub fn compile(schema: &Value) -> Result<Validator, String> {
+    jsonschema::validator_for(schema).map_err(|e| format!("Invalid JSON Schema: {}", e))
+}
+
+/// Asks about `image` until the reply validates against `schema`, giving
+/// up after `max_attempts` replies.
+pub fn extract(
+    backend: &dyn ModelBackend,
+    model: &str,
+    image: Part,
+    schema: &Value,
+    instructions: Option<&str>,
+    max_attempts: u32,
+    cancel: &AtomicBool,
+) -> Result<Extraction, String> {
+    let validator = compile(schema)?;
+    let system_instruction = system_instruction(schema);
+    let mut contents = vec![Message {
+        role: Role::User,
+        parts: vec![
+            image,
+            Part::Text {
+                text: instructions.unwrap_or(DEFAULT_INSTRUCTIONS).to_string(),
+            },
+        ],
+    }];
+
+    let mut problems = Vec::new();
+    for attempt in 1..=max_attempts.max(1) {
+        let reply = backend.stream_chat(
+            model,
+            Some(&system_instruction),
+            &contents,
+            cancel,
+            &mut |_| {},
+        )?;
+        problems = match parse_reply(&reply.text) {
+            Ok(data) => {
+                let errors = validation_errors(&validator, &data);
+                if errors.is_empty() {
+                    return Ok(Extraction {
+                        data,
+                        attempts: attempt,
+                    });
+                }
+                errors
+            }
+            Err(e) => vec![e],
+        };
+
+        contents.push(Message::text(Role::Model, &reply.text));
+        contents.push(Message::text(Role::User, &correction(&problems)));
+    }
+
+    Err(format!(
+        "The model's reply did not match the schema: {}",
+        problems.join("; ")
+    ))
+}
+
+fn validation_errors(validator: &Validator, data: &Value) -> Vec<String> {
+    validator
+        .iter_errors(data)
+        .take(MAX_REPORTED_ERRORS)
+        .map(|error| match error.instance_path.to_string() {
+            path if path.is_empty() => error.to_string(),
+            path => format!("{}: {}", path, error),
+        })
+        .collect()
+}
+
+fn system_instruction(schema: &Value) -> String {
+    format!(
+        "Reply with a single JSON value that matches this JSON Schema. \
+         Do not add explanations or code fences. Use null for values the \
+         image does not show, where the schema allows it.\n\n{}",
+        serde_json::to_string_pretty(schema).unwrap_or_default()
+    )
+}
+
+fn correction(problems: &[String]) -> String {
+    let list: Vec<String> = problems.iter().map(|p| format!("- {}", p)).collect();
+    format!(
+        "That reply does not match the schema:\n{}\nReply again with only the corrected JSON.",
+        list.join("\n")
+    )
+}
+
+/// Reads the JSON in a reply, allowing for a Markdown code fence or a
+/// sentence around it.
+fn parse_reply(text: &str) -> Result<Value, String> {
+    let text = text.trim();
+    if let Ok(value) = serde_json::from_str(text) {
+        return Ok(value);
+    }
+
+    let start = text.find(&['{', '['][..]);
+    let end = text.rfind(&['}', ']'][..]);
+    match (start, end) {
+        (Some(start), Some(end)) if start < end => serde_json::from_str(&text[start..=end])
+            .map_err(|e| format!("The reply is not valid JSON: {}", e)),
+        _ => Err("The reply does not contain JSON".into()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::llm::gemini::GeminiBackend;
+    use crate::testing::mock_server_sequence;
+    use serde_json::json;
+
+    fn image() -> Part {
+        Part::Image {
+            mime_type: "image/png".into(),
+            data: "AAAA".into(),
+        }
+    }
+
+    fn schema() -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "rows": {
+                    "type": "array",
+                    "items": {
+                        "type": "object",
+                        "properties": {
+                            "name": { "type": "string" },
+                            "price": { "type": "number" }
+                        },
+                        "required": ["name", "price"]
+                    }
+                }
+            },
+            "required": ["rows"]
+        })
+    }
+
+    #[test]
+    fn reads_json_around_prose_and_fences() {
+        assert_eq!(parse_reply("{\"a\": 1}").unwrap(), json!({ "a": 1 }));
+        assert_eq!(
+            parse_reply("Here you go:\n```json\n[1, 2]\n```").unwrap(),
+            json!([1, 2])
+        );
+        assert!(parse_reply("No data found.").is_err());
+        assert!(compile(&json!({ "type": "sometimes" })).is_err());
+    }
+
+    #[test]
+    fn retries_with_the_validation_errors() {
+        let (base_url, server) = mock_server_sequence(vec![
+            (
+                200,
+                "text/event-stream",
+                "data: {\"candidates\":[{\"content\":{\"parts\":[{\"text\":\
+                 \"{\\\"rows\\\":[{\\\"name\\\":\\\"Tea\\\",\\\"price\\\":\\\"3.50\\\"}]}\"}]}}]}\n\n",
+            ),
+            (
+                200,
+                "text/event-stream",
+                "data: {\"candidates\":[{\"content\":{\"parts\":[{\"text\":\
+                 \"```json\\n{\\\"rows\\\":[{\\\"name\\\":\\\"Tea\\\",\\\"price\\\":3.5}]}\\n```\"}]}}]}\n\n",
+            ),
+        ]);
+        let backend = GeminiBackend::new(&base_url, "test-key").unwrap();
+
+        let extraction = extract(
+            &backend,
+            "gemini-test",
+            image(),
+            &schema(),
+            Some("List the menu"),
+            DEFAULT_ATTEMPTS,
+            &AtomicBool::new(false),
+        )
+        .unwrap();
+
+        assert_eq!(
+            extraction,
+            Extraction {
+                data: json!({ "rows": [{ "name": "Tea", "price": 3.5 }] }),
+                attempts: 2
+            }
+        );
+        let received = server.join().unwrap();
+        let first = &received[0].body;
+        assert!(first["systemInstruction"]["parts"][0]["text"]
+            .as_str()
+            .unwrap()
+            .contains("\"required\""));
+        assert_eq!(first["contents"][0]["parts"][1]["text"], "List the menu");
+        let correction = received[1].body["contents"][2]["parts"][0]["text"]
+            .as_str()
+            .unwrap()
+            .to_string();
+        assert!(correction.contains("/rows/0/price"), "{}", correction);
+    }
+
+    #[test]
+    fn gives_up_after_the_last_attempt() {
+        let (base_url, server) = mock_server_sequence(vec![(
+            200,
+            "text/event-stream",
+            "data: {\"candidates\":[{\"content\":{\"parts\":[{\"text\":\"I cannot read it.\"}]}}]}\n\n",
+        )]);
+        let backend = GeminiBackend::new(&base_url, "test-key").unwrap();
+
+        let err = extract(
+            &backend,
+            "gemini-test",
+            image(),
+            &schema(),
+            None,
+            1,
+            &AtomicBool::new(false),
+        )
+        .unwrap_err();
+
+        assert_eq!(
+            err,
+            "The model's reply did not match the schema: The reply does not contain JSON"
+        );
+        server.join().unwrap();
+    }
+}
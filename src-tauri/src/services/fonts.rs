@@ -0,0 +1,38 @@
+/*
+ * @license
+ * Copyright 2025 a7mddra
+ * SPDX-License-Identifier: Apache-2.0
+ */
+
+//! A system TrueType font for text the app draws itself, in image labels
+//! and PDF exports. No font is bundled, so the first one found is used.
+
+use ab_glyph::FontVec;
+use std::sync::OnceLock;
+
+/// Fonts tried, in order. All are plain `.ttf` files, which a PDF can embed.
+const UNICODE_FONTS: &[&str] = &[
+    "/usr/share/fonts/truetype/dejavu/DejaVuSans.ttf",
+    "/usr/share/fonts/TTF/DejaVuSans.ttf",
+    "/usr/share/fonts/dejavu/DejaVuSans.ttf",
+    "/usr/share/fonts/truetype/liberation/LiberationSans-Regular.ttf",
+    "/usr/share/fonts/truetype/noto/NotoSans-Regular.ttf",
+    "/usr/share/fonts/noto/NotoSans-Regular.ttf",
+    "/System/Library/Fonts/Supplemental/Arial Unicode.ttf",
+    "/System/Library/Fonts/Supplemental/Arial.ttf",
+    "/Library/Fonts/Arial.ttf",
+    "C:\\Windows\\Fonts\\segoeui.ttf",
+    "C:\\Windows\\Fonts\\arial.ttf",
+];
+
+pub fn unicode_font() -> Result<&'static FontVec, String> {
+    static FONT: OnceLock<Option<FontVec>> = OnceLock::new();
+    FONT.get_or_init(|| {
+        UNICODE_FONTS.iter().find_map(|path| {
+            let data = std::fs::read(path).ok()?;
+            FontVec::try_from_vec(data).ok()
+        })
+    })
+    .as_ref()
+    .ok_or_else(|| "No Unicode font found on this system".to_string())
+}
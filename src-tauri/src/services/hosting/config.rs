@@ -0,0 +1,193 @@
+/*
+ * @license
+ * Copyright 2025 a7mddra
+ * SPDX-License-Identifier: Apache-2.0
+ */
+
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+use tauri::AppHandle;
+
+use super::http::HttpHost;
+use super::imgbb::{self, ImgBbHost};
+use super::s3::{S3Host, S3Options};
+use super::ImageHost;
+use crate::services::security;
+
+pub const IMGBB_KEY_PROVIDER: &str = "imgbb";
+
+const CONFIG_FILE: &str = "image_host.json";
+
+/// Reverse search only needs the image for a moment, so uploads expire after
+/// a day unless configured otherwise.
+pub const DEFAULT_EXPIRATION_SECS: u64 = 24 * 60 * 60;
+
+/// Where images are uploaded. Stored encrypted as `image_host.json` in the
+/// config dir since it may hold credentials.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(tag = "kind", rename_all = "lowercase")]
+pub enum HostConfig {
+    /// Without an explicit key, the one saved during onboarding is used.
+    #[serde(rename_all = "camelCase")]
+    Imgbb {
+        #[serde(default)]
+        api_key: Option<String>,
+    },
+    #[serde(rename_all = "camelCase")]
+    S3 {
+        endpoint: String,
+        region: String,
+        bucket: String,
+        access_key_id: String,
+        secret_access_key: String,
+        #[serde(default)]
+        path_style: bool,
+        #[serde(default = "default_prefix")]
+        prefix: String,
+    },
+    #[serde(rename_all = "camelCase")]
+    Http {
+        url: String,
+        #[serde(default)]
+        token: Option<String>,
+    },
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct HostingConfig {
+    pub host: HostConfig,
+    /// Seconds until an upload expires; `None` keeps it as long as the host
+    /// allows.
+    #[serde(default = "default_expiration")]
+    pub expiration_secs: Option<u64>,
+}
+
+/// What the frontend may see of the host: everything but credentials.
+#[derive(Serialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct HostSummary {
+    pub kind: &'static str,
+    /// The S3 endpoint or upload URL; `None` for ImgBB.
+    pub endpoint: Option<String>,
+    pub expiration_secs: Option<u64>,
+    /// False while a required credential is missing.
+    pub ready: bool,
+}
+
+impl Default for HostingConfig {
+    fn default() -> Self {
+        Self {
+            host: HostConfig::Imgbb { api_key: None },
+            expiration_secs: default_expiration(),
+        }
+    }
+}
+
+fn default_expiration() -> Option<u64> {
+    Some(DEFAULT_EXPIRATION_SECS)
+}
+
+fn default_prefix() -> String {
+    "spatialshot/".to_string()
+}
+
+impl HostingConfig {
+    pub fn kind(&self) -> &'static str {
+        match self.host {
+            HostConfig::Imgbb { .. } => "imgbb",
+            HostConfig::S3 { .. } => "s3",
+            HostConfig::Http { .. } => "http",
+        }
+    }
+
+    pub fn expiration(&self) -> Option<Duration> {
+        self.expiration_secs.map(Duration::from_secs)
+    }
+
+    pub fn summary(&self) -> HostSummary {
+        let (endpoint, ready) = match &self.host {
+            HostConfig::Imgbb { api_key } => (None, api_key.is_some()),
+            HostConfig::S3 {
+                endpoint, bucket, ..
+            } => (Some(format!("{}/{}", endpoint, bucket)), true),
+            HostConfig::Http { url, .. } => (Some(url.clone()), true),
+        };
+        HostSummary {
+            kind: self.kind(),
+            endpoint,
+            expiration_secs: self.expiration_secs,
+            ready,
+        }
+    }
+
+    pub fn open(&self) -> Result<Box<dyn ImageHost>, String> {
+        Ok(match &self.host {
+            HostConfig::Imgbb { api_key } => {
+                let key = api_key
+                    .as_deref()
+                    .ok_or("ImgBB API key is not configured")?;
+                Box::new(ImgBbHost::new(imgbb::DEFAULT_BASE_URL, key)?)
+            }
+            HostConfig::S3 {
+                endpoint,
+                region,
+                bucket,
+                access_key_id,
+                secret_access_key,
+                path_style,
+                prefix,
+            } => Box::new(S3Host::new(S3Options {
+                endpoint,
+                region,
+                bucket,
+                access_key_id,
+                secret_access_key,
+                path_style: *path_style,
+                prefix,
+            })?),
+            HostConfig::Http { url, token } => Box::new(HttpHost::new(url, token.as_deref())?),
+        })
+    }
+}
+
+/// A host able to delete uploads made to `kind`. ImgBB deletes need no key,
+/// but the other hosts need the credentials of the current configuration.
+pub fn deleter_for(config: &HostingConfig, kind: &str) -> Result<Box<dyn ImageHost>, String> {
+    if kind == "imgbb" {
+        let key = match &config.host {
+            HostConfig::Imgbb { api_key } => api_key.clone().unwrap_or_default(),
+            _ => String::new(),
+        };
+        return Ok(Box::new(ImgBbHost::new(imgbb::DEFAULT_BASE_URL, &key)?));
+    }
+    if config.kind() != kind {
+        return Err(format!(
+            "Switch back to the {} image host to delete this upload",
+            kind
+        ));
+    }
+    config.open()
+}
+
+pub fn load_config(app: &AppHandle) -> Result<HostingConfig, String> {
+    let mut config = match security::load_decrypted(app, CONFIG_FILE) {
+        Some(json) => serde_json::from_str::<HostingConfig>(&json)
+            .map_err(|e| format!("Invalid image host settings: {}", e))?,
+        None => HostingConfig::default(),
+    };
+
+    if let HostConfig::Imgbb { api_key: None } = config.host {
+        config.host = HostConfig::Imgbb {
+            api_key: security::get_decrypted_key_internal(app, IMGBB_KEY_PROVIDER)
+                .filter(|key| !key.is_empty()),
+        };
+    }
+    Ok(config)
+}
+
+pub fn save_config(app: &AppHandle, config: &HostingConfig) -> Result<(), String> {
+    let json = serde_json::to_string(config).map_err(|e| e.to_string())?;
+    security::encrypt_and_store(app, CONFIG_FILE, &json)?;
+    Ok(())
+}
@@ -0,0 +1,146 @@
+/*
+ * @license
+ * Copyright 2025 a7mddra
+ * SPDX-License-Identifier: Apache-2.0
+ */
+
+use reqwest::blocking::{Client, RequestBuilder};
+use serde::Deserialize;
+use std::time::Duration;
+
+use super::{check_status, expires_at, http_client, is_gone, HostedImage, ImageHost};
+
+/// A self-hosted upload endpoint. The image is POSTed as the raw body with
+/// its `Content-Type`, an `X-Image-Name` header and, when set, an
+/// `X-Expires-In` header in seconds. The endpoint answers with JSON:
+/// `{ "url": ..., "deleteUrl"?: ..., "expiresAt"?: unix millis }`.
+/// Deletes are a `DELETE` to `deleteUrl`. The token, if any, is sent as a
+/// bearer token on both.
+pub struct HttpHost {
+    client: Client,
+    url: String,
+    token: Option<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct UploadResponse {
+    url: String,
+    delete_url: Option<String>,
+    expires_at: Option<i64>,
+}
+
+impl HttpHost {
+    pub fn new(url: &str, token: Option<&str>) -> Result<Self, String> {
+        Ok(Self {
+            client: http_client()?,
+            url: url.to_string(),
+            token: token.filter(|t| !t.is_empty()).map(str::to_string),
+        })
+    }
+
+    fn authorize(&self, request: RequestBuilder) -> RequestBuilder {
+        match &self.token {
+            Some(token) => request.bearer_auth(token),
+            None => request,
+        }
+    }
+}
+
+impl ImageHost for HttpHost {
+    fn upload(
+        &self,
+        name: &str,
+        bytes: &[u8],
+        mime_type: &str,
+        expiration: Option<Duration>,
+    ) -> Result<HostedImage, String> {
+        let mut request = self
+            .client
+            .post(&self.url)
+            .header("Content-Type", mime_type)
+            .header("X-Image-Name", name)
+            .body(bytes.to_vec());
+        if let Some(expiration) = expiration {
+            request = request.header("X-Expires-In", expiration.as_secs().to_string());
+        }
+
+        let response = self
+            .authorize(request)
+            .send()
+            .map_err(|e| format!("Upload request failed: {}", e))?;
+        let body: UploadResponse = check_status(response, "Upload endpoint")?
+            .json()
+            .map_err(|e| format!("Invalid upload response: {}", e))?;
+
+        Ok(HostedImage {
+            url: body.url,
+            delete_url: body.delete_url,
+            expires_at: body.expires_at.or_else(|| expires_at(expiration)),
+        })
+    }
+
+    fn delete(&self, delete_url: &str) -> Result<(), String> {
+        let response = self
+            .authorize(self.client.delete(delete_url))
+            .send()
+            .map_err(|e| format!("Delete request failed: {}", e))?;
+        if is_gone(&response) {
+            return Ok(());
+        }
+        check_status(response, "Upload endpoint")?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::mock_server;
+
+    #[test]
+    fn posts_raw_image_with_headers() {
+        let (base_url, server) = mock_server(
+            201,
+            "application/json",
+            r#"{"url":"https://img.example.com/abc.png","deleteUrl":"https://img.example.com/abc.png?token=x","expiresAt":1700000000000}"#,
+        );
+        let host = HttpHost::new(&format!("{}/upload", base_url), Some("s3cret")).unwrap();
+        let hosted = host
+            .upload(
+                "abc",
+                b"png bytes",
+                "image/png",
+                Some(Duration::from_secs(3600)),
+            )
+            .unwrap();
+        let received = server.join().unwrap();
+
+        assert_eq!(received.method, "POST");
+        assert_eq!(received.url, "/upload");
+        assert_eq!(received.header("authorization"), Some("Bearer s3cret"));
+        assert_eq!(received.header("x-image-name"), Some("abc"));
+        assert_eq!(received.header("x-expires-in"), Some("3600"));
+        assert_eq!(received.raw, "png bytes");
+        assert_eq!(
+            hosted,
+            HostedImage {
+                url: "https://img.example.com/abc.png".into(),
+                delete_url: Some("https://img.example.com/abc.png?token=x".into()),
+                expires_at: Some(1_700_000_000_000),
+            }
+        );
+    }
+
+    #[test]
+    fn deletes_with_token() {
+        let (base_url, server) = mock_server(204, "text/plain", "");
+        let host = HttpHost::new(&base_url, Some("s3cret")).unwrap();
+        host.delete(&format!("{}/abc.png", base_url)).unwrap();
+        let received = server.join().unwrap();
+
+        assert_eq!(received.method, "DELETE");
+        assert_eq!(received.url, "/abc.png");
+        assert_eq!(received.header("authorization"), Some("Bearer s3cret"));
+    }
+}
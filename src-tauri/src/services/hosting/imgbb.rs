@@ -0,0 +1,237 @@
+/*
+ * @license
+ * Copyright 2025 a7mddra
+ * SPDX-License-Identifier: Apache-2.0
+ */
+
+use base64::{engine::general_purpose, Engine as _};
+use regex::Regex;
+use reqwest::blocking::Client;
+use serde_json::Value;
+use std::sync::OnceLock;
+use std::time::Duration;
+use url::Url;
+
+use super::{check_status, expires_at, http_client, is_gone, HostedImage, ImageHost};
+
+pub const DEFAULT_BASE_URL: &str = "https://api.imgbb.com";
+
+/// ImgBB accepts expirations between one minute and 180 days.
+const MIN_EXPIRATION_SECS: u64 = 60;
+const MAX_EXPIRATION_SECS: u64 = 180 * 24 * 60 * 60;
+
+pub struct ImgBbHost {
+    client: Client,
+    base_url: String,
+    api_key: String,
+}
+
+impl ImgBbHost {
+    pub fn new(base_url: &str, api_key: &str) -> Result<Self, String> {
+        Ok(Self {
+            client: http_client()?,
+            base_url: base_url.trim_end_matches('/').to_string(),
+            api_key: api_key.to_string(),
+        })
+    }
+}
+
+impl ImageHost for ImgBbHost {
+    fn upload(
+        &self,
+        name: &str,
+        bytes: &[u8],
+        _mime_type: &str,
+        expiration: Option<Duration>,
+    ) -> Result<HostedImage, String> {
+        let expiration = expiration.map(|d| {
+            Duration::from_secs(d.as_secs().clamp(MIN_EXPIRATION_SECS, MAX_EXPIRATION_SECS))
+        });
+
+        let mut query = vec![("key", self.api_key.clone())];
+        if let Some(expiration) = expiration {
+            query.push(("expiration", expiration.as_secs().to_string()));
+        }
+        let form = [
+            ("image", general_purpose::STANDARD.encode(bytes)),
+            ("name", name.to_string()),
+        ];
+
+        let response = self
+            .client
+            .post(format!("{}/1/upload", self.base_url))
+            .query(&query)
+            .form(&form)
+            .send()
+            .map_err(|e| format!("ImgBB request failed: {}", e))?;
+        let body: Value = check_status(response, "ImgBB")?
+            .json()
+            .map_err(|e| format!("Invalid ImgBB response: {}", e))?;
+
+        let url = body["data"]["url"]
+            .as_str()
+            .ok_or("ImgBB response has no image URL")?;
+        Ok(HostedImage {
+            url: url.to_string(),
+            delete_url: body["data"]["delete_url"].as_str().map(str::to_string),
+            expires_at: expires_at(expiration),
+        })
+    }
+
+    /// ImgBB has no delete API. Its delete URL, `https://ibb.co/<id>/<hash>`,
+    /// is a page whose button posts the id and hash, with the page's auth
+    /// token, to `/json`; this does the same.
+    fn delete(&self, delete_url: &str) -> Result<(), String> {
+        let url = Url::parse(delete_url).map_err(|e| format!("Invalid delete URL: {}", e))?;
+        let segments: Vec<&str> = url
+            .path_segments()
+            .map(|segments| segments.filter(|s| !s.is_empty()).collect())
+            .unwrap_or_default();
+        let [id, hash] = segments[..] else {
+            return Err(format!("Unrecognised ImgBB delete URL: {}", delete_url));
+        };
+
+        let page = self
+            .client
+            .get(url.as_str())
+            .send()
+            .map_err(|e| format!("ImgBB request failed: {}", e))?;
+        if is_gone(&page) {
+            return Ok(());
+        }
+        let page = check_status(page, "ImgBB")?
+            .text()
+            .map_err(|e| format!("Invalid ImgBB response: {}", e))?;
+        let token = auth_token(&page).ok_or("ImgBB delete page has no auth token")?;
+
+        let mut endpoint = url.clone();
+        endpoint.set_path("/json");
+        endpoint.set_query(None);
+        let form = [
+            ("auth_token", token),
+            ("pathname", url.path()),
+            ("action", "delete"),
+            ("delete", "image"),
+            ("from", "resource"),
+            ("deleting[id]", id),
+            ("deleting[hash]", hash),
+        ];
+        let response = self
+            .client
+            .post(endpoint)
+            .form(&form)
+            .send()
+            .map_err(|e| format!("ImgBB request failed: {}", e))?;
+        let body: Value = check_status(response, "ImgBB")?
+            .json()
+            .map_err(|e| format!("Invalid ImgBB response: {}", e))?;
+
+        if body["success"].is_null() {
+            return Err(body["error"]["message"]
+                .as_str()
+                .unwrap_or("ImgBB did not delete the image")
+                .to_string());
+        }
+        Ok(())
+    }
+}
+
+/// The CSRF token the delete page embeds, either in its JS config or in a
+/// hidden form field.
+fn auth_token(page: &str) -> Option<&str> {
+    static PATTERN: OnceLock<Regex> = OnceLock::new();
+    let pattern = PATTERN.get_or_init(|| {
+        Regex::new(r#"auth_token"?\s*(?:value)?=\s*["']([0-9A-Za-z]+)["']"#).unwrap()
+    });
+    pattern
+        .captures(page)
+        .and_then(|captures| captures.get(1))
+        .map(|m| m.as_str())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::{mock_server, mock_server_sequence};
+
+    #[test]
+    fn uploads_with_expiration() {
+        let (base_url, server) = mock_server(
+            200,
+            "application/json",
+            r#"{"data":{"url":"https://i.ibb.co/abc/shot.png","delete_url":"https://ibb.co/abc/def"},"success":true,"status":200}"#,
+        );
+        let host = ImgBbHost::new(&base_url, "test-key").unwrap();
+        let hosted = host
+            .upload("shot", b"png", "image/png", Some(Duration::from_secs(5)))
+            .unwrap();
+        let received = server.join().unwrap();
+
+        assert_eq!(hosted.url, "https://i.ibb.co/abc/shot.png");
+        assert_eq!(hosted.delete_url.as_deref(), Some("https://ibb.co/abc/def"));
+        assert!(hosted.expires_at.is_some());
+        assert_eq!(received.url, "/1/upload?key=test-key&expiration=60");
+        assert_eq!(
+            received.header("content-type"),
+            Some("application/x-www-form-urlencoded")
+        );
+        assert_eq!(received.raw, "image=cG5n&name=shot");
+    }
+
+    #[test]
+    fn reports_upload_errors() {
+        let (base_url, server) = mock_server(
+            400,
+            "application/json",
+            r#"{"status_code":400,"error":{"message":"Invalid API v1 key.","code":100},"status_txt":"Bad Request"}"#,
+        );
+        let host = ImgBbHost::new(&base_url, "bad").unwrap();
+        let err = host.upload("shot", b"png", "image/png", None).unwrap_err();
+        server.join().unwrap();
+        assert_eq!(err, "ImgBB error 400: Invalid API v1 key.");
+    }
+
+    #[test]
+    fn deletes_through_the_delete_page() {
+        let (base_url, server) = mock_server_sequence(vec![
+            (
+                200,
+                "text/html",
+                "<script>PF.obj.config.auth_token=\"f00dcafe\";</script>",
+            ),
+            (
+                200,
+                "application/json",
+                r#"{"success":{"message":"Content deleted","code":200},"status_code":200}"#,
+            ),
+        ]);
+        let host = ImgBbHost::new(&base_url, "test-key").unwrap();
+        host.delete(&format!("{}/abc123/def456", base_url)).unwrap();
+        let received = server.join().unwrap();
+
+        assert_eq!(received[0].method, "GET");
+        assert_eq!(received[0].url, "/abc123/def456");
+        assert_eq!(received[1].method, "POST");
+        assert_eq!(received[1].url, "/json");
+        assert_eq!(
+            received[1].raw,
+            "auth_token=f00dcafe&pathname=%2Fabc123%2Fdef456&action=delete&delete=image\
+             &from=resource&deleting%5Bid%5D=abc123&deleting%5Bhash%5D=def456"
+        );
+    }
+
+    #[test]
+    fn treats_missing_pages_as_deleted() {
+        let (base_url, server) = mock_server(404, "text/html", "Not found");
+        let host = ImgBbHost::new(&base_url, "test-key").unwrap();
+        host.delete(&format!("{}/abc123/def456", base_url)).unwrap();
+        server.join().unwrap();
+    }
+
+    #[test]
+    fn finds_auth_token_in_form_fields() {
+        let page = r#"<input type="hidden" name="auth_token" value="0a1b2c">"#;
+        assert_eq!(auth_token(page), Some("0a1b2c"));
+        assert_eq!(auth_token("<html></html>"), None);
+    }
+}
@@ -0,0 +1,78 @@
+/*
+ * @license
+ * Copyright 2025 a7mddra
+ * SPDX-License-Identifier: Apache-2.0
+ */
+
+use serde::Serialize;
+use tauri::AppHandle;
+
+use super::config::{self, HostingConfig};
+use super::now_millis;
+use crate::services::conversations::{self, Upload};
+
+const DAY_MILLIS: i64 = 24 * 60 * 60 * 1000;
+
+#[derive(Serialize, Clone, Debug, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct CleanupReport {
+    pub deleted: u32,
+    /// One message per upload that could not be deleted; those stay listed.
+    pub errors: Vec<String>,
+}
+
+pub fn list(app: &AppHandle) -> Result<Vec<Upload>, String> {
+    conversations::with_store(app, |store| store.list_uploads())
+}
+
+/// Deletes an upload from its host, then from the ledger.
+pub fn delete(app: &AppHandle, id: i64) -> Result<(), String> {
+    let upload =
+        conversations::with_store(app, |store| store.upload(id))?.ok_or("Upload not found")?;
+    let config = config::load_config(app)?;
+    delete_one(app, &config, &upload)
+}
+
+/// Deletes every upload made more than `days` days ago. Failures are
+/// collected rather than stopping the sweep.
+pub fn delete_older_than(app: &AppHandle, days: u32) -> Result<CleanupReport, String> {
+    let cutoff = now_millis() - i64::from(days) * DAY_MILLIS;
+    let uploads = conversations::with_store(app, |store| store.uploads_before(cutoff))?;
+    let report = delete_all(app, uploads)?;
+    println!(
+        "Deleted {} uploads older than {} days, {} failed",
+        report.deleted,
+        days,
+        report.errors.len()
+    );
+    Ok(report)
+}
+
+/// Deletes `uploads` from their hosts and the ledger, collecting failures.
+pub fn delete_all(app: &AppHandle, uploads: Vec<Upload>) -> Result<CleanupReport, String> {
+    let config = config::load_config(app)?;
+
+    let mut report = CleanupReport::default();
+    for upload in uploads {
+        match delete_one(app, &config, &upload) {
+            Ok(()) => report.deleted += 1,
+            Err(e) => report.errors.push(format!("{}: {}", upload.url, e)),
+        }
+    }
+    Ok(report)
+}
+
+fn delete_one(app: &AppHandle, config: &HostingConfig, upload: &Upload) -> Result<(), String> {
+    match &upload.delete_url {
+        Some(delete_url) => config::deleter_for(config, &upload.host)?.delete(delete_url)?,
+        // Nothing to call, but an expired upload is gone anyway.
+        None if upload.expires_at.is_some_and(|at| at <= now_millis()) => {}
+        None => {
+            return Err(format!(
+                "{} gave no delete URL for this upload",
+                upload.host
+            ))
+        }
+    }
+    conversations::with_store(app, |store| store.remove_upload(upload.id))
+}
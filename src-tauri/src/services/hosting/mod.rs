@@ -0,0 +1,145 @@
+/*
+ * @license
+ * Copyright 2025 a7mddra
+ * SPDX-License-Identifier: Apache-2.0
+ */
+
+pub mod config;
+pub mod http;
+pub mod imgbb;
+pub mod ledger;
+pub mod s3;
+
+use reqwest::blocking::{Client, Response};
+use reqwest::StatusCode;
+use serde::Serialize;
+use serde_json::Value;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tauri::AppHandle;
+
+use crate::services::conversations;
+use crate::services::pii::{self, Outbound};
+
+/// Somewhere to put an image so a web service can fetch it by URL.
+/// Implementations are blocking and are called from `spawn_blocking`.
+pub trait ImageHost: Send + Sync {
+    /// Uploads `bytes` under `name` (no extension). With `expiration` the
+    /// host drops the image, or stops serving it, after that long.
+    fn upload(
+        &self,
+        name: &str,
+        bytes: &[u8],
+        mime_type: &str,
+        expiration: Option<Duration>,
+    ) -> Result<HostedImage, String>;
+
+    /// Removes an earlier upload using its `delete_url`. An upload that is
+    /// already gone counts as deleted.
+    fn delete(&self, delete_url: &str) -> Result<(), String>;
+}
+
+#[derive(Serialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct HostedImage {
+    pub url: String,
+    pub delete_url: Option<String>,
+    /// Unix millis after which `url` stops working, when known.
+    pub expires_at: Option<i64>,
+}
+
+/// An earlier upload is reused only if its URL outlives this margin, so the
+/// page fetching it does not race the expiry.
+const REUSE_MARGIN_MILLIS: i64 = 10 * 60 * 1000;
+
+/// Uploads an image to the configured host after the PII check, and records
+/// the upload so it can be cleaned up later. An image already uploaded to the
+/// same host is not uploaded again while its URL still works.
+pub fn publish(app: &AppHandle, image_id: &str, bytes: &[u8]) -> Result<HostedImage, String> {
+    let config = config::load_config(app)?;
+    let host = config.open()?;
+
+    let (image_id, bytes) = match pii::preflight(app, image_id, bytes)? {
+        Outbound::Unchanged => (image_id.to_string(), bytes.to_vec()),
+        Outbound::Redacted(version) => (version.id, version.bytes),
+    };
+
+    let reusable = conversations::with_store(app, |store| {
+        store.reusable_upload(&image_id, config.kind(), now_millis() + REUSE_MARGIN_MILLIS)
+    })?;
+    if let Some(upload) = reusable {
+        println!("Reusing upload on {}: {}", config.kind(), upload.url);
+        return Ok(upload.hosted());
+    }
+
+    let mime_type = image::guess_format(&bytes)
+        .map(|f| f.to_mime_type())
+        .unwrap_or("image/png");
+
+    // Image ids are content hashes; a prefix is plenty for a file name.
+    let name = &image_id[..image_id.len().min(16)];
+    let hosted = host.upload(name, &bytes, mime_type, config.expiration())?;
+    println!("Uploaded image to {}: {}", config.kind(), hosted.url);
+
+    conversations::with_store(app, |store| {
+        store.record_upload(&image_id, config.kind(), &hosted)
+    })?;
+    Ok(hosted)
+}
+
+/// Uploads can be large and hosts slow, so the timeout is generous.
+fn http_client() -> Result<Client, String> {
+    Client::builder()
+        .timeout(Duration::from_secs(60))
+        .build()
+        .map_err(|e| e.to_string())
+}
+
+/// Turns a non-success response into an error that carries the status code
+/// and the host's own message when it sent one.
+fn check_status(response: Response, host: &str) -> Result<Response, String> {
+    let status = response.status();
+    if status.is_success() {
+        return Ok(response);
+    }
+
+    let body = response.text().unwrap_or_default();
+    let json: Value = serde_json::from_str(&body).unwrap_or_default();
+    let message = json["error"]["message"]
+        .as_str()
+        .or_else(|| json["error"].as_str())
+        .map(str::to_string)
+        .or_else(|| s3::error_message(&body))
+        .unwrap_or_else(|| {
+            status
+                .canonical_reason()
+                .unwrap_or("unknown error")
+                .to_string()
+        });
+    Err(format!("{} error {}: {}", host, status.as_u16(), message))
+}
+
+/// Whether the host no longer has what was asked for.
+fn is_gone(response: &Response) -> bool {
+    response.status() == StatusCode::NOT_FOUND
+}
+
+fn expires_at(expiration: Option<Duration>) -> Option<i64> {
+    expiration.map(|d| now_millis() + d.as_millis() as i64)
+}
+
+fn now_millis() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as i64)
+        .unwrap_or_default()
+}
+
+/// Extension for the mime types the app produces.
+fn extension_for(mime_type: &str) -> &'static str {
+    match mime_type {
+        "image/jpeg" => "jpg",
+        "image/webp" => "webp",
+        "image/gif" => "gif",
+        _ => "png",
+    }
+}
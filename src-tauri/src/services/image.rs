@@ -1,38 +0,0 @@
-/*
- * @license
- * Copyright 2025 a7mddra
- * SPDX-License-Identifier: Apache-2.0
- */
-
-use crate::state::AppState;
-use base64::{engine::general_purpose, Engine as _};
-use std::fs::File;
-use std::io::Read;
-use tauri::State;
-
-pub fn process_and_store_image(path: &str, state: &State<AppState>) -> Result<String, String> {
-    let mut file = File::open(path).map_err(|e| format!("Failed to open file: {}", e))?;
-    let mut buffer = Vec::new();
-    file.read_to_end(&mut buffer)
-        .map_err(|e| format!("Failed to read file: {}", e))?;
-
-    process_bytes_internal(buffer, state)
-}
-
-pub fn process_bytes_internal(buffer: Vec<u8>, state: &State<AppState>) -> Result<String, String> {
-    if buffer.is_empty() {
-        return Err("Empty image buffer".to_string());
-    }
-
-    let mime_type = image::guess_format(&buffer)
-        .map(|f| f.to_mime_type())
-        .unwrap_or("image/jpeg");
-
-    let base64_image = general_purpose::STANDARD.encode(&buffer);
-    let data_url = format!("data:{};base64,{}", mime_type, base64_image);
-
-    let mut image_lock = state.image_data.lock();
-    *image_lock = Some(data_url.clone());
-
-    Ok(data_url)
-}
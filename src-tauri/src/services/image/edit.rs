@@ -0,0 +1,660 @@
+/*
+ * @license
+ * Copyright 2025 a7mddra
+ * SPDX-License-Identifier: Apache-2.0
+ */
+
+//! Non-destructive edits on captured images. An edit list is plain data, so
+//! it can be stored next to the image it produced and replayed later.
+
+use ab_glyph::{Font, PxScale, ScaleFont};
+use image::{imageops, ImageFormat, Rgba, RgbaImage};
+use serde::{Deserialize, Serialize};
+use std::io::Cursor;
+
+use crate::services::fonts;
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Point {
+    pub x: i32,
+    pub y: i32,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Rect {
+    pub x: u32,
+    pub y: u32,
+    pub width: u32,
+    pub height: u32,
+}
+
+/// An RGBA colour, written as `#rrggbb` or `#rrggbbaa`.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(try_from = "String", into = "String")]
+pub struct Color(pub [u8; 4]);
+
+impl Color {
+    const BLACK: Color = Color([0, 0, 0, 255]);
+    const RED: Color = Color([229, 57, 53, 255]);
+    const HIGHLIGHT: Color = Color([255, 235, 59, 102]);
+
+    fn opaque(self) -> Self {
+        let [r, g, b, _] = self.0;
+        Color([r, g, b, 255])
+    }
+}
+
+impl TryFrom<String> for Color {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        let hex = value.strip_prefix('#').unwrap_or(&value);
+        let channel = |i: usize| {
+            hex.get(i..i + 2)
+                .and_then(|c| u8::from_str_radix(c, 16).ok())
+                .ok_or_else(|| format!("Invalid colour: {}", value))
+        };
+        match hex.len() {
+            6 => Ok(Color([channel(0)?, channel(2)?, channel(4)?, 255])),
+            8 => Ok(Color([channel(0)?, channel(2)?, channel(4)?, channel(6)?])),
+            _ => Err(format!("Invalid colour: {}", value)),
+        }
+    }
+}
+
+impl From<Color> for String {
+    fn from(color: Color) -> Self {
+        let [r, g, b, a] = color.0;
+        if a == 255 {
+            format!("#{:02x}{:02x}{:02x}", r, g, b)
+        } else {
+            format!("#{:02x}{:02x}{:02x}{:02x}", r, g, b, a)
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+#[serde(tag = "kind", rename_all = "camelCase")]
+pub enum RedactMethod {
+    /// Solid fill. The colour is always drawn fully opaque.
+    Fill {
+        #[serde(default = "black")]
+        color: Color,
+    },
+    #[serde(rename_all = "camelCase")]
+    Pixelate {
+        #[serde(default = "default_block_size")]
+        block_size: u32,
+    },
+    Blur {
+        #[serde(default = "default_blur_radius")]
+        radius: f32,
+    },
+}
+
+impl Default for RedactMethod {
+    fn default() -> Self {
+        RedactMethod::Fill { color: black() }
+    }
+}
+
+/// One edit. Coordinates are in pixels of the image as it is when the edit
+/// runs, so edits after a crop are relative to the cropped image.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum Edit {
+    Crop {
+        rect: Rect,
+    },
+    Redact {
+        rect: Rect,
+        #[serde(default)]
+        method: RedactMethod,
+    },
+    Arrow {
+        from: Point,
+        to: Point,
+        #[serde(default = "red")]
+        color: Color,
+        #[serde(default = "default_stroke")]
+        width: u32,
+    },
+    Box {
+        rect: Rect,
+        #[serde(default = "red")]
+        color: Color,
+        #[serde(default = "default_stroke")]
+        width: u32,
+    },
+    Label {
+        position: Point,
+        text: String,
+        #[serde(default = "red")]
+        color: Color,
+        #[serde(default = "default_font_size")]
+        size: f32,
+        #[serde(default)]
+        background: Option<Color>,
+    },
+    Highlight {
+        rect: Rect,
+        #[serde(default = "highlight")]
+        color: Color,
+    },
+}
+
+fn black() -> Color {
+    Color::BLACK
+}
+
+fn red() -> Color {
+    Color::RED
+}
+
+fn highlight() -> Color {
+    Color::HIGHLIGHT
+}
+
+fn default_block_size() -> u32 {
+    12
+}
+
+fn default_blur_radius() -> f32 {
+    12.0
+}
+
+fn default_stroke() -> u32 {
+    4
+}
+
+fn default_font_size() -> f32 {
+    24.0
+}
+
+/// Applies `edits` in order to an encoded image and returns the result as
+/// PNG.
+pub fn apply(bytes: &[u8], edits: &[Edit]) -> Result<Vec<u8>, String> {
+    let mut img = image::load_from_memory(bytes)
+        .map_err(|e| format!("Failed to decode image: {}", e))?
+        .to_rgba8();
+
+    for edit in edits {
+        img = apply_one(img, edit)?;
+    }
+
+    let mut png = Vec::new();
+    img.write_to(&mut Cursor::new(&mut png), ImageFormat::Png)
+        .map_err(|e| format!("Failed to encode image: {}", e))?;
+    Ok(png)
+}
+
+fn apply_one(mut img: RgbaImage, edit: &Edit) -> Result<RgbaImage, String> {
+    match edit {
+        Edit::Crop { rect } => {
+            let (x0, y0, x1, y1) = clip(&img, rect).ok_or("Crop is outside the image")?;
+            return Ok(imageops::crop_imm(&img, x0, y0, x1 - x0, y1 - y0).to_image());
+        }
+        Edit::Redact { rect, method } => redact(&mut img, rect, method),
+        Edit::Arrow {
+            from,
+            to,
+            color,
+            width,
+        } => arrow(&mut img, *from, *to, *color, *width),
+        Edit::Box { rect, color, width } => {
+            let w = (*width).max(1);
+            let Rect {
+                x,
+                y,
+                width: rw,
+                height: rh,
+            } = *rect;
+            let edges = [
+                Rect {
+                    x,
+                    y,
+                    width: rw,
+                    height: w.min(rh),
+                },
+                Rect {
+                    x,
+                    y: y.saturating_add(rh).saturating_sub(w),
+                    width: rw,
+                    height: w.min(rh),
+                },
+                Rect {
+                    x,
+                    y,
+                    width: w.min(rw),
+                    height: rh,
+                },
+                Rect {
+                    x: x.saturating_add(rw).saturating_sub(w),
+                    y,
+                    width: w.min(rw),
+                    height: rh,
+                },
+            ];
+            // Corners belong to two edges; paint each pixel once so
+            // translucent colours stay even.
+            let mut painted = std::collections::HashSet::new();
+            for edge in edges {
+                if let Some((x0, y0, x1, y1)) = clip(&img, &edge) {
+                    for py in y0..y1 {
+                        for px in x0..x1 {
+                            if painted.insert((px, py)) {
+                                blend(img.get_pixel_mut(px, py), *color);
+                            }
+                        }
+                    }
+                }
+            }
+        }
+        Edit::Label {
+            position,
+            text,
+            color,
+            size,
+            background,
+        } => label(&mut img, *position, text, *color, *size, *background)?,
+        Edit::Highlight { rect, color } => fill(&mut img, rect, *color),
+    }
+    Ok(img)
+}
+
+/// Clips a rectangle to the image, as `(x0, y0, x1, y1)` with exclusive
+/// ends. `None` if nothing is left.
+fn clip(img: &RgbaImage, rect: &Rect) -> Option<(u32, u32, u32, u32)> {
+    let x1 = rect.x.saturating_add(rect.width).min(img.width());
+    let y1 = rect.y.saturating_add(rect.height).min(img.height());
+    (rect.x < x1 && rect.y < y1).then_some((rect.x, rect.y, x1, y1))
+}
+
+fn blend(pixel: &mut Rgba<u8>, color: Color) {
+    let alpha = color.0[3] as u32;
+    for i in 0..3 {
+        pixel.0[i] = ((color.0[i] as u32 * alpha + pixel.0[i] as u32 * (255 - alpha)) / 255) as u8;
+    }
+    pixel.0[3] = pixel.0[3].max(color.0[3]);
+}
+
+fn fill(img: &mut RgbaImage, rect: &Rect, color: Color) {
+    let Some((x0, y0, x1, y1)) = clip(img, rect) else {
+        return;
+    };
+    for y in y0..y1 {
+        for x in x0..x1 {
+            blend(img.get_pixel_mut(x, y), color);
+        }
+    }
+}
+
+fn redact(img: &mut RgbaImage, rect: &Rect, method: &RedactMethod) {
+    let Some((x0, y0, x1, y1)) = clip(img, rect) else {
+        return;
+    };
+    match *method {
+        RedactMethod::Fill { color } => fill(img, rect, color.opaque()),
+        RedactMethod::Pixelate { block_size } => {
+            // A block larger than the region is the whole region; capping it
+            // also keeps `bx + block` from overflowing.
+            let block = block_size.clamp(2, (x1 - x0).max(y1 - y0).max(2));
+            for by in (y0..y1).step_by(block as usize) {
+                for bx in (x0..x1).step_by(block as usize) {
+                    let (ex, ey) = ((bx + block).min(x1), (by + block).min(y1));
+                    let mut sum = [0u64; 4];
+                    for y in by..ey {
+                        for x in bx..ex {
+                            for (total, channel) in sum.iter_mut().zip(img.get_pixel(x, y).0) {
+                                *total += channel as u64;
+                            }
+                        }
+                    }
+                    let count = (ex - bx) as u64 * (ey - by) as u64;
+                    let average = Rgba(sum.map(|total| (total / count) as u8));
+                    for y in by..ey {
+                        for x in bx..ex {
+                            img.put_pixel(x, y, average);
+                        }
+                    }
+                }
+            }
+        }
+        RedactMethod::Blur { radius } => {
+            let region = imageops::crop_imm(img, x0, y0, x1 - x0, y1 - y0).to_image();
+            let blurred = imageops::blur(&region, radius.max(1.0));
+            imageops::replace(img, &blurred, x0 as i64, y0 as i64);
+        }
+    }
+}
+
+/// A straight arrow from `from` to `to` with a filled head at `to`.
+fn arrow(img: &mut RgbaImage, from: Point, to: Point, color: Color, width: u32) {
+    let (fx, fy, tx, ty) = (from.x as f32, from.y as f32, to.x as f32, to.y as f32);
+    let (dx, dy) = (tx - fx, ty - fy);
+    let length = (dx * dx + dy * dy).sqrt();
+    if length < 1.0 {
+        return;
+    }
+    let (ux, uy) = (dx / length, dy / length);
+
+    let half = width.max(1) as f32 / 2.0;
+    let head_length = (width as f32 * 4.0).max(12.0).min(length);
+    let head_half = head_length * 0.5;
+    // The shaft stops inside the head so its square end never pokes out.
+    let (bx, by) = (tx - ux * head_length, ty - uy * head_length);
+    let shaft_end = (tx - ux * head_length * 0.5, ty - uy * head_length * 0.5);
+    let head = [
+        (tx, ty),
+        (bx - uy * head_half, by + ux * head_half),
+        (bx + uy * head_half, by - ux * head_half),
+    ];
+
+    let min_x = fx.min(tx) - head_half - half;
+    let max_x = fx.max(tx) + head_half + half;
+    let min_y = fy.min(ty) - head_half - half;
+    let max_y = fy.max(ty) + head_half + half;
+
+    let (w, h) = (img.width() as f32, img.height() as f32);
+    let (x0, x1) = (min_x.max(0.0) as u32, max_x.min(w - 1.0).max(0.0) as u32);
+    let (y0, y1) = (min_y.max(0.0) as u32, max_y.min(h - 1.0).max(0.0) as u32);
+    if min_x > w - 1.0 || min_y > h - 1.0 || max_x < 0.0 || max_y < 0.0 {
+        return;
+    }
+
+    for y in y0..=y1 {
+        for x in x0..=x1 {
+            let p = (x as f32 + 0.5, y as f32 + 0.5);
+            if segment_distance(p, (fx, fy), shaft_end) <= half || in_triangle(p, head) {
+                blend(img.get_pixel_mut(x, y), color);
+            }
+        }
+    }
+}
+
+fn segment_distance(p: (f32, f32), a: (f32, f32), b: (f32, f32)) -> f32 {
+    let (abx, aby) = (b.0 - a.0, b.1 - a.1);
+    let len2 = abx * abx + aby * aby;
+    let t = if len2 == 0.0 {
+        0.0
+    } else {
+        (((p.0 - a.0) * abx + (p.1 - a.1) * aby) / len2).clamp(0.0, 1.0)
+    };
+    let (cx, cy) = (a.0 + t * abx, a.1 + t * aby);
+    ((p.0 - cx).powi(2) + (p.1 - cy).powi(2)).sqrt()
+}
+
+fn in_triangle(p: (f32, f32), [a, b, c]: [(f32, f32); 3]) -> bool {
+    let side = |u: (f32, f32), v: (f32, f32)| (v.0 - u.0) * (p.1 - u.1) - (v.1 - u.1) * (p.0 - u.0);
+    let (d1, d2, d3) = (side(a, b), side(b, c), side(c, a));
+    let negative = d1 < 0.0 || d2 < 0.0 || d3 < 0.0;
+    let positive = d1 > 0.0 || d2 > 0.0 || d3 > 0.0;
+    !(negative && positive)
+}
+
+/// Draws text with its top-left corner at `position`.
+fn label(
+    img: &mut RgbaImage,
+    position: Point,
+    text: &str,
+    color: Color,
+    size: f32,
+    background: Option<Color>,
+) -> Result<(), String> {
+    let font = fonts::unicode_font()?;
+    let scaled = font.as_scaled(PxScale::from(size.clamp(4.0, 512.0)));
+    let line_height = scaled.height() + scaled.line_gap();
+
+    if let Some(background) = background {
+        let width = text
+            .lines()
+            .map(|line| {
+                line.chars()
+                    .map(|c| scaled.h_advance(font.glyph_id(c)))
+                    .sum::<f32>()
+            })
+            .fold(0.0, f32::max);
+        let lines = text.lines().count().max(1) as f32;
+        let pad = (scaled.height() * 0.25).round() as i32;
+        let (x, y) = (position.x - pad, position.y - pad);
+        fill(
+            img,
+            &Rect {
+                x: x.max(0) as u32,
+                y: y.max(0) as u32,
+                width: (width.ceil() as i32 + 2 * pad + x.min(0)).max(0) as u32,
+                height: ((line_height * lines).ceil() as i32 + 2 * pad + y.min(0)).max(0) as u32,
+            },
+            background,
+        );
+    }
+
+    for (row, line) in text.lines().enumerate() {
+        let baseline = position.y as f32 + scaled.ascent() + row as f32 * line_height;
+        let mut caret = position.x as f32;
+        let mut previous = None;
+        for c in line.chars() {
+            let id = font.glyph_id(c);
+            if let Some(previous) = previous {
+                caret += scaled.kern(previous, id);
+            }
+            let glyph =
+                id.with_scale_and_position(scaled.scale(), ab_glyph::point(caret, baseline));
+            caret += scaled.h_advance(id);
+            previous = Some(id);
+
+            let Some(outline) = font.outline_glyph(glyph) else {
+                continue;
+            };
+            let bounds = outline.px_bounds();
+            outline.draw(|gx, gy, coverage| {
+                let x = bounds.min.x as i32 + gx as i32;
+                let y = bounds.min.y as i32 + gy as i32;
+                if x < 0 || y < 0 || x >= img.width() as i32 || y >= img.height() as i32 {
+                    return;
+                }
+                let alpha = (color.0[3] as f32 * coverage.clamp(0.0, 1.0)).round() as u8;
+                let [r, g, b, _] = color.0;
+                blend(
+                    img.get_pixel_mut(x as u32, y as u32),
+                    Color([r, g, b, alpha]),
+                );
+            });
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn canvas() -> Vec<u8> {
+        let img = RgbaImage::from_fn(64, 48, |x, y| {
+            Rgba([(x * 4) as u8, (y * 5) as u8, 128, 255])
+        });
+        let mut png = Vec::new();
+        img.write_to(&mut Cursor::new(&mut png), ImageFormat::Png)
+            .unwrap();
+        png
+    }
+
+    fn decode(png: &[u8]) -> RgbaImage {
+        image::load_from_memory(png).unwrap().to_rgba8()
+    }
+
+    #[test]
+    fn edits_round_trip_through_json() {
+        let json = r##"[
+            {"type": "crop", "rect": {"x": 4, "y": 4, "width": 40, "height": 30}},
+            {"type": "redact", "rect": {"x": 0, "y": 0, "width": 10, "height": 10},
+             "method": {"kind": "pixelate", "blockSize": 5}},
+            {"type": "arrow", "from": {"x": 2, "y": 20}, "to": {"x": 30, "y": 20}},
+            {"type": "highlight", "rect": {"x": 0, "y": 25, "width": 40, "height": 5},
+             "color": "#00ff0080"}
+        ]"##;
+        let edits: Vec<Edit> = serde_json::from_str(json).unwrap();
+        assert_eq!(
+            edits[2],
+            Edit::Arrow {
+                from: Point { x: 2, y: 20 },
+                to: Point { x: 30, y: 20 },
+                color: Color::RED,
+                width: 4
+            }
+        );
+
+        let reparsed: Vec<Edit> =
+            serde_json::from_str(&serde_json::to_string(&edits).unwrap()).unwrap();
+        assert_eq!(reparsed, edits);
+        assert_eq!(
+            apply(&canvas(), &edits).unwrap(),
+            apply(&canvas(), &reparsed).unwrap()
+        );
+    }
+
+    #[test]
+    fn crops_then_edits_in_cropped_coordinates() {
+        let edits = vec![
+            Edit::Crop {
+                rect: Rect {
+                    x: 10,
+                    y: 10,
+                    width: 20,
+                    height: 100,
+                },
+            },
+            Edit::Redact {
+                rect: Rect {
+                    x: 0,
+                    y: 0,
+                    width: 5,
+                    height: 5,
+                },
+                method: RedactMethod::default(),
+            },
+        ];
+        let img = decode(&apply(&canvas(), &edits).unwrap());
+        assert_eq!(img.dimensions(), (20, 38));
+        assert_eq!(*img.get_pixel(0, 0), Rgba([0, 0, 0, 255]));
+        assert_eq!(*img.get_pixel(5, 5), Rgba([60, 75, 128, 255]));
+    }
+
+    #[test]
+    fn fill_redaction_ignores_transparency() {
+        let edits = vec![Edit::Redact {
+            rect: Rect {
+                x: 0,
+                y: 0,
+                width: 8,
+                height: 8,
+            },
+            method: RedactMethod::Fill {
+                color: Color([255, 255, 255, 10]),
+            },
+        }];
+        let img = decode(&apply(&canvas(), &edits).unwrap());
+        assert_eq!(*img.get_pixel(7, 7), Rgba([255, 255, 255, 255]));
+    }
+
+    #[test]
+    fn pixelate_and_blur_change_only_the_region() {
+        let original = decode(&canvas());
+        for method in [
+            RedactMethod::Pixelate { block_size: 8 },
+            RedactMethod::Blur { radius: 4.0 },
+        ] {
+            let edits = vec![Edit::Redact {
+                rect: Rect {
+                    x: 8,
+                    y: 8,
+                    width: 16,
+                    height: 16,
+                },
+                method,
+            }];
+            let img = decode(&apply(&canvas(), &edits).unwrap());
+            assert_ne!(img.get_pixel(9, 9), original.get_pixel(9, 9));
+            assert_eq!(img.get_pixel(30, 30), original.get_pixel(30, 30));
+        }
+    }
+
+    #[test]
+    fn pixelates_with_a_block_larger_than_the_image() {
+        let edits = vec![Edit::Redact {
+            rect: Rect {
+                x: 60,
+                y: 0,
+                width: u32::MAX,
+                height: 2,
+            },
+            method: RedactMethod::Pixelate {
+                block_size: u32::MAX,
+            },
+        }];
+        let img = decode(&apply(&canvas(), &edits).unwrap());
+        let average = *img.get_pixel(60, 0);
+        assert_eq!(*img.get_pixel(63, 1), average);
+        assert_eq!(average, Rgba([246, 2, 128, 255]));
+        assert_eq!(*img.get_pixel(59, 0), Rgba([236, 0, 128, 255]));
+    }
+
+    #[test]
+    fn draws_boxes_and_arrows() {
+        let edits = vec![
+            Edit::Box {
+                rect: Rect {
+                    x: 2,
+                    y: 2,
+                    width: 20,
+                    height: 20,
+                },
+                color: Color([0, 255, 0, 255]),
+                width: 2,
+            },
+            Edit::Arrow {
+                from: Point { x: 30, y: 40 },
+                to: Point { x: 60, y: 40 },
+                color: Color([0, 0, 255, 255]),
+                width: 2,
+            },
+        ];
+        let img = decode(&apply(&canvas(), &edits).unwrap());
+        assert_eq!(*img.get_pixel(3, 10), Rgba([0, 255, 0, 255]));
+        assert_ne!(*img.get_pixel(10, 10), Rgba([0, 255, 0, 255]));
+        assert_eq!(*img.get_pixel(40, 40), Rgba([0, 0, 255, 255]));
+        assert_eq!(*img.get_pixel(58, 40), Rgba([0, 0, 255, 255]));
+        assert_ne!(*img.get_pixel(40, 30), Rgba([0, 0, 255, 255]));
+    }
+
+    #[test]
+    fn rejects_crops_outside_the_image() {
+        let edits = vec![Edit::Crop {
+            rect: Rect {
+                x: 100,
+                y: 0,
+                width: 10,
+                height: 10,
+            },
+        }];
+        assert!(apply(&canvas(), &edits).is_err());
+        assert!(Color::try_from("#12345".to_string()).is_err());
+    }
+
+    #[test]
+    fn draws_labels_when_a_font_is_available() {
+        if fonts::unicode_font().is_err() {
+            return;
+        }
+        let edits = vec![Edit::Label {
+            position: Point { x: 4, y: 4 },
+            text: "Hi".into(),
+            color: Color([255, 255, 255, 255]),
+            size: 20.0,
+            background: Some(Color::BLACK),
+        }];
+        let img = decode(&apply(&canvas(), &edits).unwrap());
+        assert_eq!(*img.get_pixel(2, 2), Rgba([0, 0, 0, 255]));
+        assert!((4..30).any(|x| (4..24).any(|y| img.get_pixel(x, y).0[..3] == [255, 255, 255])));
+    }
+}
@@ -0,0 +1,150 @@
+/*
+ * @license
+ * Copyright 2025 a7mddra
+ * SPDX-License-Identifier: Apache-2.0
+ */
+
+pub mod edit;
+pub mod preprocess;
+
+use crate::services::conversations;
+use crate::services::image_store;
+use crate::state::AppState;
+use base64::{engine::general_purpose, Engine as _};
+use edit::Edit;
+use image::DynamicImage;
+use preprocess::PreprocessReport;
+use serde::Serialize;
+use std::fs::File;
+use std::io::Read;
+use tauri::{AppHandle, Emitter, Manager, State};
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProcessedImage {
+    pub data_url: String,
+    pub mime_type: String,
+    pub preprocessing: PreprocessReport,
+}
+
+/// An edited image that has been stored and made current.
+pub struct EditedVersion {
+    pub id: String,
+    pub bytes: Vec<u8>,
+    pub data_url: String,
+}
+
+pub fn process_and_store_image(
+    path: &str,
+    state: &State<AppState>,
+) -> Result<ProcessedImage, String> {
+    let mut file = File::open(path).map_err(|e| format!("Failed to open file: {}", e))?;
+    let mut buffer = Vec::new();
+    file.read_to_end(&mut buffer)
+        .map_err(|e| format!("Failed to read file: {}", e))?;
+
+    process_bytes_internal(buffer, state)
+}
+
+/// Loads an image file as the current image and tells the UI about it. This
+/// is the path used for an image given on the command line.
+pub fn load_image_path(app: &AppHandle, path: &str) -> Result<(), String> {
+    let state = app.state::<AppState>();
+    process_and_store_image(path, &state)?;
+    let _ = app.emit("image-path", path);
+    Ok(())
+}
+
+/// Runs the preprocessing pipeline on new image bytes and makes the result
+/// the current image.
+pub fn process_bytes_internal(
+    buffer: Vec<u8>,
+    state: &State<AppState>,
+) -> Result<ProcessedImage, String> {
+    if buffer.is_empty() {
+        return Err("Empty image buffer".to_string());
+    }
+
+    let options = state.settings.lock().image_preprocessing.clone();
+    let (buffer, report) = preprocess::preprocess(buffer, &options)?;
+    if !report.steps.is_empty() {
+        println!("Preprocessed image: {}", report.summary());
+    }
+
+    // A new image starts a new edit history.
+    state.image_history.lock().clear();
+    let (data_url, mime_type) = set_current_image(&buffer, state);
+
+    Ok(ProcessedImage {
+        data_url,
+        mime_type: mime_type.to_string(),
+        preprocessing: report,
+    })
+}
+
+/// Makes `bytes` the current image as-is, returning its data URL and mime
+/// type.
+pub fn set_current_image(bytes: &[u8], state: &AppState) -> (String, &'static str) {
+    let mime_type = image::guess_format(bytes)
+        .map(|f| f.to_mime_type())
+        .unwrap_or("image/jpeg");
+
+    let base64_image = general_purpose::STANDARD.encode(bytes);
+    let data_url = format!("data:{};base64,{}", mime_type, base64_image);
+
+    let mut image_lock = state.image_data.lock();
+    *image_lock = Some(data_url.clone());
+
+    (data_url, mime_type)
+}
+
+/// Applies `edits` to `source`, records the result as a new version of
+/// `source_id` and makes it the current image. The source goes on the undo
+/// history.
+pub fn save_edited_version(
+    app: &AppHandle,
+    source_id: &str,
+    source: &[u8],
+    edits: &[Edit],
+) -> Result<EditedVersion, String> {
+    let bytes = edit::apply(source, edits)?;
+    let id = image_store::store(app, &bytes)?;
+    conversations::with_store(app, |store| {
+        store.record_image_version(&id, source_id, edits)
+    })?;
+
+    let state = app.state::<AppState>();
+    let (data_url, _) = set_current_image(&bytes, &state);
+    state.image_history.lock().push(source_id.to_string());
+
+    Ok(EditedVersion {
+        id,
+        bytes,
+        data_url,
+    })
+}
+
+/// The current image as its mime type and base64 payload.
+pub fn current_image_base64(state: &State<AppState>) -> Result<(String, String), String> {
+    let data_url = state.image_data.lock().clone().ok_or("No image loaded")?;
+
+    let (header, encoded) = data_url.split_once(',').ok_or("Malformed image data URL")?;
+    let mime_type = header
+        .strip_prefix("data:")
+        .and_then(|h| h.strip_suffix(";base64"))
+        .ok_or("Malformed image data URL")?;
+
+    Ok((mime_type.to_string(), encoded.to_string()))
+}
+
+pub fn current_image_bytes(state: &State<AppState>) -> Result<Vec<u8>, String> {
+    let (_, encoded) = current_image_base64(state)?;
+    general_purpose::STANDARD
+        .decode(encoded)
+        .map_err(|e| format!("Failed to decode image: {}", e))
+}
+
+pub fn current_image(state: &State<AppState>) -> Result<DynamicImage, String> {
+    let bytes = current_image_bytes(state)?;
+    image::load_from_memory(&bytes).map_err(|e| format!("Failed to decode image: {}", e))
+}
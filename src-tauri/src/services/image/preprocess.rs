@@ -0,0 +1,340 @@
+/*
+ * @license
+ * Copyright 2025 a7mddra
+ * SPDX-License-Identifier: Apache-2.0
+ */
+
+use image::codecs::jpeg::JpegEncoder;
+use image::imageops::FilterType;
+use image::{DynamicImage, GenericImageView, ImageFormat, Rgba};
+use serde::Serialize;
+use std::io::Cursor;
+
+pub use spatialshot_core::config::image::{OutputFormat, PreprocessOptions};
+
+/// How far a pixel may stray from the border colour, per channel, and still
+/// count as border. Absorbs compression noise around flat backgrounds.
+const TRIM_TOLERANCE: u8 = 8;
+
+/// One step the pipeline actually applied.
+#[derive(Serialize, Clone, Debug, PartialEq)]
+#[serde(tag = "step", rename_all = "camelCase")]
+pub enum PreprocessStep {
+    #[serde(rename_all = "camelCase")]
+    Trimmed {
+        left: u32,
+        top: u32,
+        width: u32,
+        height: u32,
+    },
+    #[serde(rename_all = "camelCase")]
+    Downscaled {
+        from_width: u32,
+        from_height: u32,
+        width: u32,
+        height: u32,
+    },
+    Grayscale,
+    #[serde(rename_all = "camelCase")]
+    Encoded {
+        format: &'static str,
+        quality: Option<u8>,
+    },
+}
+
+#[derive(Serialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct PreprocessReport {
+    pub steps: Vec<PreprocessStep>,
+    pub width: u32,
+    pub height: u32,
+    pub original_bytes: usize,
+    pub output_bytes: usize,
+    /// Estimated image tokens before and after, using Gemini's tiling rule.
+    pub original_tokens: u32,
+    pub output_tokens: u32,
+}
+
+impl PreprocessReport {
+    pub fn summary(&self) -> String {
+        format!(
+            "{} -> {} bytes, ~{} -> ~{} tokens",
+            self.original_bytes, self.output_bytes, self.original_tokens, self.output_tokens
+        )
+    }
+}
+
+/// Runs the pipeline: trim, downscale, grayscale, then encode. When no step
+/// applies, the input bytes are returned untouched.
+pub fn preprocess(
+    bytes: Vec<u8>,
+    options: &PreprocessOptions,
+) -> Result<(Vec<u8>, PreprocessReport), String> {
+    let source_format =
+        image::guess_format(&bytes).map_err(|e| format!("Unsupported image: {}", e))?;
+    let mut img =
+        image::load_from_memory(&bytes).map_err(|e| format!("Failed to decode image: {}", e))?;
+    let (original_width, original_height) = img.dimensions();
+    let mut steps = Vec::new();
+
+    if options.trim_borders {
+        if let Some((left, top, width, height)) = content_bounds(&img) {
+            if (width, height) != img.dimensions() {
+                img = img.crop_imm(left, top, width, height);
+                steps.push(PreprocessStep::Trimmed {
+                    left,
+                    top,
+                    width,
+                    height,
+                });
+            }
+        }
+    }
+
+    if let Some(max_edge) = options.max_edge.filter(|edge| *edge > 0) {
+        let (width, height) = img.dimensions();
+        if width.max(height) > max_edge {
+            img = img.resize(max_edge, max_edge, FilterType::Lanczos3);
+            steps.push(PreprocessStep::Downscaled {
+                from_width: width,
+                from_height: height,
+                width: img.width(),
+                height: img.height(),
+            });
+        }
+    }
+
+    if options.grayscale && !is_grayscale(&img) {
+        img = DynamicImage::ImageLuma8(img.to_luma8());
+        steps.push(PreprocessStep::Grayscale);
+    }
+
+    let target = match options.format {
+        OutputFormat::Original => match source_format {
+            ImageFormat::Png | ImageFormat::Jpeg | ImageFormat::WebP => source_format,
+            _ => ImageFormat::Png,
+        },
+        OutputFormat::Jpeg => ImageFormat::Jpeg,
+        OutputFormat::Webp => ImageFormat::WebP,
+    };
+
+    let output = if steps.is_empty() && target == source_format {
+        bytes.clone()
+    } else {
+        let quality = options.output_quality().clamp(1, 100);
+        let encoded = encode(&img, target, quality)?;
+        steps.push(PreprocessStep::Encoded {
+            format: target.extensions_str()[0],
+            quality: matches!(target, ImageFormat::Jpeg | ImageFormat::WebP).then_some(quality),
+        });
+        encoded
+    };
+
+    let report = PreprocessReport {
+        steps,
+        width: img.width(),
+        height: img.height(),
+        original_bytes: bytes.len(),
+        output_bytes: output.len(),
+        original_tokens: estimate_tokens(original_width, original_height),
+        output_tokens: estimate_tokens(img.width(), img.height()),
+    };
+    Ok((output, report))
+}
+
+fn encode(img: &DynamicImage, format: ImageFormat, quality: u8) -> Result<Vec<u8>, String> {
+    let mut out = Vec::new();
+    let result = match format {
+        ImageFormat::Jpeg => {
+            // JPEG has no alpha channel.
+            let flat = match img {
+                DynamicImage::ImageLuma8(_) => img.clone(),
+                _ => DynamicImage::ImageRgb8(img.to_rgb8()),
+            };
+            flat.write_with_encoder(JpegEncoder::new_with_quality(&mut out, quality))
+        }
+        // The `image` crate only writes lossless WebP, which is often larger
+        // than the PNG it replaces, so lossy WebP goes through libwebp.
+        ImageFormat::WebP => {
+            let (width, height) = img.dimensions();
+            let encoded = if img.color().has_alpha() {
+                let rgba = img.to_rgba8();
+                webp::Encoder::from_rgba(&rgba, width, height).encode_simple(false, quality as f32)
+            } else {
+                let rgb = img.to_rgb8();
+                webp::Encoder::from_rgb(&rgb, width, height).encode_simple(false, quality as f32)
+            };
+            return encoded
+                .map(|memory| memory.to_vec())
+                .map_err(|e| format!("Failed to encode image: {:?}", e));
+        }
+        _ => img.write_to(&mut Cursor::new(&mut out), format),
+    };
+    result.map_err(|e| format!("Failed to encode image: {}", e))?;
+    Ok(out)
+}
+
+/// The bounding box of everything that differs from the top-left pixel, or
+/// `None` for a uniform image.
+fn content_bounds(img: &DynamicImage) -> Option<(u32, u32, u32, u32)> {
+    let rgba = img.to_rgba8();
+    let (width, height) = rgba.dimensions();
+    let border = *rgba.get_pixel(0, 0);
+    let is_border = |x: u32, y: u32| close_to(rgba.get_pixel(x, y), &border);
+
+    let top = (0..height).find(|&y| !(0..width).all(|x| is_border(x, y)))?;
+    let bottom = (0..height)
+        .rev()
+        .find(|&y| !(0..width).all(|x| is_border(x, y)))?;
+    let left = (0..width).find(|&x| !(top..=bottom).all(|y| is_border(x, y)))?;
+    let right = (0..width)
+        .rev()
+        .find(|&x| !(top..=bottom).all(|y| is_border(x, y)))?;
+
+    Some((left, top, right - left + 1, bottom - top + 1))
+}
+
+fn close_to(a: &Rgba<u8>, b: &Rgba<u8>) -> bool {
+    a.0.iter()
+        .zip(b.0.iter())
+        .all(|(x, y)| x.abs_diff(*y) <= TRIM_TOLERANCE)
+}
+
+fn is_grayscale(img: &DynamicImage) -> bool {
+    matches!(
+        img,
+        DynamicImage::ImageLuma8(_)
+            | DynamicImage::ImageLuma16(_)
+            | DynamicImage::ImageLumaA8(_)
+            | DynamicImage::ImageLumaA16(_)
+    )
+}
+
+/// Gemini bills an image of at most 384px on both sides as 258 tokens and
+/// anything larger as 258 tokens per 768x768 tile.
+fn estimate_tokens(width: u32, height: u32) -> u32 {
+    if width <= 384 && height <= 384 {
+        return 258;
+    }
+    258 * width.div_ceil(768) * height.div_ceil(768)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::{Rgb, RgbImage};
+
+    fn png(img: RgbImage) -> Vec<u8> {
+        let mut out = Vec::new();
+        img.write_to(&mut Cursor::new(&mut out), ImageFormat::Png)
+            .unwrap();
+        out
+    }
+
+    fn framed(width: u32, height: u32) -> Vec<u8> {
+        let mut img = RgbImage::from_pixel(width, height, Rgb([255, 255, 255]));
+        for x in 10..width - 20 {
+            for y in 5..height - 5 {
+                img.put_pixel(x, y, Rgb([20, 120, 200]));
+            }
+        }
+        png(img)
+    }
+
+    #[test]
+    fn leaves_small_images_untouched() {
+        let bytes = framed(100, 50);
+        let (output, report) = preprocess(bytes.clone(), &PreprocessOptions::default()).unwrap();
+        assert_eq!(output, bytes);
+        assert!(report.steps.is_empty());
+        assert_eq!((report.width, report.height), (100, 50));
+    }
+
+    #[test]
+    fn runs_every_step_in_order() {
+        let options = PreprocessOptions {
+            max_edge: Some(40),
+            format: OutputFormat::Jpeg,
+            quality: Some(70),
+            grayscale: true,
+            trim_borders: true,
+        };
+        let (output, report) = preprocess(framed(200, 60), &options).unwrap();
+
+        assert_eq!(
+            report.steps,
+            vec![
+                PreprocessStep::Trimmed {
+                    left: 10,
+                    top: 5,
+                    width: 170,
+                    height: 50
+                },
+                PreprocessStep::Downscaled {
+                    from_width: 170,
+                    from_height: 50,
+                    width: 40,
+                    height: 12
+                },
+                PreprocessStep::Grayscale,
+                PreprocessStep::Encoded {
+                    format: "jpg",
+                    quality: Some(70)
+                },
+            ]
+        );
+        assert_eq!(image::guess_format(&output).unwrap(), ImageFormat::Jpeg);
+        let decoded = image::load_from_memory(&output).unwrap();
+        assert_eq!(decoded.dimensions(), (40, 12));
+    }
+
+    #[test]
+    fn does_not_trim_uniform_images() {
+        let bytes = png(RgbImage::from_pixel(30, 30, Rgb([0, 0, 0])));
+        let options = PreprocessOptions {
+            trim_borders: true,
+            ..Default::default()
+        };
+        let (output, report) = preprocess(bytes.clone(), &options).unwrap();
+        assert_eq!(output, bytes);
+        assert!(report.steps.is_empty());
+    }
+
+    #[test]
+    fn encodes_lossy_webp_at_the_quality() {
+        let bytes = png(RgbImage::from_fn(128, 128, |x, y| {
+            Rgb([(x * 2) as u8, (y * 2) as u8, (x * y % 251) as u8])
+        }));
+        let webp = |quality: Option<u8>| {
+            let options = PreprocessOptions {
+                format: OutputFormat::Webp,
+                quality,
+                ..Default::default()
+            };
+            preprocess(bytes.clone(), &options).unwrap()
+        };
+
+        let (output, report) = webp(None);
+        assert_eq!(image::guess_format(&output).unwrap(), ImageFormat::WebP);
+        assert_eq!(
+            image::load_from_memory(&output).unwrap().dimensions(),
+            (128, 128)
+        );
+        assert_eq!(
+            report.steps,
+            vec![PreprocessStep::Encoded {
+                format: "webp",
+                quality: Some(spatialshot_core::config::image::DEFAULT_QUALITY)
+            }]
+        );
+        assert!(output.len() < bytes.len());
+        assert!(webp(Some(10)).0.len() < output.len());
+    }
+
+    #[test]
+    fn estimates_tokens_by_tile() {
+        assert_eq!(estimate_tokens(300, 200), 258);
+        assert_eq!(estimate_tokens(768, 768), 258);
+        assert_eq!(estimate_tokens(5120, 2880), 258 * 7 * 4);
+    }
+}
@@ -0,0 +1,134 @@
+/*
+ * @license
+ * Copyright 2025 a7mddra
+ * SPDX-License-Identifier: Apache-2.0
+ */
+
+use sha2::{Digest, Sha256};
+use std::fs;
+use std::path::{Path, PathBuf};
+use tauri::AppHandle;
+
+use crate::utils::get_app_data_dir;
+
+/// Content-addressed store for images that outlive the session, such as
+/// screenshots attached to saved conversations. An image's id is the SHA-256
+/// of its encoded bytes, so storing the same file twice is a no-op.
+pub fn images_dir(app: &AppHandle) -> PathBuf {
+    get_app_data_dir(app).join("images")
+}
+
+pub fn store(app: &AppHandle, bytes: &[u8]) -> Result<String, String> {
+    store_in(&images_dir(app), bytes)
+}
+
+pub fn path_of(app: &AppHandle, id: &str) -> Result<PathBuf, String> {
+    find(&images_dir(app), id)
+}
+
+pub fn load(app: &AppHandle, id: &str) -> Result<Vec<u8>, String> {
+    fs::read(path_of(app, id)?).map_err(|e| e.to_string())
+}
+
+/// Deletes image `id`. An image that is already gone is not an error.
+pub fn remove(app: &AppHandle, id: &str) -> Result<(), String> {
+    remove_from(&images_dir(app), id)
+}
+
+/// The id `bytes` are stored under.
+pub fn id_of(bytes: &[u8]) -> String {
+    hex::encode(Sha256::digest(bytes))
+}
+
+fn store_in(dir: &Path, bytes: &[u8]) -> Result<String, String> {
+    let id = id_of(bytes);
+    if find(dir, &id).is_ok() {
+        return Ok(id);
+    }
+
+    let extension = image::guess_format(bytes)
+        .ok()
+        .and_then(|format| format.extensions_str().first().copied())
+        .unwrap_or("bin");
+
+    fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+
+    let path = dir.join(format!("{}.{}", id, extension));
+    let tmp_path = dir.join(format!("{}.tmp", id));
+    fs::write(&tmp_path, bytes).map_err(|e| e.to_string())?;
+    fs::rename(&tmp_path, &path).map_err(|e| e.to_string())?;
+
+    Ok(id)
+}
+
+fn find(dir: &Path, id: &str) -> Result<PathBuf, String> {
+    check_id(id)?;
+    fs::read_dir(dir)
+        .map_err(|_| format!("Image {} not found", id))?
+        .flatten()
+        .map(|entry| entry.path())
+        .find(|path| {
+            path.file_stem().is_some_and(|stem| stem == id)
+                && path.extension().is_some_and(|ext| ext != "tmp")
+        })
+        .ok_or_else(|| format!("Image {} not found", id))
+}
+
+fn remove_from(dir: &Path, id: &str) -> Result<(), String> {
+    check_id(id)?;
+    match find(dir, id) {
+        Ok(path) => fs::remove_file(path).map_err(|e| e.to_string()),
+        Err(_) => Ok(()),
+    }
+}
+
+fn check_id(id: &str) -> Result<(), String> {
+    if id.len() != 64 || !id.bytes().all(|b| b.is_ascii_hexdigit()) {
+        return Err("Invalid image id".into());
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::conversations::{ConversationStore, NewConversation};
+    use crate::services::llm::Role;
+
+    #[test]
+    fn deleting_a_conversation_removes_its_own_images() {
+        let dir = std::env::temp_dir().join(format!("spatialshot_images_{}", std::process::id()));
+        let own = store_in(&dir, b"own screenshot").unwrap();
+        let shared = store_in(&dir, b"shared screenshot").unwrap();
+        assert_eq!(store_in(&dir, b"own screenshot").unwrap(), own);
+
+        let mut conversations = ConversationStore::open(&dir.join("conversations.db")).unwrap();
+        for (id, images) in [
+            ("c1", vec![own.clone(), shared.clone()]),
+            ("c2", vec![shared.clone()]),
+        ] {
+            conversations
+                .create(NewConversation {
+                    id,
+                    provider: "gemini",
+                    model: "gemini-2.5-flash",
+                    prompt: "Describe",
+                    system_instruction: None,
+                })
+                .unwrap();
+            conversations
+                .append_message(id, Role::User, "Look", &images)
+                .unwrap();
+        }
+
+        for id in conversations.delete("c1").unwrap().image_ids {
+            remove_from(&dir, &id).unwrap();
+        }
+
+        assert!(find(&dir, &own).is_err());
+        assert!(find(&dir, &shared).is_ok());
+        remove_from(&dir, &own).unwrap();
+        assert!(remove_from(&dir, "../escape").is_err());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
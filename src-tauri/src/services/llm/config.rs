@@ -0,0 +1,175 @@
+/*
+ * @license
+ * Copyright 2025 a7mddra
+ * SPDX-License-Identifier: Apache-2.0
+ */
+
+use serde::{Deserialize, Serialize};
+use std::fs;
+use tauri::AppHandle;
+
+use super::gemini::{self, GeminiBackend};
+use super::ollama::{self, OllamaBackend};
+use super::openai::{self, OpenAiBackend};
+use super::ModelBackend;
+use crate::services::security;
+use crate::utils::get_app_config_dir;
+
+pub const GEMINI_PROVIDER_ID: &str = "gemini";
+pub const OLLAMA_PROVIDER_ID: &str = "ollama";
+
+const CONFIG_SUFFIX: &str = "_backend.json";
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum BackendKind {
+    Gemini,
+    #[serde(rename = "openai")]
+    OpenAi,
+    Ollama,
+}
+
+/// Connection settings for one model provider. Stored encrypted as
+/// `<id>_backend.json` in the config dir.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct BackendConfig {
+    pub kind: BackendKind,
+    #[serde(default)]
+    pub base_url: Option<String>,
+    #[serde(default)]
+    pub api_key: Option<String>,
+}
+
+/// What the frontend may see of a provider: everything but the key.
+#[derive(Serialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct ProviderSummary {
+    pub id: String,
+    pub kind: BackendKind,
+    pub base_url: String,
+    pub has_key: bool,
+}
+
+impl BackendConfig {
+    pub fn base_url(&self) -> &str {
+        self.base_url
+            .as_deref()
+            .filter(|url| !url.is_empty())
+            .unwrap_or(match self.kind {
+                BackendKind::Gemini => gemini::DEFAULT_BASE_URL,
+                BackendKind::OpenAi => openai::DEFAULT_BASE_URL,
+                BackendKind::Ollama => ollama::DEFAULT_BASE_URL,
+            })
+    }
+
+    pub fn open(&self) -> Result<Box<dyn ModelBackend>, String> {
+        let base_url = self.base_url();
+        Ok(match self.kind {
+            BackendKind::Gemini => {
+                let key = self
+                    .api_key
+                    .as_deref()
+                    .ok_or("Gemini API key is not configured")?;
+                Box::new(GeminiBackend::new(base_url, key)?)
+            }
+            BackendKind::OpenAi => Box::new(OpenAiBackend::new(base_url, self.api_key.as_deref())?),
+            BackendKind::Ollama => Box::new(OllamaBackend::new(base_url)?),
+        })
+    }
+}
+
+fn validate_id(id: &str) -> Result<(), String> {
+    let valid = !id.is_empty()
+        && id
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-' || c == '_');
+    if valid {
+        Ok(())
+    } else {
+        Err("Provider id may only contain a-z, 0-9, '-' and '_'".into())
+    }
+}
+
+/// Loads a provider's settings. Gemini falls back to the key saved during
+/// onboarding and Ollama to its default local address, so both work without
+/// an explicit entry.
+pub fn load_config(app: &AppHandle, id: &str) -> Result<BackendConfig, String> {
+    validate_id(id)?;
+
+    let stored = match security::load_decrypted(app, &format!("{}{}", id, CONFIG_SUFFIX)) {
+        Some(json) => Some(
+            serde_json::from_str::<BackendConfig>(&json)
+                .map_err(|e| format!("Invalid settings for provider '{}': {}", id, e))?,
+        ),
+        None => None,
+    };
+
+    let mut config = match (stored, id) {
+        (Some(config), _) => config,
+        (None, GEMINI_PROVIDER_ID) => BackendConfig {
+            kind: BackendKind::Gemini,
+            base_url: None,
+            api_key: None,
+        },
+        (None, OLLAMA_PROVIDER_ID) => BackendConfig {
+            kind: BackendKind::Ollama,
+            base_url: None,
+            api_key: None,
+        },
+        (None, _) => return Err(format!("Model provider '{}' is not configured", id)),
+    };
+
+    if config.kind == BackendKind::Gemini && config.api_key.is_none() {
+        config.api_key = security::get_decrypted_key_internal(app, GEMINI_PROVIDER_ID);
+    }
+    Ok(config)
+}
+
+pub fn save_config(app: &AppHandle, id: &str, config: &BackendConfig) -> Result<(), String> {
+    validate_id(id)?;
+    let json = serde_json::to_string(config).map_err(|e| e.to_string())?;
+    security::encrypt_and_store(app, &format!("{}{}", id, CONFIG_SUFFIX), &json)?;
+    Ok(())
+}
+
+pub fn remove_config(app: &AppHandle, id: &str) -> Result<(), String> {
+    validate_id(id)?;
+    let path = get_app_config_dir(app).join(format!("{}{}", id, CONFIG_SUFFIX));
+    if path.exists() {
+        fs::remove_file(path).map_err(|e| e.to_string())?;
+    }
+    Ok(())
+}
+
+/// The built-in providers plus every saved one, sorted by id.
+pub fn list_providers(app: &AppHandle) -> Vec<ProviderSummary> {
+    let mut ids = vec![
+        GEMINI_PROVIDER_ID.to_string(),
+        OLLAMA_PROVIDER_ID.to_string(),
+    ];
+
+    if let Ok(entries) = fs::read_dir(get_app_config_dir(app)) {
+        for entry in entries.flatten() {
+            let name = entry.file_name().to_string_lossy().into_owned();
+            if let Some(id) = name.strip_suffix(CONFIG_SUFFIX) {
+                if validate_id(id).is_ok() && !ids.iter().any(|known| known == id) {
+                    ids.push(id.to_string());
+                }
+            }
+        }
+    }
+    ids.sort();
+
+    ids.into_iter()
+        .filter_map(|id| {
+            let config = load_config(app, &id).ok()?;
+            Some(ProviderSummary {
+                kind: config.kind,
+                base_url: config.base_url().to_string(),
+                has_key: config.api_key.as_deref().is_some_and(|k| !k.is_empty()),
+                id,
+            })
+        })
+        .collect()
+}
@@ -0,0 +1,286 @@
+/*
+ * @license
+ * Copyright 2025 a7mddra
+ * SPDX-License-Identifier: Apache-2.0
+ */
+
+use reqwest::blocking::Client;
+use serde_json::{json, Value};
+use std::sync::atomic::AtomicBool;
+
+use super::stream::read_sse;
+use super::{
+    check_status, http_client, Message, ModelBackend, ModelInfo, Part, Reply, TokenUsage,
+    REQUEST_TIMEOUT,
+};
+
+pub const DEFAULT_BASE_URL: &str = "https://generativelanguage.googleapis.com";
+
+/// Gemini REST backend. Responses are streamed with `alt=sse`.
+pub struct GeminiBackend {
+    http: Client,
+    base_url: String,
+    api_key: String,
+}
+
+impl GeminiBackend {
+    pub fn new(base_url: &str, api_key: &str) -> Result<Self, String> {
+        Ok(Self {
+            http: http_client()?,
+            base_url: base_url.trim_end_matches('/').to_string(),
+            api_key: api_key.to_string(),
+        })
+    }
+}
+
+impl ModelBackend for GeminiBackend {
+    fn list_models(&self) -> Result<Vec<ModelInfo>, String> {
+        let response = self
+            .http
+            .get(format!("{}/v1beta/models?pageSize=1000", self.base_url))
+            .header("x-goog-api-key", &self.api_key)
+            .timeout(REQUEST_TIMEOUT)
+            .send()
+            .map_err(|e| format!("Gemini request failed: {}", e))?;
+        let body: Value = check_status(response, "Gemini")?
+            .json()
+            .map_err(|e| e.to_string())?;
+
+        let models = body["models"]
+            .as_array()
+            .map(Vec::as_slice)
+            .unwrap_or_default()
+            .iter()
+            .filter(|model| {
+                model["supportedGenerationMethods"]
+                    .as_array()
+                    .is_some_and(|methods| methods.iter().any(|m| m == "generateContent"))
+            })
+            .filter_map(|model| {
+                let id = model["name"].as_str()?;
+                let id = id.strip_prefix("models/").unwrap_or(id);
+                Some(ModelInfo {
+                    id: id.to_string(),
+                    name: model["displayName"].as_str().unwrap_or(id).to_string(),
+                })
+            })
+            .collect();
+
+        Ok(models)
+    }
+
+    fn stream_chat(
+        &self,
+        model: &str,
+        system_instruction: Option<&str>,
+        contents: &[Message],
+        cancel: &AtomicBool,
+        on_token: &mut dyn FnMut(&str),
+    ) -> Result<Reply, String> {
+        let url = format!(
+            "{}/v1beta/models/{}:streamGenerateContent?alt=sse",
+            self.base_url, model
+        );
+
+        let response = self
+            .http
+            .post(&url)
+            .header("x-goog-api-key", &self.api_key)
+            .json(&request_body(system_instruction, contents))
+            .send()
+            .map_err(|e| format!("Gemini request failed: {}", e))?;
+        let response = check_status(response, "Gemini")?;
+
+        let mut reply = Reply::default();
+        read_sse(response, cancel, &mut |data| {
+            let (text, usage) = parse_event(data)?;
+            if !text.is_empty() {
+                on_token(&text);
+                reply.text.push_str(&text);
+            }
+            // Each event carries the running total, so the last one wins.
+            reply.usage = usage.or(reply.usage);
+            Ok(())
+        })?;
+
+        Ok(reply)
+    }
+}
+
+fn request_body(system_instruction: Option<&str>, contents: &[Message]) -> Value {
+    let contents: Vec<Value> = contents
+        .iter()
+        .map(|message| {
+            let parts: Vec<Value> = message
+                .parts
+                .iter()
+                .map(|part| match part {
+                    Part::Text { text } => json!({ "text": text }),
+                    Part::Image { mime_type, data } => json!({
+                        "inlineData": { "mimeType": mime_type, "data": data }
+                    }),
+                })
+                .collect();
+            json!({ "role": message.role, "parts": parts })
+        })
+        .collect();
+
+    let mut body = json!({ "contents": contents });
+    if let Some(instruction) = system_instruction {
+        body["systemInstruction"] = json!({ "parts": [{ "text": instruction }] });
+    }
+    body
+}
+
+/// Extracts the text and token counts of one SSE `data:` payload.
+fn parse_event(data: &str) -> Result<(String, Option<TokenUsage>), String> {
+    let event: Value =
+        serde_json::from_str(data).map_err(|e| format!("Malformed Gemini event: {}", e))?;
+
+    if let Some(message) = event["error"]["message"].as_str() {
+        return Err(format!("Gemini API error: {}", message));
+    }
+    if let Some(reason) = event["promptFeedback"]["blockReason"].as_str() {
+        return Err(format!("Prompt blocked by Gemini: {}", reason));
+    }
+
+    let text = event["candidates"][0]["content"]["parts"]
+        .as_array()
+        .map(|parts| {
+            parts
+                .iter()
+                .filter(|part| !part["thought"].as_bool().unwrap_or(false))
+                .filter_map(|part| part["text"].as_str())
+                .collect::<String>()
+        })
+        .unwrap_or_default();
+
+    let metadata = &event["usageMetadata"];
+    let usage = metadata["promptTokenCount"]
+        .as_u64()
+        .map(|input_tokens| TokenUsage {
+            input_tokens,
+            // Thinking tokens are billed as output.
+            output_tokens: metadata["candidatesTokenCount"].as_u64().unwrap_or(0)
+                + metadata["thoughtsTokenCount"].as_u64().unwrap_or(0),
+        });
+
+    Ok((text, usage))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::llm::Role;
+    use crate::testing::mock_server;
+
+    #[test]
+    fn streams_tokens_from_sse_events() {
+        let (base_url, server) = mock_server(
+            200,
+            "text/event-stream",
+            "data: {\"candidates\":[{\"content\":{\"parts\":[{\"text\":\"Hello\"}]}}]}\r\n\r\n\
+             data: {\"candidates\":[{\"content\":{\"parts\":[{\"text\":\", world\"}]}}],\
+             \"usageMetadata\":{\"promptTokenCount\":260,\"candidatesTokenCount\":3}}\r\n\r\n",
+        );
+        let backend = GeminiBackend::new(&base_url, "test-key").unwrap();
+        let contents = [Message {
+            role: Role::User,
+            parts: vec![
+                Part::Image {
+                    mime_type: "image/png".into(),
+                    data: "AAAA".into(),
+                },
+                Part::Text {
+                    text: "Describe".into(),
+                },
+            ],
+        }];
+
+        let mut tokens = Vec::new();
+        let reply = backend
+            .stream_chat(
+                "gemini-test",
+                Some("Be brief"),
+                &contents,
+                &AtomicBool::new(false),
+                &mut |token| tokens.push(token.to_string()),
+            )
+            .unwrap();
+
+        assert_eq!(reply.text, "Hello, world");
+        assert_eq!(
+            reply.usage,
+            Some(TokenUsage {
+                input_tokens: 260,
+                output_tokens: 3
+            })
+        );
+        assert_eq!(tokens, ["Hello", ", world"]);
+
+        let received = server.join().unwrap();
+        assert_eq!(
+            received.url,
+            "/v1beta/models/gemini-test:streamGenerateContent?alt=sse"
+        );
+        assert_eq!(received.header("x-goog-api-key"), Some("test-key"));
+        let body = received.body;
+        assert_eq!(body["systemInstruction"]["parts"][0]["text"], "Be brief");
+        assert_eq!(body["contents"][0]["role"], "user");
+        assert_eq!(
+            body["contents"][0]["parts"][0]["inlineData"]["mimeType"],
+            "image/png"
+        );
+        assert_eq!(body["contents"][0]["parts"][1]["text"], "Describe");
+    }
+
+    #[test]
+    fn reports_api_errors_with_status() {
+        let (base_url, server) = mock_server(
+            429,
+            "application/json",
+            "{\"error\":{\"code\":429,\"message\":\"Quota exceeded\"}}",
+        );
+        let backend = GeminiBackend::new(&base_url, "test-key").unwrap();
+
+        let err = backend
+            .stream_chat(
+                "gemini-test",
+                None,
+                &[Message::text(Role::User, "Hi")],
+                &AtomicBool::new(false),
+                &mut |_| {},
+            )
+            .unwrap_err();
+
+        assert_eq!(err, "Gemini API error 429: Quota exceeded");
+        assert!(server
+            .join()
+            .unwrap()
+            .body
+            .get("systemInstruction")
+            .is_none());
+    }
+
+    #[test]
+    fn lists_only_generative_models() {
+        let (base_url, server) = mock_server(
+            200,
+            "application/json",
+            r#"{"models":[
+                {"name":"models/gemini-2.5-flash","displayName":"Gemini 2.5 Flash",
+                 "supportedGenerationMethods":["generateContent","countTokens"]},
+                {"name":"models/text-embedding-004","displayName":"Embedding",
+                 "supportedGenerationMethods":["embedContent"]}
+            ]}"#,
+        );
+        let backend = GeminiBackend::new(&base_url, "test-key").unwrap();
+
+        let models = backend.list_models().unwrap();
+
+        assert_eq!(models.len(), 1);
+        assert_eq!(models[0].id, "gemini-2.5-flash");
+        assert_eq!(models[0].name, "Gemini 2.5 Flash");
+        server.join().unwrap();
+    }
+}
@@ -0,0 +1,190 @@
+/*
+ * @license
+ * Copyright 2025 a7mddra
+ * SPDX-License-Identifier: Apache-2.0
+ */
+
+pub mod config;
+pub mod gemini;
+pub mod ollama;
+pub mod openai;
+mod stream;
+
+use parking_lot::Mutex;
+use rand::{rngs::OsRng, RngCore};
+use reqwest::blocking::{Client, Response};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::sync::atomic::AtomicBool;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// A chat model provider. Implementations are blocking and are called from
+/// `spawn_blocking`.
+pub trait ModelBackend: Send + Sync {
+    fn list_models(&self) -> Result<Vec<ModelInfo>, String>;
+
+    /// Sends `contents` and calls `on_token` for each streamed text chunk.
+    /// Returns the full reply, or an error if `cancel` was set mid-stream.
+    fn stream_chat(
+        &self,
+        model: &str,
+        system_instruction: Option<&str>,
+        contents: &[Message],
+        cancel: &AtomicBool,
+        on_token: &mut dyn FnMut(&str),
+    ) -> Result<Reply, String>;
+}
+
+/// A finished reply and, when the provider reported it, its token count.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Reply {
+    pub text: String,
+    pub usage: Option<TokenUsage>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct TokenUsage {
+    pub input_tokens: u64,
+    pub output_tokens: u64,
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub struct ModelInfo {
+    pub id: String,
+    pub name: String,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum Role {
+    User,
+    Model,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(rename_all = "camelCase", tag = "type")]
+pub enum Part {
+    Text {
+        text: String,
+    },
+    /// Inline image, `data` is base64 without the data-URL prefix.
+    Image {
+        mime_type: String,
+        data: String,
+    },
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct Message {
+    pub role: Role,
+    pub parts: Vec<Part>,
+}
+
+impl Message {
+    pub fn text(role: Role, text: &str) -> Self {
+        Self {
+            role,
+            parts: vec![Part::Text {
+                text: text.to_string(),
+            }],
+        }
+    }
+}
+
+pub struct ChatSession {
+    pub id: String,
+    pub provider: String,
+    pub model: String,
+    pub system_instruction: Option<String>,
+    pub history: Vec<Message>,
+}
+
+/// A live chat plus the flag that cancels its in-flight request.
+#[derive(Clone)]
+pub struct SessionHandle {
+    pub session: Arc<Mutex<ChatSession>>,
+    pub cancel: Arc<AtomicBool>,
+}
+
+#[derive(Default)]
+pub struct ChatRegistry {
+    sessions: Mutex<HashMap<String, SessionHandle>>,
+}
+
+impl ChatRegistry {
+    pub fn create(
+        &self,
+        provider: &str,
+        model: &str,
+        system_instruction: Option<String>,
+    ) -> SessionHandle {
+        let mut id_bytes = [0u8; 8];
+        OsRng.fill_bytes(&mut id_bytes);
+
+        self.insert(ChatSession {
+            id: hex::encode(id_bytes),
+            provider: provider.to_string(),
+            model: model.to_string(),
+            system_instruction,
+            history: Vec::new(),
+        })
+    }
+
+    /// Registers an existing session, e.g. one restored from history.
+    pub fn insert(&self, session: ChatSession) -> SessionHandle {
+        let id = session.id.clone();
+        let handle = SessionHandle {
+            session: Arc::new(Mutex::new(session)),
+            cancel: Arc::new(AtomicBool::new(false)),
+        };
+        self.sessions.lock().insert(id, handle.clone());
+        handle
+    }
+
+    pub fn get(&self, id: &str) -> Option<SessionHandle> {
+        self.sessions.lock().get(id).cloned()
+    }
+
+    pub fn remove(&self, id: &str) -> Option<SessionHandle> {
+        self.sessions.lock().remove(id)
+    }
+}
+
+/// Time allowed for requests that are answered in one piece, such as model
+/// lists.
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// HTTP client shared by the backends. It has no overall timeout, as that
+/// would also cut off replies that stream for longer; requests answered in
+/// one piece set `REQUEST_TIMEOUT` and streams guard against stalls as they
+/// read.
+fn http_client() -> Result<Client, String> {
+    Client::builder()
+        .connect_timeout(Duration::from_secs(10))
+        .build()
+        .map_err(|e| e.to_string())
+}
+
+/// Turns a non-success response into an error that carries the status code
+/// and the provider's own message when it sent one.
+fn check_status(response: Response, provider: &str) -> Result<Response, String> {
+    let status = response.status();
+    if status.is_success() {
+        return Ok(response);
+    }
+
+    let body: Value = response.json().unwrap_or_default();
+    let message = body["error"]["message"]
+        .as_str()
+        .or_else(|| body["error"].as_str())
+        .unwrap_or_else(|| status.canonical_reason().unwrap_or("unknown error"));
+    Err(format!(
+        "{} API error {}: {}",
+        provider,
+        status.as_u16(),
+        message
+    ))
+}
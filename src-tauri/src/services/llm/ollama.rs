@@ -0,0 +1,214 @@
+/*
+ * @license
+ * Copyright 2025 a7mddra
+ * SPDX-License-Identifier: Apache-2.0
+ */
+
+use reqwest::blocking::Client;
+use serde_json::{json, Value};
+use std::sync::atomic::AtomicBool;
+
+use super::stream::read_ndjson;
+use super::{
+    check_status, http_client, Message, ModelBackend, ModelInfo, Part, Reply, Role, TokenUsage,
+    REQUEST_TIMEOUT,
+};
+
+pub const DEFAULT_BASE_URL: &str = "http://localhost:11434";
+
+/// Backend for a local Ollama server, which streams newline-delimited JSON.
+pub struct OllamaBackend {
+    http: Client,
+    base_url: String,
+}
+
+impl OllamaBackend {
+    pub fn new(base_url: &str) -> Result<Self, String> {
+        Ok(Self {
+            http: http_client()?,
+            base_url: base_url.trim_end_matches('/').to_string(),
+        })
+    }
+}
+
+impl ModelBackend for OllamaBackend {
+    fn list_models(&self) -> Result<Vec<ModelInfo>, String> {
+        let response = self
+            .http
+            .get(format!("{}/api/tags", self.base_url))
+            .timeout(REQUEST_TIMEOUT)
+            .send()
+            .map_err(|e| format!("Ollama request failed: {}", e))?;
+        let body: Value = check_status(response, "Ollama")?
+            .json()
+            .map_err(|e| e.to_string())?;
+
+        let models = body["models"]
+            .as_array()
+            .map(Vec::as_slice)
+            .unwrap_or_default()
+            .iter()
+            .filter_map(|model| model["name"].as_str())
+            .map(|name| ModelInfo {
+                id: name.to_string(),
+                name: name.to_string(),
+            })
+            .collect();
+
+        Ok(models)
+    }
+
+    fn stream_chat(
+        &self,
+        model: &str,
+        system_instruction: Option<&str>,
+        contents: &[Message],
+        cancel: &AtomicBool,
+        on_token: &mut dyn FnMut(&str),
+    ) -> Result<Reply, String> {
+        let body = json!({
+            "model": model,
+            "messages": request_messages(system_instruction, contents),
+            "stream": true,
+        });
+
+        let response = self
+            .http
+            .post(format!("{}/api/chat", self.base_url))
+            .json(&body)
+            .send()
+            .map_err(|e| format!("Ollama request failed: {}", e))?;
+        let response = check_status(response, "Ollama")?;
+
+        let mut reply = Reply::default();
+        read_ndjson(response, cancel, &mut |chunk| {
+            if let Some(message) = chunk["error"].as_str() {
+                return Err(format!("Ollama API error: {}", message));
+            }
+
+            if let Some(text) = chunk["message"]["content"].as_str() {
+                if !text.is_empty() {
+                    on_token(text);
+                    reply.text.push_str(text);
+                }
+            }
+            // The final chunk reports the evaluated prompt and reply lengths.
+            if let Some(input_tokens) = chunk["prompt_eval_count"].as_u64() {
+                reply.usage = Some(TokenUsage {
+                    input_tokens,
+                    output_tokens: chunk["eval_count"].as_u64().unwrap_or(0),
+                });
+            }
+            Ok(())
+        })?;
+
+        Ok(reply)
+    }
+}
+
+fn request_messages(system_instruction: Option<&str>, contents: &[Message]) -> Vec<Value> {
+    let system = system_instruction.map(|text| json!({ "role": "system", "content": text }));
+
+    let turns = contents.iter().map(|message| {
+        let mut text = String::new();
+        let mut images = Vec::new();
+        for part in &message.parts {
+            match part {
+                Part::Text { text: chunk } => text.push_str(chunk),
+                Part::Image { data, .. } => images.push(data.as_str()),
+            }
+        }
+
+        let role = match message.role {
+            Role::User => "user",
+            Role::Model => "assistant",
+        };
+        let mut turn = json!({ "role": role, "content": text });
+        if !images.is_empty() {
+            turn["images"] = json!(images);
+        }
+        turn
+    });
+
+    system.into_iter().chain(turns).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::mock_server;
+
+    #[test]
+    fn streams_ndjson_chunks() {
+        let (base_url, server) = mock_server(
+            200,
+            "application/x-ndjson",
+            "{\"message\":{\"role\":\"assistant\",\"content\":\"A cat\"},\"done\":false}\n\
+             {\"message\":{\"role\":\"assistant\",\"content\":\" asleep\"},\"done\":false}\n\
+             {\"message\":{\"role\":\"assistant\",\"content\":\"\"},\"done\":true,\
+             \"prompt_eval_count\":598,\"eval_count\":4}\n",
+        );
+        let backend = OllamaBackend::new(&base_url).unwrap();
+        let contents = [Message {
+            role: Role::User,
+            parts: vec![
+                Part::Image {
+                    mime_type: "image/jpeg".into(),
+                    data: "AAAA".into(),
+                },
+                Part::Text {
+                    text: "Describe".into(),
+                },
+            ],
+        }];
+
+        let mut tokens = Vec::new();
+        let reply = backend
+            .stream_chat(
+                "llava",
+                None,
+                &contents,
+                &AtomicBool::new(false),
+                &mut |token| tokens.push(token.to_string()),
+            )
+            .unwrap();
+
+        assert_eq!(reply.text, "A cat asleep");
+        assert_eq!(
+            reply.usage,
+            Some(TokenUsage {
+                input_tokens: 598,
+                output_tokens: 4
+            })
+        );
+        assert_eq!(tokens, ["A cat", " asleep"]);
+
+        let received = server.join().unwrap();
+        assert_eq!(received.url, "/api/chat");
+        assert_eq!(received.body["messages"][0]["content"], "Describe");
+        assert_eq!(received.body["messages"][0]["images"][0], "AAAA");
+    }
+
+    #[test]
+    fn surfaces_stream_errors() {
+        let (base_url, server) = mock_server(
+            200,
+            "application/x-ndjson",
+            "{\"error\":\"model 'llava' not found\"}\n",
+        );
+        let backend = OllamaBackend::new(&base_url).unwrap();
+
+        let err = backend
+            .stream_chat(
+                "llava",
+                None,
+                &[Message::text(Role::User, "Hi")],
+                &AtomicBool::new(false),
+                &mut |_| {},
+            )
+            .unwrap_err();
+
+        assert_eq!(err, "Ollama API error: model 'llava' not found");
+        server.join().unwrap();
+    }
+}
@@ -0,0 +1,253 @@
+/*
+ * @license
+ * Copyright 2025 a7mddra
+ * SPDX-License-Identifier: Apache-2.0
+ */
+
+use reqwest::blocking::{Client, RequestBuilder};
+use serde_json::{json, Value};
+use std::sync::atomic::AtomicBool;
+
+use super::stream::read_sse;
+use super::{
+    check_status, http_client, Message, ModelBackend, ModelInfo, Part, Reply, Role, TokenUsage,
+    REQUEST_TIMEOUT,
+};
+
+pub const DEFAULT_BASE_URL: &str = "http://localhost:8080/v1";
+
+/// Backend for servers that speak the OpenAI `/v1/chat/completions` API,
+/// such as llama.cpp server, vLLM and LM Studio. `base_url` includes the
+/// `/v1` prefix.
+pub struct OpenAiBackend {
+    http: Client,
+    base_url: String,
+    api_key: Option<String>,
+}
+
+impl OpenAiBackend {
+    pub fn new(base_url: &str, api_key: Option<&str>) -> Result<Self, String> {
+        Ok(Self {
+            http: http_client()?,
+            base_url: base_url.trim_end_matches('/').to_string(),
+            api_key: api_key.filter(|k| !k.is_empty()).map(str::to_string),
+        })
+    }
+
+    fn authorize(&self, request: RequestBuilder) -> RequestBuilder {
+        match &self.api_key {
+            Some(key) => request.bearer_auth(key),
+            None => request,
+        }
+    }
+}
+
+impl ModelBackend for OpenAiBackend {
+    fn list_models(&self) -> Result<Vec<ModelInfo>, String> {
+        let response = self
+            .authorize(self.http.get(format!("{}/models", self.base_url)))
+            .timeout(REQUEST_TIMEOUT)
+            .send()
+            .map_err(|e| format!("OpenAI-compatible request failed: {}", e))?;
+        let body: Value = check_status(response, "OpenAI-compatible")?
+            .json()
+            .map_err(|e| e.to_string())?;
+
+        let models = body["data"]
+            .as_array()
+            .map(Vec::as_slice)
+            .unwrap_or_default()
+            .iter()
+            .filter_map(|model| model["id"].as_str())
+            .map(|id| ModelInfo {
+                id: id.to_string(),
+                name: id.to_string(),
+            })
+            .collect();
+
+        Ok(models)
+    }
+
+    fn stream_chat(
+        &self,
+        model: &str,
+        system_instruction: Option<&str>,
+        contents: &[Message],
+        cancel: &AtomicBool,
+        on_token: &mut dyn FnMut(&str),
+    ) -> Result<Reply, String> {
+        let body = json!({
+            "model": model,
+            "messages": request_messages(system_instruction, contents),
+            "stream": true,
+            // Asks for a final chunk with token counts. Servers that do not
+            // support it ignore the option.
+            "stream_options": { "include_usage": true },
+        });
+
+        let response = self
+            .authorize(
+                self.http
+                    .post(format!("{}/chat/completions", self.base_url))
+                    .json(&body),
+            )
+            .send()
+            .map_err(|e| format!("OpenAI-compatible request failed: {}", e))?;
+        let response = check_status(response, "OpenAI-compatible")?;
+
+        let mut reply = Reply::default();
+        read_sse(response, cancel, &mut |data| {
+            if data == "[DONE]" {
+                return Ok(());
+            }
+
+            let event: Value =
+                serde_json::from_str(data).map_err(|e| format!("Malformed event: {}", e))?;
+            if let Some(message) = event["error"]["message"].as_str() {
+                return Err(format!("OpenAI-compatible API error: {}", message));
+            }
+
+            if let Some(text) = event["choices"][0]["delta"]["content"].as_str() {
+                if !text.is_empty() {
+                    on_token(text);
+                    reply.text.push_str(text);
+                }
+            }
+            if let Some(input_tokens) = event["usage"]["prompt_tokens"].as_u64() {
+                reply.usage = Some(TokenUsage {
+                    input_tokens,
+                    output_tokens: event["usage"]["completion_tokens"].as_u64().unwrap_or(0),
+                });
+            }
+            Ok(())
+        })?;
+
+        Ok(reply)
+    }
+}
+
+fn request_messages(system_instruction: Option<&str>, contents: &[Message]) -> Vec<Value> {
+    let system = system_instruction.map(|text| json!({ "role": "system", "content": text }));
+
+    let turns = contents.iter().map(|message| match message.role {
+        Role::User => {
+            let content: Vec<Value> = message
+                .parts
+                .iter()
+                .map(|part| match part {
+                    Part::Text { text } => json!({ "type": "text", "text": text }),
+                    Part::Image { mime_type, data } => json!({
+                        "type": "image_url",
+                        "image_url": { "url": format!("data:{};base64,{}", mime_type, data) }
+                    }),
+                })
+                .collect();
+            json!({ "role": "user", "content": content })
+        }
+        Role::Model => json!({ "role": "assistant", "content": text_of(message) }),
+    });
+
+    system.into_iter().chain(turns).collect()
+}
+
+fn text_of(message: &Message) -> String {
+    message
+        .parts
+        .iter()
+        .filter_map(|part| match part {
+            Part::Text { text } => Some(text.as_str()),
+            Part::Image { .. } => None,
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::mock_server;
+
+    #[test]
+    fn streams_chat_completion_deltas() {
+        let (base_url, server) = mock_server(
+            200,
+            "text/event-stream",
+            "data: {\"choices\":[{\"delta\":{\"role\":\"assistant\"}}]}\n\n\
+             data: {\"choices\":[{\"delta\":{\"content\":\"A red\"}}]}\n\n\
+             data: {\"choices\":[{\"delta\":{\"content\":\" square\"}}]}\n\n\
+             data: {\"choices\":[],\"usage\":{\"prompt_tokens\":812,\"completion_tokens\":2}}\n\n\
+             data: [DONE]\n\n",
+        );
+        let backend = OpenAiBackend::new(&format!("{}/v1", base_url), Some("sk-test")).unwrap();
+        let contents = [
+            Message {
+                role: Role::User,
+                parts: vec![
+                    Part::Image {
+                        mime_type: "image/png".into(),
+                        data: "AAAA".into(),
+                    },
+                    Part::Text {
+                        text: "What is this?".into(),
+                    },
+                ],
+            },
+            Message::text(Role::Model, "A shape."),
+            Message::text(Role::User, "Which colour?"),
+        ];
+
+        let mut tokens = Vec::new();
+        let reply = backend
+            .stream_chat(
+                "llava",
+                Some("Be brief"),
+                &contents,
+                &AtomicBool::new(false),
+                &mut |token| tokens.push(token.to_string()),
+            )
+            .unwrap();
+
+        assert_eq!(reply.text, "A red square");
+        assert_eq!(
+            reply.usage,
+            Some(TokenUsage {
+                input_tokens: 812,
+                output_tokens: 2
+            })
+        );
+        assert_eq!(tokens, ["A red", " square"]);
+
+        let received = server.join().unwrap();
+        assert_eq!(received.url, "/v1/chat/completions");
+        assert_eq!(received.header("Authorization"), Some("Bearer sk-test"));
+        let messages = &received.body["messages"];
+        assert_eq!(received.body["stream"], true);
+        assert_eq!(received.body["stream_options"]["include_usage"], true);
+        assert_eq!(messages[0]["role"], "system");
+        assert_eq!(
+            messages[1]["content"][0]["image_url"]["url"],
+            "data:image/png;base64,AAAA"
+        );
+        assert_eq!(messages[2]["role"], "assistant");
+        assert_eq!(messages[2]["content"], "A shape.");
+    }
+
+    #[test]
+    fn lists_models_without_a_key() {
+        let (base_url, server) = mock_server(
+            200,
+            "application/json",
+            r#"{"object":"list","data":[{"id":"qwen2-vl"},{"id":"llava"}]}"#,
+        );
+        let backend = OpenAiBackend::new(&base_url, None).unwrap();
+
+        let ids: Vec<String> = backend
+            .list_models()
+            .unwrap()
+            .into_iter()
+            .map(|m| m.id)
+            .collect();
+
+        assert_eq!(ids, ["qwen2-vl", "llava"]);
+        assert_eq!(server.join().unwrap().header("Authorization"), None);
+    }
+}
@@ -0,0 +1,173 @@
+/*
+ * @license
+ * Copyright 2025 a7mddra
+ * SPDX-License-Identifier: Apache-2.0
+ */
+
+use serde_json::Value;
+use std::io::{BufRead, BufReader, Read};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// How long a stream may send nothing before it counts as stalled. Models
+/// can think for a while before their first token.
+const IDLE_TIMEOUT: Duration = Duration::from_secs(120);
+/// How often a quiet stream checks whether it was cancelled.
+const CANCEL_POLL: Duration = Duration::from_millis(100);
+
+/// Reads a `text/event-stream` body and hands each event's data to
+/// `on_data`. Stops early with an error once `cancel` is set.
+pub fn read_sse(
+    body: impl Read + Send + 'static,
+    cancel: &AtomicBool,
+    on_data: &mut dyn FnMut(&str) -> Result<(), String>,
+) -> Result<(), String> {
+    let mut data = String::new();
+
+    read_lines(body, cancel, IDLE_TIMEOUT, &mut |line| {
+        if let Some(chunk) = line.strip_prefix("data:") {
+            if !data.is_empty() {
+                data.push('\n');
+            }
+            data.push_str(chunk.trim_start());
+            return Ok(());
+        }
+        if !line.is_empty() || data.is_empty() {
+            return Ok(());
+        }
+
+        on_data(&std::mem::take(&mut data))
+    })?;
+
+    if !data.is_empty() {
+        on_data(&data)?;
+    }
+    Ok(())
+}
+
+/// Reads a newline-delimited JSON body, one object per line.
+pub fn read_ndjson(
+    body: impl Read + Send + 'static,
+    cancel: &AtomicBool,
+    on_value: &mut dyn FnMut(Value) -> Result<(), String>,
+) -> Result<(), String> {
+    read_lines(body, cancel, IDLE_TIMEOUT, &mut |line| {
+        if line.trim().is_empty() {
+            return Ok(());
+        }
+
+        let value =
+            serde_json::from_str(line).map_err(|e| format!("Malformed stream line: {}", e))?;
+        on_value(value)
+    })
+}
+
+/// Hands each line of `body` to `on_line`. Lines are read on another thread
+/// so that a stream going quiet for `idle` ends with an error, and `cancel`
+/// is noticed while waiting.
+fn read_lines(
+    body: impl Read + Send + 'static,
+    cancel: &AtomicBool,
+    idle: Duration,
+    on_line: &mut dyn FnMut(&str) -> Result<(), String>,
+) -> Result<(), String> {
+    let (tx, rx) = mpsc::sync_channel(16);
+    // The reader ends once the body does, or when the line it holds can no
+    // longer be sent because this side gave up.
+    thread::spawn(move || {
+        for line in BufReader::new(body).lines() {
+            let failed = line.is_err();
+            if tx.send(line).is_err() || failed {
+                break;
+            }
+        }
+    });
+
+    let mut deadline = Instant::now() + idle;
+    loop {
+        if cancel.load(Ordering::SeqCst) {
+            return Err("Cancelled".into());
+        }
+        let wait = deadline
+            .saturating_duration_since(Instant::now())
+            .min(CANCEL_POLL);
+        match rx.recv_timeout(wait) {
+            Ok(line) => {
+                let line = line.map_err(|e| format!("Stream failed: {}", e))?;
+                on_line(&line)?;
+                deadline = Instant::now() + idle;
+            }
+            Err(RecvTimeoutError::Timeout) if Instant::now() < deadline => {}
+            Err(RecvTimeoutError::Timeout) => {
+                return Err(format!(
+                    "Stream stalled: nothing received for {} seconds",
+                    idle.as_secs()
+                ))
+            }
+            Err(RecvTimeoutError::Disconnected) => return Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    /// A body that sends one line, then goes quiet.
+    struct Stalling(Option<Cursor<&'static [u8]>>);
+
+    impl Read for Stalling {
+        fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+            match &mut self.0 {
+                Some(cursor) if (cursor.position() as usize) < cursor.get_ref().len() => {
+                    cursor.read(buf)
+                }
+                _ => {
+                    self.0 = None;
+                    thread::sleep(Duration::from_secs(5));
+                    Ok(0)
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn reads_events_until_the_stream_ends() {
+        let mut events = Vec::new();
+        read_sse(
+            Cursor::new(b"data: one\n\n: comment\ndata: two\ndata: lines\n\ndata: tail".to_vec()),
+            &AtomicBool::new(false),
+            &mut |data| {
+                events.push(data.to_string());
+                Ok(())
+            },
+        )
+        .unwrap();
+
+        assert_eq!(events, ["one", "two\nlines", "tail"]);
+    }
+
+    #[test]
+    fn a_quiet_stream_stalls_or_cancels() {
+        let mut lines = Vec::new();
+        let err = read_lines(
+            Stalling(Some(Cursor::new(b"first\n"))),
+            &AtomicBool::new(false),
+            Duration::from_millis(300),
+            &mut |line| {
+                lines.push(line.to_string());
+                Ok(())
+            },
+        )
+        .unwrap_err();
+        assert_eq!(lines, ["first"]);
+        assert!(err.starts_with("Stream stalled"), "{}", err);
+
+        let cancel = AtomicBool::new(true);
+        let err = read_lines(Stalling(None), &cancel, IDLE_TIMEOUT, &mut |_| Ok(())).unwrap_err();
+        assert_eq!(err, "Cancelled");
+    }
+}
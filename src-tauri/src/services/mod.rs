@@ -5,6 +5,23 @@
  */
 
 pub mod auth;
+pub mod avatar;
+pub mod batch;
+pub mod clipboard;
+pub mod conversations;
+pub mod export;
+pub mod extract;
+pub mod fonts;
+pub mod hosting;
 pub mod image;
+pub mod image_store;
+pub mod llm;
+pub mod oauth;
+pub mod ocr;
+pub mod oidc;
+pub mod pii;
+pub mod reverse_search;
 pub mod security;
+pub mod settings;
+pub mod templates;
 pub mod window;
@@ -0,0 +1,374 @@
+/*
+ * @license
+ * Copyright 2025 a7mddra
+ * SPDX-License-Identifier: Apache-2.0
+ */
+
+use base64::{engine::general_purpose, Engine as _};
+use rand::{rngs::OsRng, RngCore};
+use serde::Deserialize;
+use sha2::{Digest, Sha256};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant};
+use tiny_http::{Request, Response, Server};
+use url::Url;
+
+const CANCEL_POLL_INTERVAL: Duration = Duration::from_millis(250);
+
+/// Generic OAuth 2.0 authorization-code client (RFC 6749 + PKCE, RFC 7636).
+#[derive(Debug, Clone)]
+pub struct OAuthClient {
+    pub client_id: String,
+    pub client_secret: Option<String>,
+    pub auth_uri: String,
+    pub token_uri: String,
+    pub revocation_uri: Option<String>,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct TokenResponse {
+    pub access_token: String,
+    #[serde(default)]
+    pub refresh_token: Option<String>,
+    #[serde(default)]
+    pub expires_in: Option<u64>,
+    #[serde(default)]
+    pub id_token: Option<String>,
+    #[serde(default)]
+    pub scope: Option<String>,
+}
+
+pub struct Pkce {
+    pub verifier: String,
+    pub challenge: String,
+}
+
+impl Pkce {
+    pub fn generate() -> Self {
+        let verifier = random_token(32);
+        let challenge =
+            general_purpose::URL_SAFE_NO_PAD.encode(Sha256::digest(verifier.as_bytes()));
+        Self {
+            verifier,
+            challenge,
+        }
+    }
+}
+
+pub fn random_token(len: usize) -> String {
+    let mut bytes = vec![0u8; len];
+    OsRng.fill_bytes(&mut bytes);
+    general_purpose::URL_SAFE_NO_PAD.encode(bytes)
+}
+
+impl OAuthClient {
+    pub fn authorize_url(
+        &self,
+        redirect_uri: &str,
+        scope: &str,
+        state: &str,
+        pkce: &Pkce,
+        extra: &[(&str, &str)],
+    ) -> Result<Url, String> {
+        let mut url = Url::parse(&self.auth_uri).map_err(|e| format!("Invalid auth_uri: {}", e))?;
+        url.query_pairs_mut()
+            .append_pair("client_id", &self.client_id)
+            .append_pair("redirect_uri", redirect_uri)
+            .append_pair("response_type", "code")
+            .append_pair("scope", scope)
+            .append_pair("state", state)
+            .append_pair("code_challenge", &pkce.challenge)
+            .append_pair("code_challenge_method", "S256")
+            .extend_pairs(extra);
+        Ok(url)
+    }
+
+    pub fn exchange_code(
+        &self,
+        code: &str,
+        verifier: &str,
+        redirect_uri: &str,
+    ) -> Result<TokenResponse, String> {
+        self.token_request(&[
+            ("grant_type", "authorization_code"),
+            ("code", code),
+            ("code_verifier", verifier),
+            ("redirect_uri", redirect_uri),
+        ])
+        .map_err(|e| format!("Token Exchange Failed: {}", e))
+    }
+
+    pub fn refresh(&self, refresh_token: &str) -> Result<TokenResponse, String> {
+        self.token_request(&[
+            ("grant_type", "refresh_token"),
+            ("refresh_token", refresh_token),
+        ])
+        .map_err(|e| format!("Token Refresh Failed: {}", e))
+    }
+
+    /// Revokes `token` at the provider (RFC 7009). Revoking a refresh token
+    /// also invalidates the access tokens issued from it.
+    pub fn revoke(&self, token: &str) -> Result<(), String> {
+        let revocation_uri = self
+            .revocation_uri
+            .as_deref()
+            .ok_or("Provider has no revocation endpoint")?;
+
+        let res = reqwest::blocking::Client::new()
+            .post(revocation_uri)
+            .form(&[("token", token), ("client_id", &self.client_id)])
+            .send()
+            .map_err(|e| format!("Token Revocation Failed: {}", e))?;
+
+        // 400 `invalid_token` means the token is already gone.
+        if res.status().is_success() || res.status() == reqwest::StatusCode::BAD_REQUEST {
+            Ok(())
+        } else {
+            Err(format!("Token Revocation Failed: {}", res.status()))
+        }
+    }
+
+    fn token_request(&self, params: &[(&str, &str)]) -> Result<TokenResponse, String> {
+        let mut form: Vec<(&str, &str)> = vec![("client_id", self.client_id.as_str())];
+        if let Some(secret) = &self.client_secret {
+            form.push(("client_secret", secret));
+        }
+        form.extend_from_slice(params);
+
+        let res = reqwest::blocking::Client::new()
+            .post(&self.token_uri)
+            .form(&form)
+            .send()
+            .map_err(|e| e.to_string())?;
+
+        if !res.status().is_success() {
+            let status = res.status();
+            let body = res.text().unwrap_or_default();
+            return Err(format!("{} {}", status, body));
+        }
+
+        res.json().map_err(|e| e.to_string())
+    }
+}
+
+/// Browser redirect delivered to the loopback server. `code` is the
+/// authorization code, or the reason the callback was rejected.
+pub struct Callback {
+    pub request: Request,
+    pub code: Result<String, String>,
+}
+
+/// Loopback redirect listener (RFC 8252 §7.3) on an OS-assigned port.
+pub struct LoopbackServer {
+    server: Server,
+    port: u16,
+}
+
+impl LoopbackServer {
+    pub fn bind() -> Result<Self, String> {
+        let server = Server::http("127.0.0.1:0")
+            .map_err(|e| format!("Failed to start auth server: {}", e))?;
+        let port = server
+            .server_addr()
+            .to_ip()
+            .map(|addr| addr.port())
+            .ok_or("Auth server is not bound to a TCP port")?;
+
+        Ok(Self { server, port })
+    }
+
+    pub fn redirect_uri(&self) -> String {
+        format!("http://127.0.0.1:{}", self.port)
+    }
+
+    /// Blocks until the browser redirect arrives, `timeout` elapses or
+    /// `cancel` is set. Requests with the wrong `state` get a 400 and are
+    /// otherwise ignored.
+    pub fn wait_for_callback(
+        &self,
+        expected_state: &str,
+        timeout: Duration,
+        cancel: &AtomicBool,
+    ) -> Result<Callback, String> {
+        let deadline = Instant::now() + timeout;
+
+        loop {
+            if cancel.load(Ordering::SeqCst) {
+                return Err("Sign-in cancelled".into());
+            }
+
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            if remaining.is_zero() {
+                return Err("Timed out waiting for the sign-in callback".into());
+            }
+
+            let request = match self
+                .server
+                .recv_timeout(remaining.min(CANCEL_POLL_INTERVAL))
+            {
+                Ok(Some(rq)) => rq,
+                Ok(None) => continue,
+                Err(e) => return Err(format!("Server receive error: {}", e)),
+            };
+
+            let url = match Url::parse(&format!("{}{}", self.redirect_uri(), request.url())) {
+                Ok(url) => url,
+                Err(_) => {
+                    let _ = request.respond(Response::empty(400));
+                    continue;
+                }
+            };
+
+            let param = |name: &str| {
+                url.query_pairs()
+                    .find(|(key, _)| key == name)
+                    .map(|(_, value)| value.into_owned())
+            };
+
+            let state = param("state");
+            let code = param("code");
+            let error = param("error");
+
+            if state.is_none() && code.is_none() && error.is_none() {
+                let _ = request.respond(Response::empty(404));
+                continue;
+            }
+
+            // A stale tab or another page probing the port; the real
+            // redirect may still come.
+            if state.as_deref() != Some(expected_state) {
+                let _ = request.respond(Response::empty(400));
+                continue;
+            }
+
+            let code = if let Some(error) = error {
+                Err(format!("Authorization denied: {}", error))
+            } else {
+                code.ok_or_else(|| "No authorization code found.".to_string())
+            };
+
+            return Ok(Callback { request, code });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::mock_server;
+    use std::thread;
+
+    fn client(token_uri: &str) -> OAuthClient {
+        OAuthClient {
+            client_id: "client-1".into(),
+            client_secret: None,
+            auth_uri: "https://idp.example/authorize?prompt=consent".into(),
+            token_uri: token_uri.into(),
+            revocation_uri: None,
+        }
+    }
+
+    fn get(url: String) -> thread::JoinHandle<u16> {
+        thread::spawn(move || reqwest::blocking::get(url).unwrap().status().as_u16())
+    }
+
+    #[test]
+    fn authorize_url_carries_pkce_and_state() {
+        let pkce = Pkce::generate();
+        let url = client("https://idp.example/token")
+            .authorize_url(
+                "http://127.0.0.1:8080",
+                "openid email",
+                "state-1",
+                &pkce,
+                &[("nonce", "nonce-1")],
+            )
+            .unwrap();
+        let param = |name: &str| {
+            url.query_pairs()
+                .find(|(key, _)| key == name)
+                .map(|(_, value)| value.into_owned())
+        };
+
+        assert_eq!(param("prompt").as_deref(), Some("consent"));
+        assert_eq!(param("state").as_deref(), Some("state-1"));
+        assert_eq!(param("code_challenge"), Some(pkce.challenge.clone()));
+        assert_eq!(param("code_challenge_method").as_deref(), Some("S256"));
+        assert_eq!(param("nonce").as_deref(), Some("nonce-1"));
+        assert_eq!(
+            pkce.challenge,
+            general_purpose::URL_SAFE_NO_PAD.encode(Sha256::digest(pkce.verifier.as_bytes()))
+        );
+    }
+
+    #[test]
+    fn exchange_code_posts_the_verifier() {
+        let (base_url, server) = mock_server(
+            200,
+            "application/json",
+            r#"{"access_token":"at","refresh_token":"rt","expires_in":60}"#,
+        );
+
+        let token = client(&format!("{}/token", base_url))
+            .exchange_code("code-1", "verifier-1", "http://127.0.0.1:8080")
+            .unwrap();
+
+        assert_eq!(token.access_token, "at");
+        assert_eq!(token.refresh_token.as_deref(), Some("rt"));
+        let received = server.join().unwrap();
+        assert_eq!(received.url, "/token");
+        let form: Vec<(String, String)> = url::form_urlencoded::parse(received.raw.as_bytes())
+            .into_owned()
+            .collect();
+        for pair in [
+            ("grant_type", "authorization_code"),
+            ("code", "code-1"),
+            ("code_verifier", "verifier-1"),
+            ("client_id", "client-1"),
+        ] {
+            assert!(
+                form.contains(&(pair.0.to_string(), pair.1.to_string())),
+                "{:?}",
+                form
+            );
+        }
+    }
+
+    #[test]
+    fn waits_past_a_state_mismatch() {
+        let server = LoopbackServer::bind().unwrap();
+        let redirect_uri = server.redirect_uri();
+        let wrong = get(format!("{}/?state=other&code=stolen", redirect_uri));
+        let client = thread::spawn(move || {
+            let status = wrong.join().unwrap();
+            let right = get(format!("{}/?state=expected&code=code-1", redirect_uri));
+            (status, right.join().unwrap())
+        });
+
+        let callback = server
+            .wait_for_callback("expected", Duration::from_secs(10), &AtomicBool::new(false))
+            .unwrap();
+        assert_eq!(callback.code, Ok("code-1".to_string()));
+        callback.request.respond(Response::empty(200)).unwrap();
+        assert_eq!(client.join().unwrap(), (400, 200));
+    }
+
+    #[test]
+    fn stops_on_timeout_and_cancel() {
+        let server = LoopbackServer::bind().unwrap();
+
+        let started = Instant::now();
+        let err = server
+            .wait_for_callback("state", Duration::from_millis(50), &AtomicBool::new(false))
+            .err()
+            .unwrap();
+        assert_eq!(err, "Timed out waiting for the sign-in callback");
+        assert!(started.elapsed() < Duration::from_secs(5));
+
+        let err = server
+            .wait_for_callback("state", Duration::from_secs(60), &AtomicBool::new(true))
+            .err()
+            .unwrap();
+        assert_eq!(err, "Sign-in cancelled");
+    }
+}
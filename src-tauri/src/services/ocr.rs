@@ -0,0 +1,303 @@
+/*
+ * @license
+ * Copyright 2025 a7mddra
+ * SPDX-License-Identifier: Apache-2.0
+ */
+
+//! Offline OCR through the Tesseract command-line tool. Running the binary
+//! rather than linking libtesseract keeps it an optional runtime dependency:
+//! everything else works when it is not installed.
+
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::io::{ErrorKind, Write};
+use std::process::{Child, Command, Stdio};
+use tauri::{AppHandle, Manager};
+
+use crate::state::AppState;
+
+pub use spatialshot_core::config::pii::{validate_language, DEFAULT_LANGUAGE};
+
+/// Where Tesseract usually lives when it is not on the PATH of a GUI app.
+const FALLBACK_PATHS: &[&str] = &[
+    "/opt/homebrew/bin/tesseract",
+    "/usr/local/bin/tesseract",
+    "C:\\Program Files\\Tesseract-OCR\\tesseract.exe",
+];
+
+#[derive(Serialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct BoundingBox {
+    pub x: u32,
+    pub y: u32,
+    pub width: u32,
+    pub height: u32,
+}
+
+#[derive(Serialize, Clone, Debug, PartialEq)]
+pub struct OcrWord {
+    pub text: String,
+    pub confidence: f32,
+    pub bounds: BoundingBox,
+}
+
+/// One line of recognised text. `confidence` is the mean word confidence,
+/// from 0 to 1.
+#[derive(Serialize, Clone, Debug, PartialEq)]
+pub struct OcrBlock {
+    pub text: String,
+    pub confidence: f32,
+    pub bounds: BoundingBox,
+    pub words: Vec<OcrWord>,
+}
+
+#[derive(Serialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct OcrResult {
+    pub image_id: String,
+    pub language: String,
+    pub text: String,
+    pub blocks: Vec<OcrBlock>,
+}
+
+impl OcrResult {
+    pub fn new(image_id: &str, language: &str, blocks: Vec<OcrBlock>) -> Self {
+        Self {
+            image_id: image_id.to_string(),
+            language: language.to_string(),
+            text: blocks
+                .iter()
+                .map(|b| b.text.as_str())
+                .collect::<Vec<_>>()
+                .join("\n"),
+            blocks,
+        }
+    }
+
+    /// The recognised text framed for inclusion in a model prompt, or `None`
+    /// when nothing was found.
+    pub fn prompt_context(&self) -> Option<String> {
+        if self.text.trim().is_empty() {
+            return None;
+        }
+        Some(format!(
+            "Text recognised in the image by OCR (may contain errors):\n\"\"\"\n{}\n\"\"\"",
+            self.text
+        ))
+    }
+}
+
+/// Runs Tesseract on an encoded image and returns its text lines.
+pub fn recognize(bytes: &[u8], language: &str) -> Result<Vec<OcrBlock>, String> {
+    validate_language(language)?;
+    let input = tesseract_input(bytes)?;
+
+    let mut child = spawn_tesseract(language)?;
+    if let Some(mut stdin) = child.stdin.take() {
+        // A write error means Tesseract exited early; its stderr says why.
+        let _ = stdin.write_all(&input);
+    }
+    let output = child
+        .wait_with_output()
+        .map_err(|e| format!("Tesseract failed: {}", e))?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(format!("Tesseract failed: {}", stderr.trim()));
+    }
+
+    Ok(parse_tsv(&String::from_utf8_lossy(&output.stdout)))
+}
+
+fn spawn_tesseract(language: &str) -> Result<Child, String> {
+    for program in std::iter::once("tesseract").chain(FALLBACK_PATHS.iter().copied()) {
+        let spawned = Command::new(program)
+            .args(["stdin", "stdout", "-l", language, "tsv"])
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn();
+        match spawned {
+            Ok(child) => return Ok(child),
+            Err(e) if e.kind() == ErrorKind::NotFound => continue,
+            Err(e) => return Err(format!("Failed to start Tesseract: {}", e)),
+        }
+    }
+    Err("Tesseract is not installed. Install it to use offline OCR.".to_string())
+}
+
+/// Tesseract reads PNG, JPEG, TIFF and BMP everywhere; anything else is
+/// converted to PNG first.
+fn tesseract_input(bytes: &[u8]) -> Result<Vec<u8>, String> {
+    use image::ImageFormat;
+
+    let format = image::guess_format(bytes).map_err(|e| format!("Unsupported image: {}", e))?;
+    if matches!(
+        format,
+        ImageFormat::Png | ImageFormat::Jpeg | ImageFormat::Tiff | ImageFormat::Bmp
+    ) {
+        return Ok(bytes.to_vec());
+    }
+
+    let decoded =
+        image::load_from_memory(bytes).map_err(|e| format!("Failed to decode image: {}", e))?;
+    let mut png = Vec::new();
+    decoded
+        .write_to(&mut std::io::Cursor::new(&mut png), ImageFormat::Png)
+        .map_err(|e| format!("Failed to encode image: {}", e))?;
+    Ok(png)
+}
+
+/// Groups the word rows of Tesseract's TSV output into lines.
+fn parse_tsv(tsv: &str) -> Vec<OcrBlock> {
+    struct Line {
+        words: Vec<OcrWord>,
+        left: u32,
+        top: u32,
+        right: u32,
+        bottom: u32,
+    }
+
+    let mut lines: BTreeMap<(u32, u32, u32, u32), Line> = BTreeMap::new();
+    for row in tsv.lines().skip(1) {
+        let cols: Vec<&str> = row.splitn(12, '\t').collect();
+        if cols.len() < 12 || cols[0] != "5" {
+            continue;
+        }
+        let text = cols[11].trim();
+        let numbers: Option<Vec<u32>> = cols[1..10].iter().map(|c| c.parse().ok()).collect();
+        let (Some(n), Ok(confidence)) = (numbers, cols[10].parse::<f32>()) else {
+            continue;
+        };
+        if text.is_empty() || confidence < 0.0 {
+            continue;
+        }
+
+        let (left, top, width, height) = (n[5], n[6], n[7], n[8]);
+        let line = lines.entry((n[0], n[1], n[2], n[3])).or_insert(Line {
+            words: Vec::new(),
+            left,
+            top,
+            right: left + width,
+            bottom: top + height,
+        });
+        line.words.push(OcrWord {
+            text: text.to_string(),
+            confidence: confidence / 100.0,
+            bounds: BoundingBox {
+                x: left,
+                y: top,
+                width,
+                height,
+            },
+        });
+        line.left = line.left.min(left);
+        line.top = line.top.min(top);
+        line.right = line.right.max(left + width);
+        line.bottom = line.bottom.max(top + height);
+    }
+
+    lines
+        .into_values()
+        .map(|line| OcrBlock {
+            confidence: line.words.iter().map(|w| w.confidence).sum::<f32>()
+                / line.words.len() as f32,
+            text: line
+                .words
+                .iter()
+                .map(|w| w.text.as_str())
+                .collect::<Vec<_>>()
+                .join(" "),
+            bounds: BoundingBox {
+                x: line.left,
+                y: line.top,
+                width: line.right - line.left,
+                height: line.bottom - line.top,
+            },
+            words: line.words,
+        })
+        .collect()
+}
+
+/// Like [`recognize`], but remembers results per image and language for the
+/// rest of the session.
+pub fn recognize_cached(
+    app: &AppHandle,
+    image_id: &str,
+    bytes: &[u8],
+    language: &str,
+) -> Result<OcrResult, String> {
+    let state = app.state::<AppState>();
+    let key = (image_id.to_string(), language.to_string());
+    if let Some(result) = state.ocr_results.lock().get(&key) {
+        return Ok(result.clone());
+    }
+
+    let result = OcrResult::new(image_id, language, recognize(bytes, language)?);
+    state.ocr_results.lock().insert(key, result.clone());
+    Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TSV: &str = "level\tpage_num\tblock_num\tpar_num\tline_num\tword_num\tleft\ttop\twidth\theight\tconf\ttext
+1\t1\t0\t0\t0\t0\t0\t0\t400\t200\t-1\t
+2\t1\t1\t0\t0\t0\t10\t10\t200\t40\t-1\t
+4\t1\t1\t1\t1\t0\t10\t10\t200\t20\t-1\t
+5\t1\t1\t1\t1\t1\t10\t12\t80\t18\t96.5\tHello
+5\t1\t1\t1\t1\t2\t100\t10\t110\t20\t91.5\tworld
+5\t1\t1\t1\t1\t3\t220\t10\t5\t20\t95\t
+5\t1\t1\t1\t2\t1\t10\t40\t60\t20\t80\tsecond
+5\t1\t2\t1\t1\t1\t10\t120\t90\t30\t70\tfooter
+";
+
+    #[test]
+    fn groups_words_into_lines() {
+        let blocks = parse_tsv(TSV);
+        assert_eq!(blocks.len(), 3);
+
+        assert_eq!(blocks[0].text, "Hello world");
+        assert!((blocks[0].confidence - 0.94).abs() < 1e-6);
+        assert_eq!(
+            blocks[0].bounds,
+            BoundingBox {
+                x: 10,
+                y: 10,
+                width: 200,
+                height: 20
+            }
+        );
+        assert_eq!(blocks[0].words.len(), 2);
+        assert_eq!(
+            blocks[0].words[1].bounds,
+            BoundingBox {
+                x: 100,
+                y: 10,
+                width: 110,
+                height: 20
+            }
+        );
+        assert_eq!(blocks[1].text, "second");
+        assert_eq!(blocks[2].text, "footer");
+
+        let result = OcrResult::new("id", "eng", blocks);
+        assert_eq!(result.text, "Hello world\nsecond\nfooter");
+        assert!(result.prompt_context().unwrap().contains("Hello world"));
+    }
+
+    #[test]
+    fn empty_output_has_no_prompt_context() {
+        let result = OcrResult::new("id", "eng", parse_tsv(""));
+        assert!(result.blocks.is_empty());
+        assert_eq!(result.prompt_context(), None);
+    }
+
+    #[test]
+    fn rejects_option_like_languages() {
+        assert!(validate_language("eng+deu").is_ok());
+        assert!(validate_language("chi_sim").is_ok());
+        assert!(validate_language("--psm").is_err());
+        assert!(validate_language("").is_err());
+    }
+}
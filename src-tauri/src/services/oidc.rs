@@ -0,0 +1,423 @@
+/*
+ * @license
+ * Copyright 2025 a7mddra
+ * SPDX-License-Identifier: Apache-2.0
+ */
+
+use base64::{engine::general_purpose, Engine as _};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs::{self, File};
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+use url::Url;
+
+use crate::services::oauth::OAuthClient;
+
+const SECRETS_JSON: &str = include_str!("../data/credentials.json");
+
+pub const GOOGLE_PROVIDER_ID: &str = "google";
+const GOOGLE_ISSUER: &str = "https://accounts.google.com";
+
+const PROVIDERS_FILE: &str = "auth_providers.json";
+const DEFAULT_SCOPES: &str = "openid profile email";
+
+#[derive(Deserialize, Debug)]
+struct GoogleCredentials {
+    installed: Option<GoogleClientConfig>,
+    web: Option<GoogleClientConfig>,
+}
+
+#[derive(Deserialize, Debug)]
+struct GoogleClientConfig {
+    client_id: String,
+    #[serde(default)]
+    client_secret: Option<String>,
+}
+
+/// An OpenID Connect identity provider the user can sign in with.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ProviderConfig {
+    pub id: String,
+    pub name: String,
+    pub issuer: String,
+    pub client_id: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub client_secret: Option<String>,
+    #[serde(default = "default_scopes")]
+    pub scopes: String,
+    /// Extra authorization request parameters, e.g. Google's `access_type`.
+    #[serde(default)]
+    pub auth_params: BTreeMap<String, String>,
+}
+
+fn default_scopes() -> String {
+    DEFAULT_SCOPES.to_string()
+}
+
+/// The subset of `.well-known/openid-configuration` the client relies on.
+#[derive(Deserialize, Debug, Clone)]
+pub struct Discovery {
+    pub issuer: String,
+    pub authorization_endpoint: String,
+    pub token_endpoint: String,
+    #[serde(default)]
+    pub revocation_endpoint: Option<String>,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+#[serde(untagged)]
+enum Audience {
+    One(String),
+    Many(Vec<String>),
+}
+
+impl Audience {
+    fn contains(&self, client_id: &str) -> bool {
+        match self {
+            Audience::One(aud) => aud == client_id,
+            Audience::Many(auds) => auds.iter().any(|aud| aud == client_id),
+        }
+    }
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct IdTokenClaims {
+    pub iss: String,
+    pub sub: String,
+    aud: Audience,
+    pub exp: u64,
+    #[serde(default)]
+    pub nonce: Option<String>,
+    #[serde(default)]
+    pub email: Option<String>,
+    #[serde(default)]
+    pub name: Option<String>,
+    #[serde(default)]
+    pub preferred_username: Option<String>,
+    #[serde(default)]
+    pub picture: Option<String>,
+}
+
+fn google_preset() -> Option<ProviderConfig> {
+    let wrapper: GoogleCredentials = serde_json::from_str(SECRETS_JSON)
+        .map_err(|e| eprintln!("Failed to parse credentials.json: {}", e))
+        .ok()?;
+    let secrets = wrapper.installed.or(wrapper.web)?;
+
+    Some(ProviderConfig {
+        id: GOOGLE_PROVIDER_ID.to_string(),
+        name: "Google".to_string(),
+        issuer: GOOGLE_ISSUER.to_string(),
+        client_id: secrets.client_id,
+        client_secret: secrets.client_secret,
+        scopes: DEFAULT_SCOPES.to_string(),
+        auth_params: BTreeMap::from([
+            ("access_type".to_string(), "offline".to_string()),
+            ("prompt".to_string(), "consent".to_string()),
+        ]),
+    })
+}
+
+/// Returns the built-in presets followed by the user's providers from
+/// `auth_providers.json`. A user entry replaces a preset with the same id.
+pub fn load_providers(config_dir: &Path) -> Vec<ProviderConfig> {
+    let mut providers: Vec<ProviderConfig> = google_preset().into_iter().collect();
+
+    for provider in load_custom_providers(config_dir) {
+        providers.retain(|p| p.id != provider.id);
+        providers.push(provider);
+    }
+
+    providers
+}
+
+pub fn find_provider(config_dir: &Path, id: &str) -> Result<ProviderConfig, String> {
+    load_providers(config_dir)
+        .into_iter()
+        .find(|p| p.id == id)
+        .ok_or_else(|| format!("Unknown identity provider: {}", id))
+}
+
+fn load_custom_providers(config_dir: &Path) -> Vec<ProviderConfig> {
+    File::open(config_dir.join(PROVIDERS_FILE))
+        .ok()
+        .and_then(|file| serde_json::from_reader(file).ok())
+        .unwrap_or_default()
+}
+
+fn write_custom_providers(config_dir: &Path, providers: &[ProviderConfig]) -> Result<(), String> {
+    fs::create_dir_all(config_dir).map_err(|e| e.to_string())?;
+    let file = File::create(config_dir.join(PROVIDERS_FILE)).map_err(|e| e.to_string())?;
+    serde_json::to_writer_pretty(file, providers).map_err(|e| e.to_string())
+}
+
+pub fn save_provider(config_dir: &Path, provider: ProviderConfig) -> Result<(), String> {
+    if provider.id.trim().is_empty() {
+        return Err("Provider id must not be empty".into());
+    }
+    check_issuer(&provider.issuer)?;
+
+    let mut providers = load_custom_providers(config_dir);
+    providers.retain(|p| p.id != provider.id);
+    providers.push(provider);
+    write_custom_providers(config_dir, &providers)
+}
+
+/// Issuers must use https, except for a provider on this machine.
+fn check_issuer(issuer: &str) -> Result<(), String> {
+    let url = Url::parse(issuer).map_err(|e| format!("Invalid provider issuer: {}", e))?;
+    let local = matches!(url.host_str(), Some("127.0.0.1" | "localhost"));
+    match url.scheme() {
+        "https" => Ok(()),
+        "http" if local => Ok(()),
+        _ => Err("Provider issuer must be an https URL, or http on 127.0.0.1 or localhost".into()),
+    }
+}
+
+pub fn remove_provider(config_dir: &Path, id: &str) -> Result<(), String> {
+    let mut providers = load_custom_providers(config_dir);
+    providers.retain(|p| p.id != id);
+    write_custom_providers(config_dir, &providers)
+}
+
+fn normalize_issuer(issuer: &str) -> &str {
+    issuer.trim_end_matches('/')
+}
+
+pub fn discover(issuer: &str) -> Result<Discovery, String> {
+    let url = format!(
+        "{}/.well-known/openid-configuration",
+        normalize_issuer(issuer)
+    );
+
+    let discovery: Discovery = reqwest::blocking::get(&url)
+        .and_then(|res| res.error_for_status())
+        .map_err(|e| format!("OIDC discovery failed: {}", e))?
+        .json()
+        .map_err(|e| format!("Invalid OIDC discovery document: {}", e))?;
+
+    if normalize_issuer(&discovery.issuer) != normalize_issuer(issuer) {
+        return Err(format!(
+            "OIDC discovery issuer mismatch: expected {}, got {}",
+            issuer, discovery.issuer
+        ));
+    }
+
+    Ok(discovery)
+}
+
+pub struct OidcClient {
+    pub provider: ProviderConfig,
+    pub discovery: Discovery,
+}
+
+impl OidcClient {
+    pub fn discover(provider: ProviderConfig) -> Result<Self, String> {
+        let discovery = discover(&provider.issuer)?;
+
+        Ok(Self {
+            provider,
+            discovery,
+        })
+    }
+
+    pub fn oauth(&self) -> OAuthClient {
+        OAuthClient {
+            client_id: self.provider.client_id.clone(),
+            client_secret: self.provider.client_secret.clone(),
+            auth_uri: self.discovery.authorization_endpoint.clone(),
+            token_uri: self.discovery.token_endpoint.clone(),
+            revocation_uri: self.discovery.revocation_endpoint.clone(),
+        }
+    }
+
+    /// Decodes and validates ID token claims. The token comes straight from
+    /// the token endpoint over TLS, so per OIDC Core §3.1.3.7 the server's
+    /// certificate stands in for checking the JWS signature.
+    pub fn validate_id_token(
+        &self,
+        id_token: &str,
+        nonce: Option<&str>,
+    ) -> Result<IdTokenClaims, String> {
+        let payload = id_token.split('.').nth(1).ok_or("Malformed ID token")?;
+        let bytes = general_purpose::URL_SAFE_NO_PAD
+            .decode(payload.trim_end_matches('='))
+            .map_err(|e| format!("Malformed ID token: {}", e))?;
+        let claims: IdTokenClaims = serde_json::from_slice(&bytes)
+            .map_err(|e| format!("Invalid ID token claims: {}", e))?;
+
+        if normalize_issuer(&claims.iss) != normalize_issuer(&self.discovery.issuer) {
+            return Err("ID token issuer mismatch".into());
+        }
+        if !claims.aud.contains(&self.provider.client_id) {
+            return Err("ID token audience mismatch".into());
+        }
+
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or(0);
+        if claims.exp <= now {
+            return Err("ID token has expired".into());
+        }
+
+        if let Some(expected) = nonce {
+            if claims.nonce.as_deref() != Some(expected) {
+                return Err("ID token nonce mismatch".into());
+            }
+        }
+
+        Ok(claims)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::mock_server_with;
+    use serde_json::{json, Value};
+
+    const CLIENT_ID: &str = "client-1";
+
+    fn provider(issuer: &str) -> ProviderConfig {
+        ProviderConfig {
+            id: "test".into(),
+            name: "Test".into(),
+            issuer: issuer.into(),
+            client_id: CLIENT_ID.into(),
+            client_secret: None,
+            scopes: default_scopes(),
+            auth_params: BTreeMap::new(),
+        }
+    }
+
+    fn discovery_json(issuer: &str, base_url: &str) -> String {
+        json!({
+            "issuer": issuer,
+            "authorization_endpoint": format!("{}/authorize", base_url),
+            "token_endpoint": format!("{}/token", base_url),
+        })
+        .to_string()
+    }
+
+    fn now() -> u64 {
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_secs()
+    }
+
+    fn claims(issuer: &str) -> Value {
+        json!({
+            "iss": issuer,
+            "sub": "user-1",
+            "aud": [CLIENT_ID, "other"],
+            "exp": now() + 600,
+            "nonce": "nonce-1",
+            "email": "user@example.com",
+        })
+    }
+
+    /// An unsigned JWT; signatures are not checked.
+    fn id_token(claims: &Value) -> String {
+        let encode = |value: &Value| general_purpose::URL_SAFE_NO_PAD.encode(value.to_string());
+        format!("{}.{}.", encode(&json!({ "alg": "none" })), encode(claims))
+    }
+
+    #[test]
+    fn discovers_and_validates_an_id_token() {
+        let (base_url, server) = mock_server_with(|base_url| {
+            let token = json!({
+                "access_token": "at",
+                "id_token": id_token(&claims(base_url)),
+            });
+            vec![
+                (200, "application/json", discovery_json(base_url, base_url)),
+                (200, "application/json", token.to_string()),
+            ]
+        });
+
+        let client = OidcClient::discover(provider(&format!("{}/", base_url))).unwrap();
+        assert_eq!(client.oauth().token_uri, format!("{}/token", base_url));
+        let token = client
+            .oauth()
+            .exchange_code("code-1", "verifier-1", "http://127.0.0.1:1")
+            .unwrap();
+        let claims = client
+            .validate_id_token(token.id_token.as_deref().unwrap(), Some("nonce-1"))
+            .unwrap();
+
+        assert_eq!(claims.sub, "user-1");
+        assert_eq!(claims.email.as_deref(), Some("user@example.com"));
+        let received = server.join().unwrap();
+        assert_eq!(received[0].url, "/.well-known/openid-configuration");
+        assert_eq!(received[1].url, "/token");
+    }
+
+    #[test]
+    fn discovery_rejects_another_issuer() {
+        let (base_url, server) = mock_server_with(|base_url| {
+            let document = discovery_json("https://evil.example", base_url);
+            vec![(200, "application/json", document)]
+        });
+
+        let err = OidcClient::discover(provider(&base_url)).err().unwrap();
+        assert!(err.starts_with("OIDC discovery issuer mismatch"), "{}", err);
+        server.join().unwrap();
+    }
+
+    #[test]
+    fn rejects_bad_id_token_claims() {
+        let issuer = "https://idp.example";
+        let client = OidcClient {
+            provider: provider(issuer),
+            discovery: serde_json::from_str(&discovery_json(issuer, issuer)).unwrap(),
+        };
+        let check = |changes: Value, nonce: &str| {
+            let mut claims = claims(issuer);
+            for (key, value) in changes.as_object().unwrap() {
+                claims[key] = value.clone();
+            }
+            client
+                .validate_id_token(&id_token(&claims), Some(nonce))
+                .map(|claims| claims.sub)
+        };
+
+        assert_eq!(check(json!({}), "nonce-1"), Ok("user-1".to_string()));
+        assert_eq!(
+            check(json!({ "aud": CLIENT_ID }), "nonce-1"),
+            Ok("user-1".to_string())
+        );
+        assert_eq!(
+            check(json!({ "iss": "https://evil.example" }), "nonce-1"),
+            Err("ID token issuer mismatch".to_string())
+        );
+        assert_eq!(
+            check(json!({ "aud": "someone-else" }), "nonce-1"),
+            Err("ID token audience mismatch".to_string())
+        );
+        assert_eq!(
+            check(json!({ "exp": now() - 1 }), "nonce-1"),
+            Err("ID token has expired".to_string())
+        );
+        assert_eq!(
+            check(json!({}), "nonce-2"),
+            Err("ID token nonce mismatch".to_string())
+        );
+        assert!(client.validate_id_token("not-a-jwt", None).is_err());
+    }
+
+    #[test]
+    fn issuers_need_https_unless_local() {
+        assert!(check_issuer("https://accounts.google.com").is_ok());
+        assert!(check_issuer("http://127.0.0.1:8080/realms/dev").is_ok());
+        assert!(check_issuer("http://localhost:8080").is_ok());
+        assert!(check_issuer("http://127.0.0.1.evil.com").is_err());
+        assert!(check_issuer("http://localhost.evil.com").is_err());
+        assert!(check_issuer("http://example.com").is_err());
+        assert!(check_issuer("ftp://127.0.0.1").is_err());
+        assert!(check_issuer("accounts.google.com").is_err());
+    }
+}
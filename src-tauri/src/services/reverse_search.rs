@@ -0,0 +1,235 @@
+/*
+ * @license
+ * Copyright 2025 a7mddra
+ * SPDX-License-Identifier: Apache-2.0
+ */
+
+use serde::Serialize;
+use url::Url;
+
+pub use spatialshot_core::config::search::{SearchOptions, DEFAULT_ENGINE};
+
+/// A reverse image search site that can look up an image by its URL.
+pub struct SearchEngine {
+    pub id: &'static str,
+    pub name: &'static str,
+    base_url: &'static str,
+    /// Adds the image URL and locale to the engine's query.
+    params: fn(&str, &Locale) -> Vec<(&'static str, String)>,
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub struct EngineInfo {
+    pub id: &'static str,
+    pub name: &'static str,
+}
+
+pub const ENGINES: &[SearchEngine] = &[
+    SearchEngine {
+        id: "lens",
+        name: "Google Lens",
+        base_url: "https://lens.google.com/uploadbyurl",
+        params: |image_url, locale| {
+            vec![
+                ("url", image_url.to_string()),
+                ("ep", "subb".to_string()),
+                ("re", "df".to_string()),
+                ("s", "4".to_string()),
+                ("hl", locale.language.clone()),
+                ("gl", locale.region.clone()),
+            ]
+        },
+    },
+    SearchEngine {
+        id: "bing",
+        name: "Bing Visual Search",
+        base_url: "https://www.bing.com/images/search",
+        params: |image_url, locale| {
+            vec![
+                ("view", "detailv2".to_string()),
+                ("iss", "sbi".to_string()),
+                ("q", format!("imgurl:{}", image_url)),
+                ("setlang", locale.language.clone()),
+                ("cc", locale.region.clone()),
+            ]
+        },
+    },
+    SearchEngine {
+        id: "yandex",
+        name: "Yandex",
+        base_url: "https://yandex.com/images/search",
+        params: |image_url, locale| {
+            vec![
+                ("rpt", "imageview".to_string()),
+                ("url", image_url.to_string()),
+                ("lang", locale.language.clone()),
+            ]
+        },
+    },
+    SearchEngine {
+        id: "tineye",
+        name: "TinEye",
+        base_url: "https://tineye.com/search",
+        params: |image_url, _| vec![("url", image_url.to_string())],
+    },
+    SearchEngine {
+        id: "saucenao",
+        name: "SauceNAO",
+        base_url: "https://saucenao.com/search.php",
+        params: |image_url, _| vec![("url", image_url.to_string())],
+    },
+];
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Locale {
+    /// ISO 639-1, e.g. `en`.
+    pub language: String,
+    /// ISO 3166-1 alpha-2, e.g. `US`.
+    pub region: String,
+}
+
+/// The configured locale, filling gaps from the system locale.
+pub fn locale(options: &SearchOptions) -> Locale {
+    let system = system_locale();
+    Locale {
+        language: options.language.clone().unwrap_or(system.language),
+        region: options.region.clone().unwrap_or(system.region),
+    }
+}
+
+pub fn engines() -> Vec<EngineInfo> {
+    ENGINES
+        .iter()
+        .map(|engine| EngineInfo {
+            id: engine.id,
+            name: engine.name,
+        })
+        .collect()
+}
+
+pub fn find_engine(id: &str) -> Result<&'static SearchEngine, String> {
+    ENGINES
+        .iter()
+        .find(|engine| engine.id == id)
+        .ok_or_else(|| format!("Unknown search engine '{}'", id))
+}
+
+pub fn search_url(engine_id: &str, image_url: &str, locale: &Locale) -> Result<String, String> {
+    let engine = find_engine(engine_id)?;
+
+    let mut url = Url::parse(engine.base_url).expect("valid engine URL");
+    url.query_pairs_mut()
+        .extend_pairs((engine.params)(image_url, locale));
+    Ok(url.into())
+}
+
+/// Reads `LC_ALL`, `LC_MESSAGES` or `LANG` (e.g. `de_AT.UTF-8`), falling
+/// back to `en`/`US`.
+fn system_locale() -> Locale {
+    let value = ["LC_ALL", "LC_MESSAGES", "LANG"]
+        .iter()
+        .filter_map(|name| std::env::var(name).ok())
+        .find(|value| !value.is_empty());
+    parse_locale(value.as_deref().unwrap_or_default())
+}
+
+fn parse_locale(value: &str) -> Locale {
+    let tag = value.split(['.', '@']).next().unwrap_or_default();
+    let mut parts = tag.split(['_', '-']);
+    let language = parts
+        .next()
+        .filter(|l| l.len() == 2 && l.chars().all(|c| c.is_ascii_alphabetic()))
+        .map(str::to_ascii_lowercase);
+    let region = parts
+        .next()
+        .filter(|r| r.len() == 2 && r.chars().all(|c| c.is_ascii_alphabetic()))
+        .map(str::to_ascii_uppercase);
+
+    match language {
+        Some(language) => Locale {
+            region: region.unwrap_or_else(|| "US".to_string()),
+            language,
+        },
+        None => Locale {
+            language: "en".to_string(),
+            region: "US".to_string(),
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn locale() -> Locale {
+        Locale {
+            language: "de".into(),
+            region: "AT".into(),
+        }
+    }
+
+    #[test]
+    fn builds_each_engine_url() {
+        let image_url = "https://i.ibb.co/abc/shot.png?x=1&y=2";
+        let urls: Vec<String> = ENGINES
+            .iter()
+            .map(|engine| search_url(engine.id, image_url, &locale()).unwrap())
+            .collect();
+        let encoded = "https%3A%2F%2Fi.ibb.co%2Fabc%2Fshot.png%3Fx%3D1%26y%3D2";
+
+        assert_eq!(
+            urls,
+            vec![
+                format!("https://lens.google.com/uploadbyurl?url={}&ep=subb&re=df&s=4&hl=de&gl=AT", encoded),
+                format!("https://www.bing.com/images/search?view=detailv2&iss=sbi&q=imgurl%3A{}&setlang=de&cc=AT", encoded),
+                format!("https://yandex.com/images/search?rpt=imageview&url={}&lang=de", encoded),
+                format!("https://tineye.com/search?url={}", encoded),
+                format!("https://saucenao.com/search.php?url={}", encoded),
+            ]
+        );
+        assert!(search_url("altavista", image_url, &locale()).is_err());
+    }
+
+    #[test]
+    fn parses_system_locales() {
+        assert_eq!(parse_locale("de_AT.UTF-8"), locale());
+        assert_eq!(
+            parse_locale("fr"),
+            Locale {
+                language: "fr".into(),
+                region: "US".into()
+            }
+        );
+        assert_eq!(
+            parse_locale("C.UTF-8"),
+            Locale {
+                language: "en".into(),
+                region: "US".into()
+            }
+        );
+        assert_eq!(
+            parse_locale(""),
+            Locale {
+                language: "en".into(),
+                region: "US".into()
+            }
+        );
+    }
+
+    #[test]
+    fn options_override_the_system_locale() {
+        let options = SearchOptions {
+            engine: None,
+            language: Some("ja".into()),
+            region: Some("JP".into()),
+        };
+        assert_eq!(options.engine(), "lens");
+        assert_eq!(
+            super::locale(&options),
+            Locale {
+                language: "ja".into(),
+                region: "JP".into()
+            }
+        );
+    }
+}
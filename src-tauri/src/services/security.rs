@@ -11,8 +11,11 @@ use aes_gcm::{
 };
 use base64::{engine::general_purpose, Engine as _};
 use pbkdf2::pbkdf2;
+use rand::{rngs::OsRng, RngCore};
 use sha2::{Digest, Sha256};
-use std::fs;
+use std::fs::{self, File};
+use std::io::Write;
+use std::path::PathBuf;
 use tauri::AppHandle;
 
 pub fn get_stable_passphrase() -> String {
@@ -30,9 +33,58 @@ pub fn derive_key(passphrase: &str, salt: &[u8]) -> [u8; 32] {
     key
 }
 
+pub fn encrypt_and_store(
+    app: &AppHandle,
+    file_name: &str,
+    plaintext: &str,
+) -> Result<PathBuf, String> {
+    let passphrase = get_stable_passphrase();
+    let mut salt = [0u8; 16];
+    let mut iv = [0u8; 12];
+    OsRng.fill_bytes(&mut salt);
+    OsRng.fill_bytes(&mut iv);
+
+    let key_bytes = derive_key(&passphrase, &salt);
+    let key = Key::<Aes256Gcm>::from_slice(&key_bytes);
+    let cipher = Aes256Gcm::new(key);
+    let nonce = Nonce::from_slice(&iv);
+
+    let encrypted_data = cipher
+        .encrypt(nonce, plaintext.as_bytes())
+        .map_err(|e| format!("Encryption failed: {}", e))?;
+
+    let (ciphertext, tag) = encrypted_data.split_at(encrypted_data.len() - 16);
+
+    let payload = serde_json::json!({
+        "version": 1,
+        "algo": "aes-256-gcm",
+        "salt": general_purpose::STANDARD.encode(salt),
+        "iv": general_purpose::STANDARD.encode(iv),
+        "tag": general_purpose::STANDARD.encode(tag),
+        "ciphertext": general_purpose::STANDARD.encode(ciphertext)
+    });
+
+    let config_dir = get_app_config_dir(app);
+    if !config_dir.exists() {
+        fs::create_dir_all(&config_dir).map_err(|e| e.to_string())?;
+    }
+
+    let file_path = config_dir.join(file_name);
+    let mut file = File::create(&file_path).map_err(|e| e.to_string())?;
+
+    file.write_all(serde_json::to_string_pretty(&payload).unwrap().as_bytes())
+        .map_err(|e| e.to_string())?;
+
+    Ok(file_path)
+}
+
 pub fn get_decrypted_key_internal(app: &AppHandle, provider: &str) -> Option<String> {
+    load_decrypted(app, &format!("{}_key.json", provider))
+}
+
+pub fn load_decrypted(app: &AppHandle, file_name: &str) -> Option<String> {
     let config_dir = get_app_config_dir(app);
-    let file_path = config_dir.join(format!("{}_key.json", provider));
+    let file_path = config_dir.join(file_name);
 
     if !file_path.exists() {
         return None;
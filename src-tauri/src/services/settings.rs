@@ -0,0 +1,50 @@
+/*
+ * @license
+ * Copyright 2025 a7mddra
+ * SPDX-License-Identifier: Apache-2.0
+ */
+
+//! The settings schema and file live in `spatialshot_core::config`, shared
+//! with the daemon. This adds the checks that need the app's registries.
+
+use serde_json::Value;
+use std::path::Path;
+
+use crate::services::reverse_search;
+use spatialshot_core::config;
+
+pub use spatialshot_core::config::{save, Settings};
+
+pub fn load(dir: &Path) -> Result<Settings, String> {
+    let settings = config::load(dir)?;
+    validate(&settings)?;
+    Ok(settings)
+}
+
+/// `Settings::patched`, also rejecting values only the app can check.
+pub fn patched(current: &Settings, patch: Value) -> Result<Settings, String> {
+    let settings = current.patched(patch)?;
+    validate(&settings)?;
+    Ok(settings)
+}
+
+fn validate(settings: &Settings) -> Result<(), String> {
+    reverse_search::find_engine(settings.reverse_search.engine()).map(|_| ())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn rejects_unknown_search_engines() {
+        let settings = Settings::default();
+        assert!(patched(&settings, json!({ "reverseSearch": { "engine": "bing" } })).is_ok());
+        assert!(patched(
+            &settings,
+            json!({ "reverseSearch": { "engine": "altavista" } })
+        )
+        .is_err());
+    }
+}
@@ -0,0 +1,326 @@
+/*
+ * @license
+ * Copyright 2025 a7mddra
+ * SPDX-License-Identifier: Apache-2.0
+ */
+
+//! Named prompt templates, stored as `prompt_templates.json` in the config
+//! dir. A template's prompt and system instruction may use `{{variable}}`
+//! placeholders, which are filled in when a chat starts from it.
+
+use parking_lot::Mutex;
+use rand::{rngs::OsRng, RngCore};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeSet, HashMap};
+use std::fs;
+use std::path::Path;
+use std::sync::OnceLock;
+
+pub const TEMPLATES_FILE: &str = "prompt_templates.json";
+pub const LIBRARY_VERSION: u32 = 1;
+
+/// Text found in the image by offline OCR.
+pub const OCR_TEXT: &str = "ocr_text";
+/// The name of this app.
+pub const APP_NAME: &str = "app_name";
+/// Today's local date, as `YYYY-MM-DD`.
+pub const DATE: &str = "date";
+/// Text on the clipboard.
+pub const CLIPBOARD: &str = "clipboard";
+pub const VARIABLES: &[&str] = &[OCR_TEXT, APP_NAME, DATE, CLIPBOARD];
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct PromptTemplate {
+    /// Assigned when the template is first saved.
+    #[serde(default)]
+    pub id: String,
+    pub name: String,
+    pub prompt: String,
+    #[serde(default)]
+    pub system_instruction: Option<String>,
+    /// Provider and model to use instead of the chat's defaults.
+    #[serde(default)]
+    pub provider: Option<String>,
+    #[serde(default)]
+    pub model: Option<String>,
+}
+
+/// A template with its variables filled in.
+#[derive(Serialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct RenderedPrompt {
+    pub prompt: String,
+    pub system_instruction: Option<String>,
+    pub provider: Option<String>,
+    pub model: Option<String>,
+}
+
+/// The file format, shared by the library and exported files.
+#[derive(Serialize, Deserialize)]
+struct Library {
+    version: u32,
+    templates: Vec<PromptTemplate>,
+}
+
+impl PromptTemplate {
+    /// The variables used by the prompt and system instruction.
+    pub fn variables(&self) -> BTreeSet<String> {
+        let texts = std::iter::once(&self.prompt).chain(&self.system_instruction);
+        texts
+            .flat_map(|text| placeholder().captures_iter(text))
+            .map(|captures| captures[1].to_string())
+            .collect()
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        if self.name.trim().is_empty() {
+            return Err("A template needs a name".into());
+        }
+        if self.prompt.trim().is_empty() {
+            return Err(format!("Template {} has an empty prompt", self.name));
+        }
+        let unknown: Vec<String> = self
+            .variables()
+            .into_iter()
+            .filter(|name| !VARIABLES.contains(&name.as_str()))
+            .collect();
+        if !unknown.is_empty() {
+            return Err(format!(
+                "Template {} uses unknown variables: {}. Available: {}",
+                self.name,
+                unknown.join(", "),
+                VARIABLES.join(", ")
+            ));
+        }
+        Ok(())
+    }
+
+    /// Fills in the variables from `values`. Variables without a value are
+    /// left empty.
+    pub fn render(&self, values: &HashMap<&str, String>) -> RenderedPrompt {
+        RenderedPrompt {
+            prompt: render(&self.prompt, values),
+            system_instruction: self
+                .system_instruction
+                .as_deref()
+                .map(|text| render(text, values))
+                .filter(|text| !text.trim().is_empty()),
+            provider: self.provider.clone().filter(|p| !p.is_empty()),
+            model: self.model.clone().filter(|m| !m.is_empty()),
+        }
+    }
+}
+
+/// `{{ name }}`, with optional spaces inside the braces.
+fn placeholder() -> &'static Regex {
+    static PLACEHOLDER: OnceLock<Regex> = OnceLock::new();
+    PLACEHOLDER.get_or_init(|| Regex::new(r"\{\{\s*([A-Za-z0-9_]+)\s*\}\}").unwrap())
+}
+
+fn render(text: &str, values: &HashMap<&str, String>) -> String {
+    placeholder()
+        .replace_all(text, |captures: &regex::Captures| {
+            values.get(&captures[1]).cloned().unwrap_or_default()
+        })
+        .into_owned()
+}
+
+/// Serializes read-modify-write cycles on the library file.
+static LIBRARY_LOCK: Mutex<()> = Mutex::new(());
+
+/// The saved templates, or none if the library was never written.
+pub fn load(dir: &Path) -> Result<Vec<PromptTemplate>, String> {
+    match fs::read_to_string(dir.join(TEMPLATES_FILE)) {
+        Ok(json) => parse(&json),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Vec::new()),
+        Err(e) => Err(format!("Failed to read templates: {}", e)),
+    }
+}
+
+pub fn find(dir: &Path, id: &str) -> Result<PromptTemplate, String> {
+    load(dir)?
+        .into_iter()
+        .find(|template| template.id == id)
+        .ok_or_else(|| format!("Unknown template: {}", id))
+}
+
+/// Loads the library, applies `f` and saves the result.
+pub fn update<T>(
+    dir: &Path,
+    f: impl FnOnce(&mut Vec<PromptTemplate>) -> Result<T, String>,
+) -> Result<T, String> {
+    let _guard = LIBRARY_LOCK.lock();
+    let mut templates = load(dir)?;
+    let result = f(&mut templates)?;
+    save(dir, &templates)?;
+    Ok(result)
+}
+
+fn save(dir: &Path, templates: &[PromptTemplate]) -> Result<(), String> {
+    fs::create_dir_all(dir).map_err(|e| format!("Failed to write templates: {}", e))?;
+    let json = export(templates)?;
+    let tmp = dir.join(format!("{}.tmp", TEMPLATES_FILE));
+    fs::write(&tmp, json).map_err(|e| format!("Failed to write templates: {}", e))?;
+    fs::rename(&tmp, dir.join(TEMPLATES_FILE))
+        .map_err(|e| format!("Failed to write templates: {}", e))
+}
+
+/// Adds `template`, or replaces the one with its id. A template without an
+/// id gets a new one.
+pub fn upsert(
+    templates: &mut Vec<PromptTemplate>,
+    mut template: PromptTemplate,
+) -> Result<PromptTemplate, String> {
+    template.validate()?;
+    if template.id.is_empty() {
+        let mut id_bytes = [0u8; 8];
+        OsRng.fill_bytes(&mut id_bytes);
+        template.id = hex::encode(id_bytes);
+    }
+
+    match templates.iter_mut().find(|t| t.id == template.id) {
+        Some(existing) => *existing = template.clone(),
+        None => templates.push(template.clone()),
+    }
+    Ok(template)
+}
+
+pub fn remove(templates: &mut Vec<PromptTemplate>, id: &str) -> Result<(), String> {
+    let before = templates.len();
+    templates.retain(|template| template.id != id);
+    if templates.len() == before {
+        return Err(format!("Unknown template: {}", id));
+    }
+    Ok(())
+}
+
+pub fn export(templates: &[PromptTemplate]) -> Result<String, String> {
+    serde_json::to_string_pretty(&Library {
+        version: LIBRARY_VERSION,
+        templates: templates.to_vec(),
+    })
+    .map_err(|e| e.to_string())
+}
+
+/// Merges templates from an exported file. Ones with an id already in the
+/// library replace it. Nothing is added unless every template is valid.
+pub fn import(templates: &mut Vec<PromptTemplate>, json: &str) -> Result<usize, String> {
+    let imported = parse(json)?;
+    for template in &imported {
+        template.validate()?;
+    }
+    let count = imported.len();
+    for template in imported {
+        upsert(templates, template)?;
+    }
+    Ok(count)
+}
+
+/// Reads the library format, or a bare list of templates.
+fn parse(json: &str) -> Result<Vec<PromptTemplate>, String> {
+    let invalid = |e: serde_json::Error| format!("Invalid templates file: {}", e);
+    if json.trim_start().starts_with('[') {
+        return serde_json::from_str(json).map_err(invalid);
+    }
+
+    let library: Library = serde_json::from_str(json).map_err(invalid)?;
+    if library.version > LIBRARY_VERSION {
+        return Err(format!(
+            "Templates file version {} is newer than this app supports ({})",
+            library.version, LIBRARY_VERSION
+        ));
+    }
+    Ok(library.templates)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn template(name: &str, prompt: &str) -> PromptTemplate {
+        PromptTemplate {
+            name: name.into(),
+            prompt: prompt.into(),
+            ..PromptTemplate::default()
+        }
+    }
+
+    #[test]
+    fn renders_variables() {
+        let template = PromptTemplate {
+            system_instruction: Some("You help {{ app_name }} users.".into()),
+            model: Some("gemini-2.5-pro".into()),
+            ..template("Fix", "On {{date}}, explain:\n{{ocr_text}}\n{{clipboard}}")
+        };
+        let values = HashMap::from([
+            (DATE, "2025-06-01".to_string()),
+            (OCR_TEXT, "error: E0382".to_string()),
+            (APP_NAME, "spatialshot".to_string()),
+        ]);
+
+        assert_eq!(
+            template.variables().into_iter().collect::<Vec<_>>(),
+            ["app_name", "clipboard", "date", "ocr_text"]
+        );
+        assert_eq!(
+            template.render(&values),
+            RenderedPrompt {
+                prompt: "On 2025-06-01, explain:\nerror: E0382\n".into(),
+                system_instruction: Some("You help spatialshot users.".into()),
+                provider: None,
+                model: Some("gemini-2.5-pro".into()),
+            }
+        );
+    }
+
+    #[test]
+    fn rejects_unknown_variables_and_empty_fields() {
+        assert!(template("Ok", "Read {{ocr_text}}").validate().is_ok());
+        assert!(template("Bad", "Hi {{username}}").validate().is_err());
+        assert!(template(" ", "Describe").validate().is_err());
+        assert!(template("Empty", "").validate().is_err());
+    }
+
+    #[test]
+    fn saves_imports_and_exports() {
+        let dir =
+            std::env::temp_dir().join(format!("spatialshot_templates_{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        assert!(load(&dir).unwrap().is_empty());
+
+        let saved = update(&dir, |templates| {
+            upsert(templates, template("Describe", "What is this?"))
+        })
+        .unwrap();
+        assert_eq!(saved.id.len(), 16);
+        let renamed = update(&dir, |templates| {
+            upsert(
+                templates,
+                PromptTemplate {
+                    name: "Describe briefly".into(),
+                    ..saved.clone()
+                },
+            )
+        })
+        .unwrap();
+        assert_eq!(find(&dir, &saved.id).unwrap(), renamed);
+
+        let exported = export(&load(&dir).unwrap()).unwrap();
+        let mut other = vec![template("Other", "Hi")];
+        assert_eq!(import(&mut other, &exported).unwrap(), 1);
+        assert_eq!(other.len(), 2);
+        assert_eq!(other[1], renamed);
+
+        // A bad template leaves the library untouched.
+        let bad = r#"[{"name":"A","prompt":"ok"},{"name":"B","prompt":"{{nope}}"}]"#;
+        assert!(import(&mut other, bad).is_err());
+        assert_eq!(other.len(), 2);
+        assert!(import(&mut other, r#"{"version":9,"templates":[]}"#).is_err());
+
+        update(&dir, |templates| remove(templates, &saved.id)).unwrap();
+        assert!(load(&dir).unwrap().is_empty());
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
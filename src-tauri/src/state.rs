@@ -5,20 +5,58 @@
  */
 
 use parking_lot::Mutex;
+use std::collections::{HashMap, HashSet};
 use std::sync::{atomic::AtomicBool, Arc};
 
+use crate::cli::LaunchOverrides;
+use crate::services::clipboard::image::{ClipboardImage, ClipboardOwner};
+use crate::services::clipboard::ClipboardWatcher;
+use crate::services::conversations::ConversationStore;
+use crate::services::llm::ChatRegistry;
+use crate::services::ocr::OcrResult;
+use crate::services::settings::Settings;
+
 pub struct AppState {
     pub image_data: Arc<Mutex<Option<String>>>,
-    pub watcher_running: Arc<AtomicBool>,
+    pub settings: Arc<Mutex<Settings>>,
+    /// Image ids the current image was edited from, oldest first.
+    pub image_history: Arc<Mutex<Vec<String>>>,
+    pub clipboard_watcher: Arc<Mutex<Option<ClipboardWatcher>>>,
+    pub clipboard_owner: Arc<Mutex<ClipboardOwner>>,
+    pub clipboard_ingest: Arc<Mutex<Option<ClipboardWatcher>>>,
+    pub pending_clipboard_image: Arc<Mutex<Option<ClipboardImage>>>,
     pub auth_running: Arc<AtomicBool>,
+    pub auth_cancel: Arc<AtomicBool>,
+    pub chats: Arc<ChatRegistry>,
+    pub conversations: Arc<Mutex<Option<ConversationStore>>>,
+    /// OCR results keyed by image id and language.
+    pub ocr_results: Arc<Mutex<HashMap<(String, String), OcrResult>>>,
+    /// Image ids cleared to leave the machine despite PII findings.
+    pub pii_reviewed: Arc<Mutex<HashSet<String>>>,
+    pub launch_overrides: Arc<Mutex<LaunchOverrides>>,
+    /// Token other launches present to hand off to this process, set while
+    /// it is listening for them.
+    pub instance_token: Arc<Mutex<Option<String>>>,
 }
 
 impl AppState {
     pub fn new() -> Self {
         Self {
             image_data: Arc::new(Mutex::new(None)),
-            watcher_running: Arc::new(AtomicBool::new(false)),
+            settings: Arc::new(Mutex::new(Settings::default())),
+            image_history: Arc::new(Mutex::new(Vec::new())),
+            clipboard_watcher: Arc::new(Mutex::new(None)),
+            clipboard_owner: Arc::new(Mutex::new(ClipboardOwner::default())),
+            clipboard_ingest: Arc::new(Mutex::new(None)),
+            pending_clipboard_image: Arc::new(Mutex::new(None)),
             auth_running: Arc::new(AtomicBool::new(false)),
+            auth_cancel: Arc::new(AtomicBool::new(false)),
+            chats: Arc::new(ChatRegistry::default()),
+            conversations: Arc::new(Mutex::new(None)),
+            ocr_results: Arc::new(Mutex::new(HashMap::new())),
+            pii_reviewed: Arc::new(Mutex::new(HashSet::new())),
+            launch_overrides: Arc::new(Mutex::new(LaunchOverrides::default())),
+            instance_token: Arc::new(Mutex::new(None)),
         }
     }
 }
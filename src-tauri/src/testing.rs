@@ -0,0 +1,96 @@
+/*
+ * @license
+ * Copyright 2025 a7mddra
+ * SPDX-License-Identifier: Apache-2.0
+ */
+
+//! Local HTTP servers standing in for remote APIs in tests.
+
+use serde_json::Value;
+use std::thread::{self, JoinHandle};
+use tiny_http::{Header, Response, Server};
+
+/// What the mock server received.
+pub struct Received {
+    pub method: String,
+    pub url: String,
+    pub headers: Vec<(String, String)>,
+    pub body: Value,
+    /// The body as text, for requests that are not JSON.
+    pub raw: String,
+}
+
+impl Received {
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(field, _)| field.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_str())
+    }
+}
+
+/// A canned reply: status, content type and body.
+pub type MockResponse = (u16, &'static str, &'static str);
+
+/// Serves a single request on an ephemeral port and returns its base URL.
+pub fn mock_server(
+    status: u16,
+    content_type: &'static str,
+    body: &'static str,
+) -> (String, JoinHandle<Received>) {
+    let (base_url, handle) = mock_server_sequence(vec![(status, content_type, body)]);
+    let handle = thread::spawn(move || handle.join().unwrap().remove(0));
+    (base_url, handle)
+}
+
+/// Answers one request per response, in order, and returns what each of
+/// them sent.
+pub fn mock_server_sequence(responses: Vec<MockResponse>) -> (String, JoinHandle<Vec<Received>>) {
+    mock_server_with(|_| {
+        responses
+            .into_iter()
+            .map(|(status, content_type, body)| (status, content_type, body.to_string()))
+            .collect()
+    })
+}
+
+/// Like `mock_server_sequence`, for replies that mention the server's own
+/// base URL, such as an OIDC discovery document.
+pub fn mock_server_with(
+    responses: impl FnOnce(&str) -> Vec<(u16, &'static str, String)>,
+) -> (String, JoinHandle<Vec<Received>>) {
+    let server = Server::http("127.0.0.1:0").unwrap();
+    let base_url = format!("http://{}", server.server_addr().to_ip().unwrap());
+    let responses = responses(&base_url);
+
+    let handle = thread::spawn(move || {
+        let mut results = Vec::new();
+        for (status, content_type, body) in responses {
+            let mut request = server.recv().unwrap();
+            let mut received = Vec::new();
+            request.as_reader().read_to_end(&mut received).unwrap();
+            let received = String::from_utf8_lossy(&received).into_owned();
+
+            results.push(Received {
+                method: request.method().to_string(),
+                url: request.url().to_string(),
+                headers: request
+                    .headers()
+                    .iter()
+                    .map(|h| (h.field.to_string(), h.value.to_string()))
+                    .collect(),
+                body: serde_json::from_str(&received).unwrap_or(Value::Null),
+                raw: received,
+            });
+
+            let header = Header::from_bytes("Content-Type", content_type).unwrap();
+            let response = Response::from_string(body)
+                .with_status_code(status)
+                .with_header(header);
+            request.respond(response).unwrap();
+        }
+        results
+    });
+
+    (base_url, handle)
+}
@@ -12,3 +12,15 @@ pub fn get_app_config_dir(app: &AppHandle) -> PathBuf {
         .app_config_dir()
         .expect("Could not resolve app config dir")
 }
+
+pub fn get_app_cache_dir(app: &AppHandle) -> PathBuf {
+    app.path()
+        .app_cache_dir()
+        .expect("Could not resolve app cache dir")
+}
+
+pub fn get_app_data_dir(app: &AppHandle) -> PathBuf {
+    app.path()
+        .app_data_dir()
+        .expect("Could not resolve app data dir")
+}
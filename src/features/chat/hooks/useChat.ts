@@ -95,27 +95,8 @@ export const useChatEngine = ({
 
       const combinedPrompt = `<sys-prmp>\n${systemPrompt}\n</sys-prmp>\nMSS: ${prompt}`;
 
-      let finalBase64 = imgData.base64;
-      if (imgData.isFilePath) {
-        try {
-          const res = await fetch(imgData.base64);
-          const blob = await res.blob();
-          finalBase64 = await new Promise<string>((resolve, reject) => {
-            const reader = new FileReader();
-            reader.onloadend = () => resolve(reader.result as string);
-            reader.onerror = reject;
-            reader.readAsDataURL(blob);
-          });
-        } catch (e) {
-          console.error("Failed to fetch asset for Gemini", e);
-          throw new Error("Failed to load image file.");
-        }
-      }
-
       await startNewChatStream(
         modelId,
-        finalBase64,
-        imgData.mimeType,
         combinedPrompt,
         (token: string) => {
           fullResponse += token;
@@ -4,10 +4,10 @@
  * SPDX-License-Identifier: Apache-2.0
  */
 
-import { useState, useEffect, useRef } from "react";
+import { useState, useEffect } from "react";
 import { invoke } from "@tauri-apps/api/core";
 import { listen } from "@tauri-apps/api/event";
-import { uploadToImgBB, generateLensUrl } from "../services/lens.google";
+import { commands } from "../../../lib/api/tauri/commands";
 
 export const useLens = (
   startupImage: {
@@ -21,73 +21,30 @@ export const useLens = (
   const [isLensLoading, setIsLensLoading] = useState(false);
   const [waitingForKey, setWaitingForKey] = useState(false);
 
-  const imageRef = useRef(startupImage);
-  useEffect(() => {
-    imageRef.current = startupImage;
-  }, [startupImage]);
-
-  const getRealBase64 = async (img: {
-    base64: string;
-    isFilePath?: boolean;
-  }) => {
-    if (img.isFilePath) {
-      try {
-        const response = await fetch(img.base64);
-        const blob = await response.blob();
-        return await new Promise<string>((resolve, reject) => {
-          const reader = new FileReader();
-          reader.onloadend = () => resolve(reader.result as string);
-          reader.onerror = reject;
-          reader.readAsDataURL(blob);
-        });
-      } catch (e) {
-        console.error("Failed to fetch local asset:", e);
-        throw e;
-      }
-    }
-    return img.base64;
-  };
-
   useEffect(() => {
     if (!startupImage || cachedUrl) return;
 
     const prefetchLensUrl = async () => {
       try {
-        const apiKey = await invoke<string>("get_api_key", {
-          provider: "imgbb",
-        });
-        if (apiKey) {
-          console.log("Background: Uploading to ImgBB...");
-          const realBase64 = await getRealBase64(startupImage);
-          const publicUrl = await uploadToImgBB(realBase64, apiKey);
-          const url = generateLensUrl(publicUrl);
-          setCachedUrl(url);
+        const host = await commands.getImageHost();
+        if (host.ready) {
+          console.log(`Background: Uploading to ${host.kind}...`);
+          setCachedUrl(await commands.reverseSearchUrl());
         }
       } catch (e) {}
     };
     prefetchLensUrl();
   }, [startupImage, cachedUrl]);
 
-  const runLensSearch = async (imgData: string, key: string) => {
+  const runLensSearch = async () => {
     try {
       setIsLensLoading(true);
       if (cachedUrl) {
         await invoke("open_external_url", { url: cachedUrl });
-        setIsLensLoading(false);
         return;
       }
 
-      // Check if imgData looks like a URL (asset://) and fetch if needed, 
-      // but simpler to rely on caller passing real base64. 
-      // However, triggerLens calls this with startupImage.base64 which might be asset URL.
-      // So we should handle it here or ensure caller does.
-      // Let's rely on caller (triggerLens and listener).
-      
-      const publicUrl = await uploadToImgBB(imgData, key);
-      const lensUrl = generateLensUrl(publicUrl);
-
-      setCachedUrl(lensUrl);
-      await invoke("open_external_url", { url: lensUrl });
+      setCachedUrl(await commands.reverseSearch());
     } finally {
       setIsLensLoading(false);
     }
@@ -103,10 +60,9 @@ export const useLens = (
         if (provider === "imgbb") {
           setWaitingForKey(false);
           invoke("close_imgbb_window");
-          if (imageRef.current) {
-             const realBase64 = await getRealBase64(imageRef.current);
-            await runLensSearch(realBase64, key);
-          }
+          // useAuth saves the key as well; the upload must not race it.
+          await invoke("encrypt_and_save", { plaintext: key, provider });
+          await runLensSearch();
         }
       }
     );
@@ -133,12 +89,11 @@ export const useLens = (
 
     try {
       setIsLensLoading(true);
-      const apiKey = await invoke<string>("get_api_key", { provider: "imgbb" });
+      const host = await commands.getImageHost();
 
-      if (apiKey) {
-        const realBase64 = await getRealBase64(startupImage);
-        await runLensSearch(realBase64, apiKey);
-      } else {
+      if (host.ready) {
+        await runLensSearch();
+      } else if (host.kind === "imgbb") {
         await invoke("open_imgbb_window");
         setWaitingForKey(true);
         setIsLensLoading(false);
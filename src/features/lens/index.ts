@@ -6,4 +6,3 @@
 
 export * from "./components/Button/Button";
 export * from "./hooks/useLens";
-export * from "./services/lens.google";
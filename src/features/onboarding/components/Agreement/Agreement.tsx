@@ -5,10 +5,7 @@
  */
 
 import React, { useEffect, useState } from "react";
-import {
-  savePreferences,
-  defaultPreferences,
-} from "../../../../lib/config/preferences";
+import { acceptTerms } from "../../../../lib/config/preferences";
 import ReactMarkdown from "react-markdown";
 import remarkGfm from "remark-gfm";
 import remarkMath from "remark-math";
@@ -50,7 +47,7 @@ export const Agreement: React.FC<AgreementProps> = ({
   const handleNext = async () => {
     setIsSaving(true);
     try {
-      await savePreferences(defaultPreferences);
+      await acceptTerms();
       onNext();
     } catch (e) {
       console.error("Failed to save agreement preferences:", e);
@@ -8,18 +8,19 @@ import { useState, useEffect, useRef } from "react";
 import { invoke } from "@tauri-apps/api/core";
 import { listen } from "@tauri-apps/api/event";
 import { showToast } from "../components/ui/Notifications/Toast";
-import { initializeGemini } from "../lib/api/gemini/client";
 import { useTheme } from "./useTheme";
 import {
   loadPreferences,
   savePreferences,
-  hasPreferencesFile,
+  hasAcceptedTerms,
 } from "../lib/config/preferences";
 import {
   DEFAULT_MODEL,
   DEFAULT_PROMPT,
   DEFAULT_THEME,
 } from "../lib/utils/constants";
+import { commands } from "../lib/api/tauri/commands";
+import { LaunchOverrides } from "../lib/types/tauri.types";
 
 export const useSystemSync = (onToggleSettings: () => void) => {
   const { theme, toggleTheme, setTheme } = useTheme();
@@ -54,7 +55,7 @@ export const useSystemSync = (onToggleSettings: () => void) => {
   const [hasAgreed, setHasAgreed] = useState<boolean | null>(null);
   useEffect(() => {
     const init = async () => {
-      const agreed = await hasPreferencesFile();
+      const agreed = await hasAcceptedTerms();
       setHasAgreed(agreed);
 
       if (agreed) {
@@ -75,21 +76,39 @@ export const useSystemSync = (onToggleSettings: () => void) => {
       } else {
         setTheme(DEFAULT_THEME as "light" | "dark");
       }
+
+      applyLaunchOverrides(await commands.getLaunchOverrides());
     };
     init();
+
+    const unlisten = listen<LaunchOverrides>("launch-overrides", (event) =>
+      applyLaunchOverrides(event.payload)
+    );
+    return () => {
+      unlisten.then((f) => f());
+    };
   }, []);
 
+  // Command-line overrides last for the session and are not saved.
+  const applyLaunchOverrides = (overrides: LaunchOverrides) => {
+    if (overrides.prompt) {
+      setActivePrompt(overrides.prompt);
+    }
+    if (overrides.model) {
+      setSessionModel(overrides.model);
+    }
+  };
+
   useEffect(() => {
     let unlisteners: (() => void)[] = [];
 
     const setupIpc = async () => {
       try {
-        const apiKey = await invoke<string>("get_api_key", {
-          provider: "gemini",
+        const hasGeminiKey = await invoke<boolean>("check_file_exists", {
+          filename: "gemini_key.json",
         });
-        if (apiKey) {
-          setApiKey(apiKey);
-          initializeGemini(apiKey);
+        if (hasGeminiKey) {
+          setApiKey("gemini");
         }
 
         const userData = await invoke<any>("get_user_data");
@@ -5,6 +5,27 @@
  */
 
 import { invoke } from "@tauri-apps/api/core";
+import {
+  CleanupReport,
+  Conversation,
+  ConversationSummary,
+  EditedImage,
+  ExportFormat,
+  Extraction,
+  ImageEdit,
+  ImageHostSettings,
+  ImageHostSummary,
+  ImageVersion,
+  LaunchOverrides,
+  OcrResult,
+  PiiScan,
+  PromptTemplate,
+  RenderedPrompt,
+  SearchEngine,
+  Settings,
+  SettingsPatch,
+  Upload,
+} from "../../types/tauri.types";
 
 export const commands = {
   // Image Processing
@@ -13,21 +34,150 @@ export const commands = {
   processImageBytes: (bytes: number[]) =>
     invoke<string>("process_image_bytes", { bytes }),
   getInitialImage: () => invoke<string | null>("get_initial_image"),
+  applyImageEdits: (edits: ImageEdit[], sourceId?: string) =>
+    invoke<EditedImage>("apply_image_edits", { edits, sourceId }),
+  undoImageEdit: () => invoke<EditedImage | null>("undo_image_edit"),
+  getImageVersion: (id: string) =>
+    invoke<ImageVersion | null>("get_image_version", { id }),
+  runOcr: (language?: string) => invoke<OcrResult>("run_ocr", { language }),
+  scanPii: () => invoke<PiiScan>("scan_pii"),
+  redactPii: () => invoke<EditedImage | null>("redact_pii"),
+  approvePiiReview: (imageId: string) =>
+    invoke("approve_pii_review", { imageId }),
+
+  // Reverse Search
+  reverseSearch: (imageId?: string, engine?: string) =>
+    invoke<string>("reverse_search", { imageId, engine }),
+  reverseSearchUrl: (imageId?: string, engine?: string) =>
+    invoke<string>("reverse_search_url", { imageId, engine }),
+  listSearchEngines: () => invoke<SearchEngine[]>("list_search_engines"),
+  getImageHost: () => invoke<ImageHostSummary>("get_image_host"),
+  setImageHost: (settings: ImageHostSettings) =>
+    invoke("set_image_host", { settings }),
+  listUploads: () => invoke<Upload[]>("list_uploads"),
+  deleteUpload: (id: number) => invoke("delete_upload", { id }),
+  deleteUploadsOlderThan: (days: number) =>
+    invoke<CleanupReport>("delete_uploads_older_than", { days }),
 
   // Auth & Keys
-  getApiKey: (provider: "gemini" | "imgbb") =>
-    invoke<string>("get_api_key", { provider }),
   resetApiKey: () => invoke("reset_api_key"),
-  startGoogleAuth: () => invoke("start_google_auth"),
+  startGoogleAuth: (timeoutSecs?: number) =>
+    invoke("start_google_auth", { timeoutSecs }),
+  startAuth: (provider: string, timeoutSecs?: number) =>
+    invoke("start_auth", { provider, timeoutSecs }),
+  cancelGoogleAuth: () => invoke("cancel_google_auth"),
+  listAuthProviders: () =>
+    invoke<{ id: string; name: string; issuer: string }[]>(
+      "list_auth_providers"
+    ),
+  getAccessToken: () => invoke<string>("get_access_token"),
   logout: () => invoke("logout"),
   getUserData: () => invoke<any>("get_user_data"),
 
+  // Chat
+  chatStart: (
+    model: string,
+    prompt: string,
+    systemInstruction?: string,
+    provider?: string,
+    ocrContext?: boolean
+  ) =>
+    invoke<{ sessionId: string; text: string }>("chat_start", {
+      provider,
+      model,
+      prompt,
+      systemInstruction,
+      ocrContext,
+    }),
+  chatSend: (sessionId: string, text: string) =>
+    invoke<{ sessionId: string; text: string }>("chat_send", {
+      sessionId,
+      text,
+    }),
+  chatCancel: (sessionId: string) => invoke("chat_cancel", { sessionId }),
+  extractStructured: <T = unknown>(
+    imageId: string,
+    schema: object,
+    model: string,
+    instructions?: string,
+    provider?: string,
+    maxAttempts?: number
+  ) =>
+    invoke<Extraction<T>>("extract_structured", {
+      imageId,
+      schema,
+      model,
+      instructions,
+      provider,
+      maxAttempts,
+    }),
+
+  // Model Providers
+  listModelProviders: () =>
+    invoke<
+      {
+        id: string;
+        kind: "gemini" | "openai" | "ollama";
+        baseUrl: string;
+        hasKey: boolean;
+      }[]
+    >("list_model_providers"),
+  saveModelProvider: (
+    id: string,
+    settings: {
+      kind: "gemini" | "openai" | "ollama";
+      baseUrl?: string;
+      apiKey?: string;
+    }
+  ) => invoke("save_model_provider", { id, settings }),
+  removeModelProvider: (id: string) => invoke("remove_model_provider", { id }),
+  listModels: (provider: string) =>
+    invoke<{ id: string; name: string }[]>("list_models", { provider }),
+
+  // Conversations
+  listConversations: (limit?: number, offset?: number) =>
+    invoke<ConversationSummary[]>("list_conversations", { limit, offset }),
+  searchConversations: (query: string, limit?: number) =>
+    invoke<ConversationSummary[]>("search_conversations", { query, limit }),
+  loadConversation: (id: string) =>
+    invoke<Conversation>("load_conversation", { id }),
+  renameConversation: (id: string, title: string) =>
+    invoke("rename_conversation", { id, title }),
+  deleteConversation: (id: string) => invoke("delete_conversation", { id }),
+  exportConversation: (id: string, format: ExportFormat, path?: string) =>
+    invoke<string | null>("export_conversation", { id, format, path }),
+
   // Window Mgmt
   openImgbbWindow: () => invoke("open_imgbb_window"),
   closeImgbbWindow: () => invoke("close_imgbb_window"),
   resizeWindow: (width: number, height: number, show: boolean = true) =>
     invoke("resize_window", { width, height, show }),
 
+  // Settings
+  getSettings: () => invoke<Settings>("get_settings"),
+  updateSettings: (patch: SettingsPatch) =>
+    invoke<Settings>("update_settings", { patch }),
+  getLaunchOverrides: () => invoke<LaunchOverrides>("get_launch_overrides"),
+
+  // Prompt Templates
+  listPromptTemplates: () => invoke<PromptTemplate[]>("list_prompt_templates"),
+  savePromptTemplate: (template: PromptTemplate) =>
+    invoke<PromptTemplate>("save_prompt_template", { template }),
+  deletePromptTemplate: (id: string) =>
+    invoke("delete_prompt_template", { id }),
+  exportPromptTemplates: (path?: string) =>
+    invoke<string | null>("export_prompt_templates", { path }),
+  importPromptTemplates: (path?: string) =>
+    invoke<PromptTemplate[] | null>("import_prompt_templates", { path }),
+  renderPromptTemplate: (id: string) =>
+    invoke<RenderedPrompt>("render_prompt_template", { id }),
+  chatStartTemplate: (templateId: string, provider?: string, model?: string) =>
+    invoke<{ sessionId: string; text: string }>("chat_start_template", {
+      templateId,
+      provider,
+      model,
+    }),
+
   // Utils
   openExternalUrl: (url: string) => invoke("open_external_url", { url }),
   clearCache: () => invoke("clear_cache"),
@@ -35,4 +185,16 @@ export const commands = {
   // Clipboard Watcher
   startClipboardWatcher: () => invoke("start_clipboard_watcher"),
   stopClipboardWatcher: () => invoke("stop_clipboard_watcher"),
+  setClipboardAutoIngest: (enabled: boolean) =>
+    invoke("set_clipboard_auto_ingest", { enabled }),
+  ingestClipboardImage: (hash: string) =>
+    invoke("ingest_clipboard_image", { hash }),
+  pasteImageFromClipboard: () => invoke<string>("paste_image_from_clipboard"),
+  copyImageToClipboard: () => invoke("copy_image_to_clipboard"),
+  copyRegionToClipboard: (
+    x: number,
+    y: number,
+    width: number,
+    height: number
+  ) => invoke("copy_region_to_clipboard", { x, y, width, height }),
 };
@@ -4,19 +4,12 @@
  * spdx-license-identifier: apache-2.0
  */
 
-import {
-  BaseDirectory,
-  exists,
-  readTextFile,
-  writeTextFile,
-  mkdir,
-} from "@tauri-apps/plugin-fs";
+import { commands } from "../api/tauri/commands";
 
 import {
   DEFAULT_MODEL,
   DEFAULT_PROMPT,
   DEFAULT_THEME,
-  PREFERENCES_FILE_NAME,
 } from "../utils/constants";
 
 export interface UserPreferences {
@@ -31,30 +24,23 @@ export const defaultPreferences: UserPreferences = {
   prompt: DEFAULT_PROMPT,
 };
 
-export async function hasPreferencesFile(): Promise<boolean> {
+export async function hasAcceptedTerms(): Promise<boolean> {
   try {
-    return await exists(PREFERENCES_FILE_NAME, {
-      baseDir: BaseDirectory.AppConfig,
-    });
+    return (await commands.getSettings()).acceptedTerms;
   } catch (error) {
-    console.warn("Preferences check failed (fresh install?):", error);
+    console.warn("Terms check failed:", error);
     return false;
   }
 }
 
+export async function acceptTerms(): Promise<void> {
+  await commands.updateSettings({ acceptedTerms: true });
+}
+
 export async function loadPreferences(): Promise<UserPreferences> {
   try {
-    const fileExists = await hasPreferencesFile();
-    if (!fileExists) {
-      return defaultPreferences;
-    }
-
-    const content = await readTextFile(PREFERENCES_FILE_NAME, {
-      baseDir: BaseDirectory.AppConfig,
-    });
-    const parsed = JSON.parse(content);
-
-    return { ...defaultPreferences, ...parsed };
+    const { model, theme, prompt } = await commands.getSettings();
+    return { model, theme, prompt };
   } catch (error) {
     console.error("Failed to load preferences:", error);
     return defaultPreferences;
@@ -63,15 +49,9 @@ export async function loadPreferences(): Promise<UserPreferences> {
 
 export async function savePreferences(prefs: UserPreferences): Promise<void> {
   try {
-    await mkdir("", { baseDir: BaseDirectory.AppConfig, recursive: true });
-    await writeTextFile(PREFERENCES_FILE_NAME, JSON.stringify(prefs, null, 2), {
-      baseDir: BaseDirectory.AppConfig,
-    });
+    await commands.updateSettings(prefs);
   } catch (error) {
     console.error("Failed to save preferences:", error);
-    if (typeof error === "object" && error !== null) {
-      console.error("Error details:", JSON.stringify(error));
-    }
     throw error;
   }
 }
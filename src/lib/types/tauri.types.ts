@@ -13,4 +13,242 @@ export interface UserData {
   name: string;
   email: string;
   avatar: string;
+  provider?: string;
+  issuer?: string;
+  subject?: string;
+}
+
+export interface ConversationSummary {
+  id: string;
+  title: string;
+  provider: string;
+  model: string;
+  createdAt: number;
+  updatedAt: number;
+  messageCount: number;
+}
+
+export interface StoredMessage {
+  role: "user" | "model";
+  text: string;
+  imageIds: string[];
+  createdAt: number;
+}
+
+export interface Conversation {
+  id: string;
+  title: string;
+  provider: string;
+  model: string;
+  prompt: string;
+  systemInstruction: string | null;
+  createdAt: number;
+  updatedAt: number;
+  messages: StoredMessage[];
+}
+
+export type ExportFormat = "json" | "markdown" | "html" | "pdf";
+
+export interface OcrWord {
+  text: string;
+  confidence: number;
+  bounds: { x: number; y: number; width: number; height: number };
+}
+
+export interface OcrBlock {
+  text: string;
+  confidence: number;
+  bounds: { x: number; y: number; width: number; height: number };
+  words: OcrWord[];
+}
+
+export interface OcrResult {
+  imageId: string;
+  language: string;
+  text: string;
+  blocks: OcrBlock[];
+}
+
+export interface PreprocessOptions {
+  maxEdge: number | null;
+  format: "original" | "jpeg" | "webp";
+  /** JPEG or WebP quality, 1 to 100; null uses the default. */
+  quality: number | null;
+  grayscale: boolean;
+  trimBorders: boolean;
+}
+
+export interface Point {
+  x: number;
+  y: number;
+}
+
+export interface Rect {
+  x: number;
+  y: number;
+  width: number;
+  height: number;
+}
+
+/** `#rrggbb` or `#rrggbbaa`. */
+export type Color = string;
+
+export type RedactMethod =
+  | { kind: "fill"; color?: Color }
+  | { kind: "pixelate"; blockSize?: number }
+  | { kind: "blur"; radius?: number };
+
+export type ImageEdit =
+  | { type: "crop"; rect: Rect }
+  | { type: "redact"; rect: Rect; method?: RedactMethod }
+  | { type: "arrow"; from: Point; to: Point; color?: Color; width?: number }
+  | { type: "box"; rect: Rect; color?: Color; width?: number }
+  | {
+      type: "label";
+      position: Point;
+      text: string;
+      color?: Color;
+      size?: number;
+      background?: Color;
+    }
+  | { type: "highlight"; rect: Rect; color?: Color };
+
+export interface EditedImage {
+  id: string;
+  sourceId: string | null;
+  dataUrl: string;
+  canUndo: boolean;
+}
+
+export interface ImageVersion {
+  id: string;
+  sourceId: string;
+  edits: ImageEdit[];
+  createdAt: number;
+}
+
+export type PiiKind = "email" | "phone" | "apiKey" | "ipAddress" | "creditCard";
+
+export type PiiMode = "off" | "warn" | "review" | "redact";
+
+export interface PiiPolicy {
+  mode: PiiMode;
+  kinds: PiiKind[];
+  language: string;
+}
+
+/** Data extracted by `extractStructured`, valid against its schema. */
+export interface Extraction<T = unknown> {
+  data: T;
+  attempts: number;
+}
+
+export interface PiiFinding {
+  kind: PiiKind;
+  text: string;
+  bounds: { x: number; y: number; width: number; height: number };
+  detail: string | null;
+}
+
+export interface PiiScan {
+  imageId: string;
+  findings: PiiFinding[];
+}
+
+export type ImageHostConfig =
+  | { kind: "imgbb"; apiKey?: string }
+  | {
+      kind: "s3";
+      endpoint: string;
+      region: string;
+      bucket: string;
+      accessKeyId: string;
+      secretAccessKey: string;
+      pathStyle?: boolean;
+      prefix?: string;
+    }
+  | { kind: "http"; url: string; token?: string };
+
+export interface ImageHostSettings {
+  host: ImageHostConfig;
+  expirationSecs?: number | null;
+}
+
+export interface ImageHostSummary {
+  kind: "imgbb" | "s3" | "http";
+  endpoint: string | null;
+  expirationSecs: number | null;
+  ready: boolean;
+}
+
+export interface Upload {
+  id: number;
+  imageId: string;
+  host: string;
+  url: string;
+  deleteUrl: string | null;
+  expiresAt: number | null;
+  createdAt: number;
+}
+
+export interface CleanupReport {
+  deleted: number;
+  errors: string[];
+}
+
+export interface SearchEngine {
+  id: string;
+  name: string;
+}
+
+export interface ReverseSearchOptions {
+  engine?: string | null;
+  language?: string | null;
+  region?: string | null;
+}
+
+export interface Settings {
+  version: number;
+  acceptedTerms: boolean;
+  model: string;
+  theme: "dark" | "light";
+  prompt: string;
+  hotkey: string;
+  capture: { muteAudio: boolean };
+  imagePreprocessing: PreprocessOptions;
+  pii: PiiPolicy;
+  reverseSearch: ReverseSearchOptions;
+}
+
+export type SettingsPatch = {
+  [K in keyof Omit<Settings, "version">]?: Settings[K] extends object
+    ? Partial<Settings[K]>
+    : Settings[K];
+};
+
+/** Prompt and model given on the command line, for this session only. */
+export interface LaunchOverrides {
+  prompt?: string | null;
+  model?: string | null;
+}
+
+/**
+ * A saved prompt. `prompt` and `systemInstruction` may use `{{ocr_text}}`,
+ * `{{app_name}}`, `{{date}}` and `{{clipboard}}`.
+ */
+export interface PromptTemplate {
+  /** Empty for a template that has not been saved yet. */
+  id: string;
+  name: string;
+  prompt: string;
+  systemInstruction?: string | null;
+  provider?: string | null;
+  model?: string | null;
+}
+
+export interface RenderedPrompt {
+  prompt: string;
+  systemInstruction: string | null;
+  provider: string | null;
+  model: string | null;
 }
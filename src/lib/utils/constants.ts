@@ -11,4 +11,3 @@ export const DEFAULT_THEME = "dark";
 
 export const DEFAULT_PROMPT =
   "analyze this image and explain it or discuss fixes about the issue it discribes.";
-export const PREFERENCES_FILE_NAME = "preferences.json";